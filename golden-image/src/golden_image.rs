@@ -0,0 +1,110 @@
+use image::RgbaImage;
+use std::path::PathBuf;
+use thiserror::Error;
+
+use crate::{ImageDiff, Tolerance};
+
+#[derive(Debug, Error)]
+pub enum GoldenImageError {
+    #[error(
+        "Reference image {0} does not exist, run the tests with GOLDEN_IMAGE_BLESS=1 to create it"
+    )]
+    MissingReferenceError(PathBuf),
+    #[error("Image {name} is {actual:?} but the reference is {expected:?}")]
+    SizeMismatchError {
+        name: String,
+        expected: (u32, u32),
+        actual: (u32, u32),
+    },
+    #[error("Image {name} differs from the reference in {failing_pixels}/{total_pixels} pixels (max delta E {max_delta_e:.2}), see {diff}")]
+    MismatchError {
+        name: String,
+        failing_pixels: usize,
+        total_pixels: usize,
+        max_delta_e: f32,
+        diff: PathBuf,
+    },
+    #[error("Failed to read or write golden image: {0}")]
+    ImageError(#[from] image::ImageError),
+    #[error("Failed to create golden image directory: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+// Compares rendered images to reference PNGs in `reference_dir`.
+//
+// On failure `<reference_dir>/failures/<name>.actual.png` and `<name>.diff.png` are written.
+// With `GOLDEN_IMAGE_BLESS=1` the references are overwritten by the rendered images instead,
+// without it a missing reference fails like a mismatch.
+#[derive(Debug, Clone)]
+pub struct GoldenImage {
+    reference_dir: PathBuf,
+    tolerance: Tolerance,
+}
+impl GoldenImage {
+    pub fn new(reference_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            reference_dir: reference_dir.into(),
+            tolerance: Tolerance::default(),
+        }
+    }
+
+    pub fn with_tolerance(self, tolerance: Tolerance) -> Self {
+        Self { tolerance, ..self }
+    }
+
+    fn bless() -> bool {
+        std::env::var("GOLDEN_IMAGE_BLESS")
+            .map(|bless| bless != "0")
+            .unwrap_or(false)
+    }
+
+    fn write(&self, path: &PathBuf, image: &RgbaImage) -> Result<(), GoldenImageError> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        image.save(path)?;
+        Ok(())
+    }
+
+    pub fn check(&self, name: &str, actual: &RgbaImage) -> Result<(), GoldenImageError> {
+        let reference = self.reference_dir.join(format!("{}.png", name));
+        let failures = self.reference_dir.join("failures");
+
+        if Self::bless() {
+            log::warn!("Recording golden image {}", reference.display());
+            return self.write(&reference, actual);
+        }
+        if !reference.exists() {
+            return Err(GoldenImageError::MissingReferenceError(reference));
+        }
+
+        let expected = image::open(&reference)?.to_rgba8();
+        if expected.dimensions() != actual.dimensions() {
+            self.write(&failures.join(format!("{}.actual.png", name)), actual)?;
+            return Err(GoldenImageError::SizeMismatchError {
+                name: name.into(),
+                expected: expected.dimensions(),
+                actual: actual.dimensions(),
+            });
+        }
+
+        let diff = ImageDiff::compare(&expected, actual, &self.tolerance);
+        if diff.passed(&self.tolerance) {
+            return Ok(());
+        }
+        let diff_path = failures.join(format!("{}.diff.png", name));
+        self.write(&failures.join(format!("{}.actual.png", name)), actual)?;
+        self.write(&diff_path, &diff.image)?;
+        Err(GoldenImageError::MismatchError {
+            name: name.into(),
+            failing_pixels: diff.failing_pixels,
+            total_pixels: diff.total_pixels,
+            max_delta_e: diff.max_delta_e,
+            diff: diff_path,
+        })
+    }
+
+    pub fn assert(&self, name: &str, actual: &RgbaImage) {
+        self.check(name, actual).unwrap_or_else(|e| panic!("{}", e));
+    }
+}
@@ -0,0 +1,129 @@
+use image::{Rgba, RgbaImage};
+
+// How far a rendered image may drift from its reference.
+// Pixels are compared by CIE76 color difference, where about 2.3 is a just noticeable difference.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    pub max_delta_e: f32,
+    // Ratio of pixels allowed to exceed `max_delta_e`, e.g. for rasterization differences on edges.
+    pub max_failing_ratio: f32,
+}
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            max_delta_e: 2.3,
+            max_failing_ratio: 0.001,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ImageDiff {
+    pub failing_pixels: usize,
+    pub total_pixels: usize,
+    pub max_delta_e: f32,
+    // Failing pixels in red over a faded copy of the reference.
+    pub image: RgbaImage,
+}
+impl ImageDiff {
+    pub fn compare(expected: &RgbaImage, actual: &RgbaImage, tolerance: &Tolerance) -> Self {
+        assert_eq!(expected.dimensions(), actual.dimensions());
+
+        let mut failing_pixels = 0;
+        let mut max_delta_e = 0.0_f32;
+        let image = RgbaImage::from_fn(expected.width(), expected.height(), |x, y| {
+            let expected = expected.get_pixel(x, y);
+            let delta_e = delta_e(expected, actual.get_pixel(x, y));
+            max_delta_e = max_delta_e.max(delta_e);
+            if delta_e > tolerance.max_delta_e {
+                failing_pixels += 1;
+                Rgba([255, 0, 0, 255])
+            } else {
+                let luma = (0.2126 * expected[0] as f32
+                    + 0.7152 * expected[1] as f32
+                    + 0.0722 * expected[2] as f32)
+                    / 4.0;
+                Rgba([luma as u8, luma as u8, luma as u8, 255])
+            }
+        });
+
+        Self {
+            failing_pixels,
+            total_pixels: (expected.width() * expected.height()) as usize,
+            max_delta_e,
+            image,
+        }
+    }
+
+    pub fn passed(&self, tolerance: &Tolerance) -> bool {
+        self.failing_pixels as f32 <= tolerance.max_failing_ratio * self.total_pixels as f32
+    }
+}
+
+fn srgb_to_linear(c: u8) -> f32 {
+    let c = c as f32 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+// sRGB to CIELAB (D65), alpha is treated as an extra lightness channel
+// so that transparent and opaque pixels of the same color differ.
+fn lab(pixel: &Rgba<u8>) -> [f32; 4] {
+    let [r, g, b] = [pixel[0], pixel[1], pixel[2]].map(srgb_to_linear);
+    let x = (0.4124 * r + 0.3576 * g + 0.1805 * b) / 0.95047;
+    let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    let z = (0.0193 * r + 0.1192 * g + 0.9505 * b) / 1.08883;
+    let f = |t: f32| {
+        if t > 0.008856 {
+            t.cbrt()
+        } else {
+            7.787 * t + 16.0 / 116.0
+        }
+    };
+    let (fx, fy, fz) = (f(x), f(y), f(z));
+    [
+        116.0 * fy - 16.0,
+        500.0 * (fx - fy),
+        200.0 * (fy - fz),
+        100.0 * pixel[3] as f32 / 255.0,
+    ]
+}
+
+fn delta_e(a: &Rgba<u8>, b: &Rgba<u8>) -> f32 {
+    lab(a)
+        .iter()
+        .zip(lab(b).iter())
+        .map(|(a, b)| (a - b) * (a - b))
+        .sum::<f32>()
+        .sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_pass_small_color_drift() {
+        let expected = RgbaImage::from_pixel(8, 8, Rgba([100, 150, 200, 255]));
+        let actual = RgbaImage::from_pixel(8, 8, Rgba([101, 150, 199, 255]));
+        let tolerance = Tolerance::default();
+        let diff = ImageDiff::compare(&expected, &actual, &tolerance);
+        assert!(diff.passed(&tolerance));
+        assert_eq!(diff.failing_pixels, 0);
+    }
+
+    #[test]
+    fn it_should_fail_visible_differences() {
+        let expected = RgbaImage::from_pixel(8, 8, Rgba([0, 0, 0, 255]));
+        let mut actual = expected.clone();
+        actual.put_pixel(3, 3, Rgba([255, 255, 255, 255]));
+        let tolerance = Tolerance::default();
+        let diff = ImageDiff::compare(&expected, &actual, &tolerance);
+        assert!(!diff.passed(&tolerance));
+        assert_eq!(diff.failing_pixels, 1);
+        assert_eq!(diff.image.get_pixel(3, 3), &Rgba([255, 0, 0, 255]));
+    }
+}
@@ -0,0 +1,10 @@
+mod golden_image;
+mod image_diff;
+mod offscreen_renderer;
+mod texture_readback;
+
+pub use golden_image::{GoldenImage, GoldenImageError};
+pub use image::RgbaImage;
+pub use image_diff::{ImageDiff, Tolerance};
+pub use offscreen_renderer::OffscreenRenderer;
+pub use texture_readback::TextureReadback;
@@ -0,0 +1,110 @@
+use image::RgbaImage;
+
+use crate::TextureReadback;
+
+// Device on a software adapter (lavapipe, llvmpipe, WARP) rendering into offscreen textures,
+// so golden images are reproducible on machines without a GPU.
+#[derive(Debug)]
+pub struct OffscreenRenderer {
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+    pub format: wgpu::TextureFormat,
+}
+impl OffscreenRenderer {
+    pub fn new() -> Self {
+        futures::executor::block_on(async {
+            let backends = wgpu::util::backend_bits_from_env().unwrap_or(wgpu::Backends::all());
+            let instance = wgpu::Instance::new(backends);
+            let adapter = match instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: wgpu::PowerPreference::default(),
+                    compatible_surface: None,
+                    force_fallback_adapter: true,
+                })
+                .await
+            {
+                Some(adapter) => Some(adapter),
+                None => {
+                    log::warn!("No software adapter found, golden images may differ.");
+                    instance
+                        .request_adapter(&wgpu::RequestAdapterOptions {
+                            power_preference: wgpu::PowerPreference::default(),
+                            compatible_surface: None,
+                            force_fallback_adapter: false,
+                        })
+                        .await
+                }
+            }
+            .unwrap_or_else(|| panic!("Failed to request adapter."));
+
+            let (device, queue) = adapter
+                .request_device(
+                    &wgpu::DeviceDescriptor {
+                        features: wgpu::Features::empty(),
+                        limits: wgpu::Limits::downlevel_defaults(),
+                        label: None,
+                    },
+                    None,
+                )
+                .await
+                .unwrap_or_else(|_| panic!("Failed to request device."));
+
+            Self {
+                device,
+                queue,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+            }
+        })
+    }
+
+    pub fn config(&self, width: u32, height: u32) -> wgpu::SurfaceConfiguration {
+        wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: self.format,
+            width,
+            height,
+            present_mode: wgpu::PresentMode::Fifo,
+        }
+    }
+
+    // Call `render` with a view of a fresh texture and read the texture back.
+    // `render` submits its own command buffers to `self.queue`.
+    pub fn render(
+        &self,
+        width: u32,
+        height: u32,
+        render: impl FnOnce(&wgpu::TextureView),
+    ) -> RgbaImage {
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("golden_image_texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        });
+        render(&texture.create_view(&wgpu::TextureViewDescriptor::default()));
+
+        let readback = TextureReadback::new(&self.device, width, height);
+        readback.copy(&self.device, &self.queue, &texture);
+        let map_future = readback.map();
+        self.device.poll(wgpu::Maintain::Wait);
+        futures::executor::block_on(map_future)
+            .unwrap_or_else(|_| panic!("Failed to map golden image buffer."));
+        let rgba = readback.read();
+
+        RgbaImage::from_raw(width, height, rgba)
+            .unwrap_or_else(|| panic!("Failed to create golden image."))
+    }
+}
+impl Default for OffscreenRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,86 @@
+use std::future::Future;
+
+// Buffer a texture is copied into to read it back on the CPU.
+// Rows are padded to `COPY_BYTES_PER_ROW_ALIGNMENT` in the buffer and tightly packed RGBA8 once read.
+#[derive(Debug)]
+pub struct TextureReadback {
+    buffer: wgpu::Buffer,
+    width: u32,
+    height: u32,
+    padded_bytes_per_row: u32,
+}
+impl TextureReadback {
+    pub fn new(device: &wgpu::Device, width: u32, height: u32) -> Self {
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_bytes_per_row = (4 * width).div_ceil(align) * align;
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("texture_readback_buffer"),
+            size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            buffer,
+            width,
+            height,
+            padded_bytes_per_row,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    // Copy `texture`, which has the size of the readback, into the buffer.
+    pub fn copy(&self, device: &wgpu::Device, queue: &wgpu::Queue, texture: &wgpu::Texture) {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Texture Readback Encoder"),
+        });
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &self.buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: std::num::NonZeroU32::new(self.padded_bytes_per_row),
+                    rows_per_image: std::num::NonZeroU32::new(self.height),
+                },
+            },
+            wgpu::Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: 1,
+            },
+        );
+        queue.submit(std::iter::once(encoder.finish()));
+    }
+
+    // Resolves once the copied texture can be read, the device has to be polled meanwhile.
+    pub fn map(&self) -> impl Future<Output = Result<(), wgpu::BufferAsyncError>> + Send {
+        self.buffer.slice(..).map_async(wgpu::MapMode::Read)
+    }
+
+    // Read the mapped buffer without the row padding and unmap it for the next copy.
+    pub fn read(&self) -> Vec<u8> {
+        let row_bytes = 4 * self.width as usize;
+        let mut rgba = Vec::with_capacity(row_bytes * self.height as usize);
+        {
+            let mapped = self.buffer.slice(..).get_mapped_range();
+            for row in mapped.chunks(self.padded_bytes_per_row as usize) {
+                rgba.extend_from_slice(&row[..row_bytes]);
+            }
+        }
+        self.buffer.unmap();
+        rgba
+    }
+}
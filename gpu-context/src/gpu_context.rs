@@ -0,0 +1,409 @@
+use raw_window_handle::HasRawWindowHandle;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum GpuContextError {
+    #[error("No compatible adapter found, WebGPU may not be supported by this browser")]
+    AdapterError,
+    #[error("Failed to request device: {0}")]
+    DeviceError(wgpu::RequestDeviceError),
+}
+
+// Requested adapter, device and surface settings.
+// Unsupported values fall back to supported ones with a warning instead of failing.
+#[derive(Debug, Clone)]
+pub struct GpuContextDescriptor {
+    // Only used when the context creates the instance itself.
+    pub backends: wgpu::Backends,
+    pub power_preference: wgpu::PowerPreference,
+    // Features the adapter lacks are dropped.
+    pub features: wgpu::Features,
+    // Falls back to the downlevel defaults when the adapter cannot satisfy them.
+    pub limits: wgpu::Limits,
+    // Falls back to `Fifo` when the surface does not support it.
+    pub present_mode: wgpu::PresentMode,
+    // Surface format, `None` uses the preferred format of the surface.
+    pub format: Option<wgpu::TextureFormat>,
+}
+impl Default for GpuContextDescriptor {
+    fn default() -> Self {
+        Self {
+            backends: wgpu::Backends::all(),
+            power_preference: wgpu::PowerPreference::default(),
+            features: wgpu::Features::empty(),
+            limits: wgpu::Limits::default(),
+            present_mode: wgpu::PresentMode::Fifo,
+            format: None,
+        }
+    }
+}
+
+// Adapter, device, queue and the optional surface they render to.
+// Without a surface the config still describes the frames, e.g. for offscreen targets.
+#[derive(Debug)]
+pub struct GpuContext {
+    pub instance: wgpu::Instance,
+    pub adapter: wgpu::Adapter,
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+    pub config: wgpu::SurfaceConfiguration,
+    surface: Option<wgpu::Surface>,
+    // Set by the uncaptured error handler when the device is lost.
+    lost: Arc<AtomicBool>,
+}
+impl GpuContext {
+    // Format of contexts without a surface, frames are read back as RGBA.
+    pub const OFFSCREEN_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+    pub async fn new(
+        instance: wgpu::Instance,
+        surface: Option<wgpu::Surface>,
+        width: u32,
+        height: u32,
+        descriptor: &GpuContextDescriptor,
+    ) -> Result<Self, GpuContextError> {
+        let adapter = Self::request_adapter(&instance, surface.as_ref(), descriptor).await?;
+        let (device, queue) = Self::request_device(&adapter, descriptor).await?;
+        let lost = Arc::new(AtomicBool::new(false));
+        Self::handle_errors(&device, lost.clone());
+
+        let preferred_format = Self::surface_preferred_format(surface.as_ref(), &adapter);
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: negotiate_format(descriptor.format, preferred_format),
+            width,
+            height,
+            present_mode: descriptor.present_mode,
+        };
+
+        let context = Self {
+            instance,
+            adapter,
+            device,
+            queue,
+            config,
+            surface,
+            lost,
+        };
+        context.configure();
+        Ok(context)
+    }
+
+    // Request a new adapter and device after the device was lost.
+    // Every resource of the old device is invalid, the owners have to recreate them.
+    pub async fn recreate_device(
+        &mut self,
+        descriptor: &GpuContextDescriptor,
+    ) -> Result<(), GpuContextError> {
+        let adapter =
+            Self::request_adapter(&self.instance, self.surface.as_ref(), descriptor).await?;
+        let (device, queue) = Self::request_device(&adapter, descriptor).await?;
+        let lost = Arc::new(AtomicBool::new(false));
+        Self::handle_errors(&device, lost.clone());
+
+        self.adapter = adapter;
+        self.device = device;
+        self.queue = queue;
+        self.lost = lost;
+        self.configure();
+        Ok(())
+    }
+
+    // wgpu has no device lost callback yet, so loss is detected from the uncaptured errors.
+    // Other errors are logged, the frame they occur in may render incompletely.
+    fn handle_errors(device: &wgpu::Device, lost: Arc<AtomicBool>) {
+        device.on_uncaptured_error(move |error| {
+            if is_device_lost(&error) {
+                log::error!("GPU device lost: {}", error);
+                lost.store(true, Ordering::SeqCst);
+            } else {
+                log::error!("wgpu error: {}", error);
+            }
+        });
+    }
+
+    pub fn is_lost(&self) -> bool {
+        self.lost.load(Ordering::SeqCst)
+    }
+
+    // Create the instance and a surface for a window, e.g. a winit window or a canvas.
+    pub async fn from_window<W: HasRawWindowHandle>(
+        window: &W,
+        width: u32,
+        height: u32,
+        descriptor: &GpuContextDescriptor,
+    ) -> Result<Self, GpuContextError> {
+        let instance = wgpu::Instance::new(descriptor.backends);
+        let surface = unsafe { instance.create_surface(window) };
+        Self::new(instance, Some(surface), width, height, descriptor).await
+    }
+
+    async fn request_adapter(
+        instance: &wgpu::Instance,
+        surface: Option<&wgpu::Surface>,
+        descriptor: &GpuContextDescriptor,
+    ) -> Result<wgpu::Adapter, GpuContextError> {
+        let request = |force_fallback_adapter| {
+            instance.request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: descriptor.power_preference,
+                compatible_surface: surface,
+                force_fallback_adapter,
+            })
+        };
+        match request(false).await {
+            Some(adapter) => Ok(adapter),
+            None => {
+                // Software adapters such as lavapipe or llvmpipe, e.g. on CI machines.
+                log::warn!("No hardware adapter found, falling back to a software adapter.");
+                request(true).await.ok_or(GpuContextError::AdapterError)
+            }
+        }
+    }
+
+    async fn request_device(
+        adapter: &wgpu::Adapter,
+        descriptor: &GpuContextDescriptor,
+    ) -> Result<(wgpu::Device, wgpu::Queue), GpuContextError> {
+        let missing_features = descriptor.features - adapter.features();
+        if !missing_features.is_empty() {
+            log::warn!(
+                "Adapter does not support {:?}, disabling them.",
+                missing_features
+            );
+        }
+        let features = descriptor.features & adapter.features();
+
+        let request = |limits| {
+            adapter.request_device(
+                &wgpu::DeviceDescriptor {
+                    features,
+                    limits,
+                    label: None,
+                },
+                None, // Trace path
+            )
+        };
+        match request(descriptor.limits.clone()).await {
+            Ok(device) => Ok(device),
+            Err(e) => {
+                log::warn!(
+                    "Failed to request device with the requested limits ({}), falling back to downlevel limits.",
+                    e
+                );
+                request(wgpu::Limits::downlevel_defaults().using_resolution(adapter.limits()))
+                    .await
+                    .map_err(GpuContextError::DeviceError)
+            }
+        }
+    }
+
+    fn surface_preferred_format(
+        surface: Option<&wgpu::Surface>,
+        adapter: &wgpu::Adapter,
+    ) -> wgpu::TextureFormat {
+        match surface {
+            Some(surface) => surface
+                .get_preferred_format(adapter)
+                .unwrap_or(wgpu::TextureFormat::Bgra8Unorm),
+            None => Self::OFFSCREEN_FORMAT,
+        }
+    }
+
+    pub fn surface(&self) -> Option<&wgpu::Surface> {
+        self.surface.as_ref()
+    }
+
+    // Format the surface renders best in, negotiate requested formats with it.
+    pub fn preferred_format(&self) -> wgpu::TextureFormat {
+        Self::surface_preferred_format(self.surface.as_ref(), &self.adapter)
+    }
+
+    // Apply changes of the config to the surface.
+    pub fn configure(&self) {
+        if let Some(surface) = &self.surface {
+            surface.configure(&self.device, &self.config);
+        }
+    }
+
+    // Returns false for an empty size, e.g. a minimized window.
+    pub fn resize(&mut self, width: u32, height: u32) -> bool {
+        if width == 0 || height == 0 {
+            return false;
+        }
+        if (self.config.width, self.config.height) != (width, height) {
+            self.config.width = width;
+            self.config.height = height;
+            self.configure();
+        }
+        true
+    }
+
+    // Acquire the next surface texture, reconfiguring a lost or outdated surface once.
+    // Contexts without a surface always report it as lost.
+    pub fn acquire(&self) -> Result<wgpu::SurfaceTexture, wgpu::SurfaceError> {
+        let surface = match &self.surface {
+            Some(surface) => surface,
+            None => return Err(wgpu::SurfaceError::Lost),
+        };
+        match surface.get_current_texture() {
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                log::warn!("Surface lost, reconfiguring.");
+                self.configure();
+                let result = surface.get_current_texture();
+                // Reconfiguring does not help when the device itself is gone.
+                if let Err(wgpu::SurfaceError::Lost) = result {
+                    log::error!("Surface still lost after reconfiguring.");
+                    self.lost.store(true, Ordering::SeqCst);
+                }
+                result
+            }
+            // The device has to be recreated to free the memory.
+            Err(wgpu::SurfaceError::OutOfMemory) => {
+                log::error!("Out of memory while acquiring the surface texture.");
+                self.lost.store(true, Ordering::SeqCst);
+                Err(wgpu::SurfaceError::OutOfMemory)
+            }
+            result => result,
+        }
+    }
+}
+
+// Out of memory is handled like a loss, the memory is only freed with the device.
+fn is_device_lost(error: &wgpu::Error) -> bool {
+    match error {
+        wgpu::Error::OutOfMemory { .. } => true,
+        wgpu::Error::Validation { source, .. } => {
+            let mut source: Option<&(dyn std::error::Error + 'static)> = Some(source.as_ref());
+            while let Some(e) = source {
+                if is_device_lost_source(e) {
+                    return true;
+                }
+                source = e.source();
+            }
+            false
+        }
+    }
+}
+
+// The device errors are wrapped transparently, so the wrappers have to be matched themselves.
+#[cfg(not(target_arch = "wasm32"))]
+fn is_device_lost_source(e: &(dyn std::error::Error + 'static)) -> bool {
+    use wgpu_core::{
+        binding_model::*,
+        device::{queue::*, DeviceError::Lost},
+        pipeline::*,
+        resource::*,
+    };
+
+    matches!(e.downcast_ref(), Some(Lost))
+        || matches!(e.downcast_ref(), Some(CreateBufferError::Device(Lost)))
+        || matches!(e.downcast_ref(), Some(CreateTextureError::Device(Lost)))
+        || matches!(e.downcast_ref(), Some(CreateSamplerError::Device(Lost)))
+        || matches!(e.downcast_ref(), Some(CreateQuerySetError::Device(Lost)))
+        || matches!(e.downcast_ref(), Some(BufferAccessError::Device(Lost)))
+        || matches!(e.downcast_ref(), Some(CreateBindGroupError::Device(Lost)))
+        || matches!(
+            e.downcast_ref(),
+            Some(CreateBindGroupLayoutError::Device(Lost))
+        )
+        || matches!(
+            e.downcast_ref(),
+            Some(CreatePipelineLayoutError::Device(Lost))
+        )
+        || matches!(
+            e.downcast_ref(),
+            Some(CreateShaderModuleError::Device(Lost))
+        )
+        || matches!(
+            e.downcast_ref(),
+            Some(CreateRenderPipelineError::Device(Lost))
+        )
+        || matches!(
+            e.downcast_ref(),
+            Some(CreateComputePipelineError::Device(Lost))
+        )
+        || matches!(e.downcast_ref(), Some(QueueWriteError::Queue(Lost)))
+        || matches!(e.downcast_ref(), Some(QueueSubmitError::Queue(Lost)))
+}
+
+// The WebGPU backend of wgpu 0.12 does not report device loss, only out of memory is detected.
+#[cfg(target_arch = "wasm32")]
+fn is_device_lost_source(_e: &(dyn std::error::Error + 'static)) -> bool {
+    false
+}
+
+// Negotiate a requested format with the preferred one.
+// Only the preferred format and its sRGB or linear counterpart are known to be supported.
+pub fn negotiate_format(
+    requested: Option<wgpu::TextureFormat>,
+    preferred: wgpu::TextureFormat,
+) -> wgpu::TextureFormat {
+    match requested {
+        Some(format) if format == preferred || Some(format) == srgb_counterpart(preferred) => {
+            format
+        }
+        Some(format) => {
+            log::warn!(
+                "Surface format {:?} is not supported, using {:?}.",
+                format,
+                preferred
+            );
+            preferred
+        }
+        None => preferred,
+    }
+}
+
+pub fn srgb_counterpart(format: wgpu::TextureFormat) -> Option<wgpu::TextureFormat> {
+    match format {
+        wgpu::TextureFormat::Bgra8Unorm => Some(wgpu::TextureFormat::Bgra8UnormSrgb),
+        wgpu::TextureFormat::Bgra8UnormSrgb => Some(wgpu::TextureFormat::Bgra8Unorm),
+        wgpu::TextureFormat::Rgba8Unorm => Some(wgpu::TextureFormat::Rgba8UnormSrgb),
+        wgpu::TextureFormat::Rgba8UnormSrgb => Some(wgpu::TextureFormat::Rgba8Unorm),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_negotiate_surface_format() {
+        let preferred = wgpu::TextureFormat::Bgra8UnormSrgb;
+        assert_eq!(negotiate_format(None, preferred), preferred);
+        assert_eq!(
+            negotiate_format(Some(wgpu::TextureFormat::Bgra8Unorm), preferred),
+            wgpu::TextureFormat::Bgra8Unorm
+        );
+        assert_eq!(
+            negotiate_format(Some(wgpu::TextureFormat::Rgba16Float), preferred),
+            preferred
+        );
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn it_should_detect_device_lost_errors() {
+        use wgpu_core::{device::DeviceError, resource::CreateBufferError};
+
+        let validation = |source: CreateBufferError| wgpu::Error::Validation {
+            description: source.to_string(),
+            source: Box::new(source),
+        };
+        assert!(is_device_lost(&validation(CreateBufferError::Device(
+            DeviceError::Lost
+        ))));
+        assert!(!is_device_lost(&validation(CreateBufferError::Device(
+            DeviceError::Invalid
+        ))));
+        assert!(!is_device_lost(&validation(
+            CreateBufferError::UnalignedSize
+        )));
+        assert!(is_device_lost(&wgpu::Error::OutOfMemory {
+            source: Box::new(DeviceError::OutOfMemory),
+        }));
+    }
+}
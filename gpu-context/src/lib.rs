@@ -0,0 +1,5 @@
+mod gpu_context;
+
+pub use gpu_context::{
+    negotiate_format, srgb_counterpart, GpuContext, GpuContextDescriptor, GpuContextError,
+};
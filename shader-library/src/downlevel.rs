@@ -0,0 +1,152 @@
+// wgpu 0.12 parses WGSL with naga 0.8, which only knows the `[[...]]` attributes
+// and ends struct members with `;` and structs with `};`.
+// On native the current syntax is rewritten, e.g. `@group(0) @binding(1)`
+// becomes `[[group(0), binding(1)]]`, browsers get the shaders as they are.
+// Lines are kept so errors still map to the original files.
+pub(crate) fn downlevel_attributes(source: &str) -> String {
+    let mut output = String::with_capacity(source.len());
+    let mut struct_body = StructBody::default();
+    for line in source.split_inclusive('\n') {
+        let (code, comment) = match line.find("//") {
+            Some(index) => line.split_at(index),
+            None => (line, ""),
+        };
+        let separator = match struct_body.line(code) {
+            StructLine::Member => ";",
+            StructLine::End => "};",
+            StructLine::Other => "",
+        };
+        let trimmed = code.trim_end();
+        let (code, trailing) = if separator.is_empty() {
+            (code, "")
+        } else {
+            (
+                trimmed.trim_end_matches([',', ';', '}']),
+                &code[trimmed.len()..],
+            )
+        };
+        let mut rest = code;
+        while let Some(index) = rest.find('@') {
+            output.push_str(&rest[..index]);
+            rest = &rest[index..];
+            let mut attributes = vec![];
+            while let Some((attribute, after)) = attribute(rest) {
+                attributes.push(attribute);
+                rest = after;
+                let trimmed = rest.trim_start_matches([' ', '\t']);
+                if !trimmed.starts_with('@') {
+                    break;
+                }
+                rest = trimmed;
+            }
+            if attributes.is_empty() {
+                // Not an attribute, let naga report it.
+                output.push('@');
+                rest = &rest[1..];
+            } else {
+                output.push_str("[[");
+                output.push_str(&attributes.join(", "));
+                output.push_str("]]");
+            }
+        }
+        output.push_str(rest);
+        output.push_str(separator);
+        output.push_str(trailing);
+        output.push_str(comment);
+    }
+    output
+}
+
+pub(crate) enum StructLine {
+    // A member, ended with `,` in the current syntax and `;` in naga 0.8.
+    Member,
+    // The closing brace of a struct.
+    End,
+    Other,
+}
+
+// Tracks struct bodies line by line, members are expected on their own lines.
+#[derive(Debug, Default)]
+pub(crate) struct StructBody {
+    inside: bool,
+}
+impl StructBody {
+    // Classify the code of a line, without its comment.
+    pub(crate) fn line(&mut self, code: &str) -> StructLine {
+        let code = code.trim();
+        if self.inside {
+            if code.starts_with('}') {
+                self.inside = false;
+                return StructLine::End;
+            }
+            if code.contains(':') {
+                return StructLine::Member;
+            }
+        } else if code.starts_with("struct ") && code.ends_with('{') {
+            self.inside = true;
+        }
+        StructLine::Other
+    }
+}
+
+// An attribute at the start of `source`, in naga 0.8 spelling, and the source after it.
+fn attribute(source: &str) -> Option<(String, &str)> {
+    let source = source.strip_prefix('@')?;
+    let name_len = source
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(source.len());
+    if name_len == 0 {
+        return None;
+    }
+    let (name, rest) = source.split_at(name_len);
+    if !rest.starts_with('(') {
+        let attribute = match name {
+            "vertex" | "fragment" | "compute" => format!("stage({})", name),
+            _ => name.to_string(),
+        };
+        return Some((attribute, rest));
+    }
+    let mut depth = 0;
+    for (index, c) in rest.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        if depth == 0 {
+            let (arguments, rest) = rest.split_at(index + 1);
+            return Some((format!("{}{}", name, arguments), rest));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_rewrite_attributes_per_line() {
+        let source = "struct VertexOutput {\n    @builtin(position) position: vec4<f32>,\n}\n\
+            @group(0) @binding(1) var t: texture_2d<f32>; // @ignored\n\
+            @vertex\nfn vs_main(@builtin(vertex_index) i: u32) -> @location(0) vec4<f32> {\n";
+        assert_eq!(
+            downlevel_attributes(source),
+            "struct VertexOutput {\n    [[builtin(position)]] position: vec4<f32>;\n};\n\
+            [[group(0), binding(1)]] var t: texture_2d<f32>; // @ignored\n\
+            [[stage(vertex)]]\nfn vs_main([[builtin(vertex_index)]] i: u32) -> [[location(0)]] vec4<f32> {\n"
+        );
+    }
+
+    #[test]
+    fn it_should_end_struct_members_with_semicolons() {
+        let source =
+            "struct Light {\n    direction: vec3<f32>, // normalized\n\n    color: vec3<f32>\n}\n\
+            fn f(a: f32, b: f32) -> f32 {\n    return max(a, b);\n}\n";
+        assert_eq!(
+            downlevel_attributes(source),
+            "struct Light {\n    direction: vec3<f32>; // normalized\n\n    color: vec3<f32>;\n};\n\
+            fn f(a: f32, b: f32) -> f32 {\n    return max(a, b);\n}\n"
+        );
+    }
+}
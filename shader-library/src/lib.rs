@@ -0,0 +1,11 @@
+#[cfg(not(target_arch = "wasm32"))]
+mod downlevel;
+mod preprocessor;
+#[cfg(not(target_arch = "wasm32"))]
+mod shader_interface;
+mod shader_library;
+
+pub use preprocessor::{preprocess, PreprocessedShader, ShaderError, SourceLocation};
+#[cfg(not(target_arch = "wasm32"))]
+pub use shader_interface::{deprecated_syntax, PipelineInterface};
+pub use shader_library::{ShaderLibrary, ShaderSource};
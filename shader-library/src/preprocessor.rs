@@ -0,0 +1,225 @@
+use std::collections::HashSet;
+use std::fmt;
+use thiserror::Error;
+
+// Line and column in an original shader file, both 1-based.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+}
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ShaderError {
+    #[error("{0}: include \"{1}\" not found")]
+    IncludeNotFoundError(SourceLocation, String),
+    #[error("{0}: invalid directive `{1}`")]
+    DirectiveError(SourceLocation, String),
+    #[error("{0}: #ifdef without #endif")]
+    UnterminatedIfError(SourceLocation),
+    #[error("Shader \"{0}\" not found")]
+    NotFoundError(String),
+    #[error("{0}: {1}")]
+    ParseError(SourceLocation, String),
+    #[error("{0}: {1}")]
+    ValidationError(SourceLocation, String),
+    #[error("{0}: pipeline does not match the shader: {1}")]
+    InterfaceError(String, String),
+}
+
+// WGSL with the directives applied and a map from its lines back to the original files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreprocessedShader {
+    pub source: String,
+    lines: Vec<(String, usize)>,
+}
+impl PreprocessedShader {
+    // Original location of a 1-based line and column of the preprocessed source.
+    pub fn location(&self, line: usize, column: usize) -> SourceLocation {
+        let (file, line) = self
+            .lines
+            .get(line.saturating_sub(1))
+            .cloned()
+            .unwrap_or_else(|| ("<unknown>".to_string(), line));
+        SourceLocation { file, line, column }
+    }
+
+    // Original location of a byte offset in the preprocessed source.
+    pub fn offset_location(&self, offset: usize) -> SourceLocation {
+        let before = &self.source[..offset.min(self.source.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+        self.location(line, column)
+    }
+}
+
+// One branch of an #ifdef/#ifndef, `active` includes the enclosing branches.
+struct Condition {
+    location: SourceLocation,
+    active: bool,
+    parent_active: bool,
+    has_else: bool,
+}
+
+// Resolve the directives of a shader:
+// `#include "name"` pastes another shader once, later includes of it are skipped.
+// `#define NAME` sets a flag, `#ifdef NAME`, `#ifndef NAME`, `#else` and `#endif` test it.
+pub fn preprocess(
+    name: &str,
+    defines: &[&str],
+    sources: &dyn Fn(&str) -> Option<String>,
+) -> Result<PreprocessedShader, ShaderError> {
+    let source = sources(name).ok_or_else(|| ShaderError::NotFoundError(name.to_string()))?;
+    let mut preprocessor = Preprocessor {
+        sources,
+        defines: defines.iter().map(|define| define.to_string()).collect(),
+        included: HashSet::from([name.to_string()]),
+        output: PreprocessedShader {
+            source: String::new(),
+            lines: vec![],
+        },
+    };
+    preprocessor.process(name, &source)?;
+    Ok(preprocessor.output)
+}
+
+struct Preprocessor<'a> {
+    sources: &'a dyn Fn(&str) -> Option<String>,
+    defines: HashSet<String>,
+    included: HashSet<String>,
+    output: PreprocessedShader,
+}
+impl<'a> Preprocessor<'a> {
+    fn process(&mut self, name: &str, source: &str) -> Result<(), ShaderError> {
+        let mut conditions: Vec<Condition> = vec![];
+        for (index, line) in source.lines().enumerate() {
+            let location = SourceLocation {
+                file: name.to_string(),
+                line: index + 1,
+                column: 1,
+            };
+            let active = conditions.last().is_none_or(|condition| condition.active);
+            let trimmed = line.trim();
+            if !trimmed.starts_with('#') {
+                if active {
+                    self.output.source.push_str(line);
+                    self.output.source.push('\n');
+                    self.output.lines.push((name.to_string(), index + 1));
+                }
+                continue;
+            }
+
+            let invalid = || ShaderError::DirectiveError(location.clone(), trimmed.to_string());
+            let mut words = trimmed.split_whitespace();
+            let directive = words.next().unwrap_or_default();
+            let argument = words.next();
+            if words.next().is_some() {
+                return Err(invalid());
+            }
+            match (directive, argument) {
+                ("#include", Some(include)) => {
+                    if !active {
+                        continue;
+                    }
+                    let include = include
+                        .strip_prefix('"')
+                        .and_then(|include| include.strip_suffix('"'))
+                        .ok_or_else(invalid)?;
+                    if !self.included.insert(include.to_string()) {
+                        continue;
+                    }
+                    let source = (self.sources)(include).ok_or_else(|| {
+                        ShaderError::IncludeNotFoundError(location.clone(), include.to_string())
+                    })?;
+                    self.process(include, &source)?;
+                }
+                ("#define", Some(define)) => {
+                    if active {
+                        self.defines.insert(define.to_string());
+                    }
+                }
+                ("#ifdef", Some(define)) | ("#ifndef", Some(define)) => {
+                    let defined = self.defines.contains(define);
+                    conditions.push(Condition {
+                        location: location.clone(),
+                        active: active && defined == (directive == "#ifdef"),
+                        parent_active: active,
+                        has_else: false,
+                    });
+                }
+                ("#else", None) => {
+                    let condition = conditions
+                        .last_mut()
+                        .filter(|condition| !condition.has_else)
+                        .ok_or_else(invalid)?;
+                    condition.active = condition.parent_active && !condition.active;
+                    condition.has_else = true;
+                }
+                ("#endif", None) => {
+                    conditions.pop().ok_or_else(invalid)?;
+                }
+                _ => return Err(invalid()),
+            }
+        }
+        match conditions.pop() {
+            Some(condition) => Err(ShaderError::UnterminatedIfError(condition.location)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sources(name: &str) -> Option<String> {
+        match name {
+            "main.wgsl" => Some(
+                "#include \"common.wgsl\"\n#include \"common.wgsl\"\n#ifdef FOG\nfog\n#else\nno_fog\n#endif\nmain\n"
+                    .to_string(),
+            ),
+            "common.wgsl" => Some("#ifndef FOG\n#define FLAT\n#endif\ncommon\n".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn it_should_include_once_and_apply_defines() {
+        let shader = preprocess("main.wgsl", &[], &sources).unwrap();
+        assert_eq!(shader.source, "common\nno_fog\nmain\n");
+        assert_eq!(
+            shader.location(1, 3),
+            SourceLocation {
+                file: "common.wgsl".into(),
+                line: 4,
+                column: 3,
+            }
+        );
+        assert_eq!(shader.offset_location(8).to_string(), "main.wgsl:6:2");
+
+        let shader = preprocess("main.wgsl", &["FOG"], &sources).unwrap();
+        assert_eq!(shader.source, "common\nfog\nmain\n");
+    }
+
+    #[test]
+    fn it_should_report_directive_errors_with_locations() {
+        let sources = |name: &str| match name {
+            "missing.wgsl" => Some("\n#include \"nope.wgsl\"\n".to_string()),
+            "unterminated.wgsl" => Some("#ifdef FOG\n".to_string()),
+            _ => None,
+        };
+        let error = preprocess("missing.wgsl", &[], &sources).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "missing.wgsl:2:1: include \"nope.wgsl\" not found"
+        );
+        let error = preprocess("unterminated.wgsl", &[], &sources).unwrap_err();
+        assert!(matches!(error, ShaderError::UnterminatedIfError(_)));
+    }
+}
@@ -0,0 +1,477 @@
+use std::error::Error as _;
+
+use crate::downlevel::{StructBody, StructLine};
+use crate::preprocessor::*;
+
+// Vertex buffers and bind group layouts the Rust side creates a pipeline with,
+// checked against the entry points of its shader.
+#[derive(Debug, Clone, Copy)]
+pub struct PipelineInterface<'a> {
+    pub vertex_entry_point: &'a str,
+    pub fragment_entry_point: Option<&'a str>,
+    pub vertex_buffers: &'a [wgpu::VertexBufferLayout<'a>],
+    // Entries of the bind group layouts, in group order.
+    pub bind_group_layouts: &'a [&'a [wgpu::BindGroupLayoutEntry]],
+}
+
+// Parse and validate the preprocessed WGSL with naga.
+pub(crate) fn validate(
+    shader: &PreprocessedShader,
+) -> Result<(naga::Module, naga::valid::ModuleInfo), ShaderError> {
+    let module = naga::front::wgsl::parse_str(&shader.source).map_err(|e| {
+        let (line, column) = e.location(&shader.source);
+        ShaderError::ParseError(shader.location(line, column), e.to_string())
+    })?;
+    let info = naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::all(),
+    )
+    .validate(&module)
+    .map_err(|e| {
+        let location = e
+            .spans()
+            .find_map(|(span, _)| span.to_range())
+            .map(|range| shader.offset_location(range.start))
+            .unwrap_or_else(|| shader.location(1, 1));
+        let mut message = e.to_string();
+        let mut source = e.source();
+        while let Some(e) = source {
+            message += &format!(": {}", e);
+            source = e.source();
+        }
+        ShaderError::ValidationError(location, message)
+    })?;
+    Ok((module, info))
+}
+
+// Locations of syntax newer WGSL versions removed,
+// e.g. `[[stage(vertex)]]` or `@stage(vertex)` instead of `@vertex`,
+// or struct members ended with `;` instead of `,`.
+pub fn deprecated_syntax(shader: &PreprocessedShader) -> Vec<SourceLocation> {
+    let mut locations = vec![];
+    let mut struct_body = StructBody::default();
+    for (index, line) in shader.source.lines().enumerate() {
+        let code = line.split("//").next().unwrap_or_default();
+        let member_semicolon = match struct_body.line(code) {
+            StructLine::Member if code.trim_end().ends_with(';') => code.rfind(';'),
+            _ => None,
+        };
+        let column = ["[[", "@stage("]
+            .into_iter()
+            .filter_map(|syntax| code.find(syntax))
+            .chain(member_semicolon)
+            .min();
+        if let Some(column) = column {
+            locations.push(shader.location(index + 1, column + 1));
+        }
+    }
+    locations
+}
+
+// Check that the vertex buffers provide every vertex input with a matching scalar kind
+// and that every binding the entry points use is in the bind group layouts,
+// with a matching type and visible to the stages that use it.
+pub fn check_interface(
+    name: &str,
+    module: &naga::Module,
+    info: &naga::valid::ModuleInfo,
+    interface: &PipelineInterface,
+) -> Result<(), ShaderError> {
+    let mut errors = vec![];
+
+    let mut stages = vec![(interface.vertex_entry_point, naga::ShaderStage::Vertex)];
+    if let Some(fragment_entry_point) = interface.fragment_entry_point {
+        stages.push((fragment_entry_point, naga::ShaderStage::Fragment));
+    }
+    for (entry_point_name, stage) in stages {
+        let index = module
+            .entry_points
+            .iter()
+            .position(|entry_point| entry_point.name == entry_point_name);
+        let (entry_point, function_info) = match index {
+            Some(index) if module.entry_points[index].stage == stage => {
+                (&module.entry_points[index], info.get_entry_point(index))
+            }
+            _ => {
+                errors.push(format!("no {:?} entry point `{}`", stage, entry_point_name));
+                continue;
+            }
+        };
+
+        if stage == naga::ShaderStage::Vertex {
+            for (location, kind) in vertex_inputs(module, entry_point) {
+                let attribute = interface
+                    .vertex_buffers
+                    .iter()
+                    .flat_map(|buffer| buffer.attributes)
+                    .find(|attribute| attribute.shader_location == location);
+                match attribute {
+                    None => errors.push(format!("vertex input {} has no attribute", location)),
+                    Some(attribute) if vertex_format_kind(attribute.format) != kind => {
+                        errors.push(format!(
+                            "vertex input {} is {:?}, the attribute is {:?}",
+                            location, kind, attribute.format
+                        ))
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+
+        let shader_stage = match stage {
+            naga::ShaderStage::Vertex => wgpu::ShaderStages::VERTEX,
+            naga::ShaderStage::Fragment => wgpu::ShaderStages::FRAGMENT,
+            naga::ShaderStage::Compute => wgpu::ShaderStages::COMPUTE,
+        };
+        for (handle, global) in module.global_variables.iter() {
+            let binding = match &global.binding {
+                Some(binding) if !function_info[handle].is_empty() => binding,
+                _ => continue,
+            };
+            let label = format!(
+                "`{}` (group {}, binding {})",
+                global.name.as_deref().unwrap_or("?"),
+                binding.group,
+                binding.binding
+            );
+            let entry = interface
+                .bind_group_layouts
+                .get(binding.group as usize)
+                .and_then(|entries| {
+                    entries
+                        .iter()
+                        .find(|entry| entry.binding == binding.binding)
+                });
+            let entry = match entry {
+                Some(entry) => entry,
+                None => {
+                    errors.push(format!("{} is not in the bind group layouts", label));
+                    continue;
+                }
+            };
+            if !entry.visibility.contains(shader_stage) {
+                errors.push(format!("{} is not visible to {:?}", label, stage));
+            }
+            if let Err(e) = check_binding_type(module, global, &entry.ty) {
+                errors.push(format!("{} {}", label, e));
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(ShaderError::InterfaceError(
+            name.to_string(),
+            errors.join(", "),
+        ))
+    }
+}
+
+// Locations and scalar kinds of the vertex inputs, including the members of struct arguments.
+fn vertex_inputs(
+    module: &naga::Module,
+    entry_point: &naga::EntryPoint,
+) -> Vec<(u32, naga::ScalarKind)> {
+    let mut inputs = vec![];
+    let mut add = |binding: &Option<naga::Binding>, ty: naga::Handle<naga::Type>| {
+        if let Some(naga::Binding::Location { location, .. }) = binding {
+            if let Some(kind) = module.types[ty].inner.scalar_kind() {
+                inputs.push((*location, kind));
+            }
+        }
+    };
+    for argument in &entry_point.function.arguments {
+        match &module.types[argument.ty].inner {
+            naga::TypeInner::Struct { members, .. } => {
+                for member in members {
+                    add(&member.binding, member.ty);
+                }
+            }
+            _ => add(&argument.binding, argument.ty),
+        }
+    }
+    inputs
+}
+
+fn vertex_format_kind(format: wgpu::VertexFormat) -> naga::ScalarKind {
+    use wgpu::VertexFormat::*;
+    match format {
+        Uint8x2 | Uint8x4 | Uint16x2 | Uint16x4 | Uint32 | Uint32x2 | Uint32x3 | Uint32x4 => {
+            naga::ScalarKind::Uint
+        }
+        Sint8x2 | Sint8x4 | Sint16x2 | Sint16x4 | Sint32 | Sint32x2 | Sint32x3 | Sint32x4 => {
+            naga::ScalarKind::Sint
+        }
+        _ => naga::ScalarKind::Float,
+    }
+}
+
+fn check_binding_type(
+    module: &naga::Module,
+    global: &naga::GlobalVariable,
+    ty: &wgpu::BindingType,
+) -> Result<(), String> {
+    let matches = match (global.class, &module.types[global.ty].inner, ty) {
+        (
+            naga::StorageClass::Uniform,
+            _,
+            wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                ..
+            },
+        ) => true,
+        (
+            naga::StorageClass::Storage { access },
+            _,
+            wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                ..
+            },
+        ) => *read_only || access.contains(naga::StorageAccess::STORE),
+        (
+            naga::StorageClass::Handle,
+            naga::TypeInner::Sampler { comparison },
+            wgpu::BindingType::Sampler(sampler),
+        ) => *comparison == (*sampler == wgpu::SamplerBindingType::Comparison),
+        (
+            naga::StorageClass::Handle,
+            naga::TypeInner::Image {
+                dim,
+                arrayed,
+                class,
+            },
+            wgpu::BindingType::Texture {
+                sample_type,
+                view_dimension,
+                multisampled,
+            },
+        ) => {
+            let (kind_matches, multi) = match class {
+                naga::ImageClass::Sampled { kind, multi } => (
+                    matches!(
+                        (kind, sample_type),
+                        (
+                            naga::ScalarKind::Float,
+                            wgpu::TextureSampleType::Float { .. }
+                        ) | (naga::ScalarKind::Sint, wgpu::TextureSampleType::Sint)
+                            | (naga::ScalarKind::Uint, wgpu::TextureSampleType::Uint)
+                    ),
+                    *multi,
+                ),
+                naga::ImageClass::Depth { multi } => {
+                    (*sample_type == wgpu::TextureSampleType::Depth, *multi)
+                }
+                naga::ImageClass::Storage { .. } => (false, false),
+            };
+            kind_matches
+                && multi == *multisampled
+                && texture_view_dimension(*dim, *arrayed) == *view_dimension
+        }
+        (
+            naga::StorageClass::Handle,
+            naga::TypeInner::Image {
+                class: naga::ImageClass::Storage { .. },
+                dim,
+                arrayed,
+            },
+            wgpu::BindingType::StorageTexture { view_dimension, .. },
+        ) => texture_view_dimension(*dim, *arrayed) == *view_dimension,
+        _ => false,
+    };
+    if matches {
+        Ok(())
+    } else {
+        Err(format!("does not match the layout entry {:?}", ty))
+    }
+}
+
+fn texture_view_dimension(dim: naga::ImageDimension, arrayed: bool) -> wgpu::TextureViewDimension {
+    match (dim, arrayed) {
+        (naga::ImageDimension::D1, _) => wgpu::TextureViewDimension::D1,
+        (naga::ImageDimension::D2, false) => wgpu::TextureViewDimension::D2,
+        (naga::ImageDimension::D2, true) => wgpu::TextureViewDimension::D2Array,
+        (naga::ImageDimension::D3, _) => wgpu::TextureViewDimension::D3,
+        (naga::ImageDimension::Cube, false) => wgpu::TextureViewDimension::Cube,
+        (naga::ImageDimension::Cube, true) => wgpu::TextureViewDimension::CubeArray,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::downlevel::*;
+    use crate::*;
+
+    const SHADER: ShaderSource = ShaderSource {
+        name: "interface.wgsl",
+        source: "struct VertexInput {\n\
+            @location(0) position: vec3<f32>,\n\
+            @location(1) index: u32,\n\
+            }\n\
+            @group(0) @binding(0) var t: texture_2d<f32>;\n\
+            @group(0) @binding(1) var s: sampler;\n\
+            @vertex\n\
+            fn vs_main(in: VertexInput) -> @builtin(position) vec4<f32> {\n\
+                return vec4<f32>(in.position, f32(in.index));\n\
+            }\n\
+            @fragment\n\
+            fn fs_main() -> @location(0) vec4<f32> {\n\
+                return textureSample(t, s, vec2<f32>(0.5));\n\
+            }\n",
+        path: None,
+    };
+
+    fn layout_entries(visibility: wgpu::ShaderStages) -> [wgpu::BindGroupLayoutEntry; 2] {
+        [
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn it_should_check_vertex_and_bind_group_interfaces() {
+        let library = ShaderLibrary::new();
+        let attributes = wgpu::vertex_attr_array![0 => Float32x3, 1 => Uint32];
+        let vertex_buffers = [wgpu::VertexBufferLayout {
+            array_stride: 16,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &attributes,
+        }];
+        let entries = layout_entries(wgpu::ShaderStages::FRAGMENT);
+        let interface = PipelineInterface {
+            vertex_entry_point: "vs_main",
+            fragment_entry_point: Some("fs_main"),
+            vertex_buffers: &vertex_buffers,
+            bind_group_layouts: &[&entries],
+        };
+        library.check_interface(SHADER, &[], &interface).unwrap();
+
+        let attributes = wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32];
+        let vertex_buffers = [wgpu::VertexBufferLayout {
+            attributes: &attributes,
+            ..vertex_buffers[0]
+        }];
+        let entries = layout_entries(wgpu::ShaderStages::VERTEX);
+        let error = library
+            .check_interface(
+                SHADER,
+                &[],
+                &PipelineInterface {
+                    vertex_buffers: &vertex_buffers,
+                    bind_group_layouts: &[&entries[..1]],
+                    ..interface
+                },
+            )
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("vertex input 1 is Uint"), "{}", error);
+        assert!(
+            error.contains("`t` (group 0, binding 0) is not visible to Fragment"),
+            "{}",
+            error
+        );
+        assert!(
+            error.contains("`s` (group 0, binding 1) is not in the bind group layouts"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn it_should_find_deprecated_attribute_syntax() {
+        let library = ShaderLibrary::new();
+        let shader = library.preprocess(SHADER, &[]).unwrap();
+        assert!(deprecated_syntax(&shader).is_empty());
+
+        let shader = library
+            .preprocess(
+                ShaderSource {
+                    name: "deprecated.wgsl",
+                    source: "// [[comment]]\n[[stage(vertex)]]\nfn vs_main() -> [[builtin(position)]] vec4<f32> {\n}\n\
+                        @stage(fragment)\nfn fs_main() -> @location(0) vec4<f32> {\n}\n\
+                        struct Light {\n    color: vec3<f32>;\n};\n",
+                    path: None,
+                },
+                &[],
+            )
+            .unwrap();
+        let locations = deprecated_syntax(&shader);
+        assert_eq!(locations.len(), 4);
+        assert_eq!(locations[0].to_string(), "deprecated.wgsl:2:1");
+        assert_eq!(locations[2].to_string(), "deprecated.wgsl:5:1");
+        assert_eq!(locations[3].to_string(), "deprecated.wgsl:9:21");
+    }
+
+    fn workspace_shaders(dir: &std::path::Path, paths: &mut Vec<std::path::PathBuf>) {
+        let entries = std::fs::read_dir(dir).unwrap_or_else(|_| panic!("Failed to read {:?}", dir));
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if path.is_dir() {
+                if !name.starts_with('.')
+                    && !["target", "node_modules", "dist", "tmp"].contains(&name.as_ref())
+                {
+                    workspace_shaders(&path, paths);
+                }
+            } else if name.ends_with(".wgsl") {
+                paths.push(path);
+            }
+        }
+    }
+
+    // Every shader has to parse and validate with the naga of the wgpu in use,
+    // and must not use syntax the next WGSL version removes.
+    #[test]
+    fn it_should_validate_every_workspace_shader() {
+        let root = std::path::Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/.."));
+        let mut paths = vec![];
+        workspace_shaders(root, &mut paths);
+        assert!(!paths.is_empty());
+
+        let library = ShaderLibrary::new();
+        let mut errors = vec![];
+        for path in paths {
+            let name = path
+                .strip_prefix(root)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .to_string();
+            let source = std::fs::read_to_string(&path)
+                .unwrap_or_else(|_| panic!("Failed to read {}", name));
+            let source = ShaderSource {
+                name: Box::leak(name.into_boxed_str()),
+                source: Box::leak(source.into_boxed_str()),
+                path: None,
+            };
+            match library.preprocess(source, &[]) {
+                Ok(mut shader) => {
+                    for location in deprecated_syntax(&shader) {
+                        errors.push(format!("{}: deprecated syntax", location));
+                    }
+                    shader.source = downlevel_attributes(&shader.source);
+                    if let Err(e) = validate(&shader) {
+                        errors.push(e.to_string());
+                    }
+                }
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+        assert!(errors.is_empty(), "\n{}", errors.join("\n"));
+    }
+}
@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::downlevel::*;
+use crate::preprocessor::*;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::shader_interface::*;
+
+// A shader embedded in the binary, with the file it came from for hot reload.
+// Create it with `shader_source!`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShaderSource {
+    pub name: &'static str,
+    pub source: &'static str,
+    pub path: Option<&'static str>,
+}
+
+// Embed a shader of the `src` directory of the calling crate, e.g. `shader_source!("pass/mesh.wgsl")`.
+#[macro_export]
+macro_rules! shader_source {
+    ($name:literal) => {
+        $crate::ShaderSource {
+            name: $name,
+            source: include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/src/", $name)),
+            path: Some(concat!(env!("CARGO_MANIFEST_DIR"), "/src/", $name)),
+        }
+    };
+}
+
+#[derive(Debug)]
+struct ShaderFile {
+    source: String,
+    path: Option<PathBuf>,
+    #[cfg(not(target_arch = "wasm32"))]
+    modified: Option<std::time::SystemTime>,
+}
+
+#[derive(Debug)]
+struct ShaderLibraryInner {
+    files: HashMap<String, ShaderFile>,
+    // Last valid source of every compiled shader and defines, used while an edit does not compile.
+    valid: HashMap<(String, Vec<String>), String>,
+    hot_reload: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    last_poll: Option<std::time::Instant>,
+}
+
+// Shaders and the snippets they include, e.g. `color.wgsl` and `fullscreen.wgsl`.
+// On native debug builds the files are watched and reloaded when they change.
+// Cloning shares the library.
+#[derive(Debug, Clone)]
+pub struct ShaderLibrary {
+    inner: Arc<Mutex<ShaderLibraryInner>>,
+}
+impl ShaderLibrary {
+    pub const SNIPPETS: [ShaderSource; 2] = [
+        shader_source!("shaders/color.wgsl"),
+        shader_source!("shaders/fullscreen.wgsl"),
+    ];
+
+    pub fn new() -> Self {
+        let library = Self {
+            inner: Arc::new(Mutex::new(ShaderLibraryInner {
+                files: HashMap::new(),
+                valid: HashMap::new(),
+                hot_reload: cfg!(all(not(target_arch = "wasm32"), debug_assertions)),
+                #[cfg(not(target_arch = "wasm32"))]
+                last_poll: None,
+            })),
+        };
+        for snippet in Self::SNIPPETS {
+            // Included by file name.
+            library.add(ShaderSource {
+                name: snippet.name.trim_start_matches("shaders/"),
+                ..snippet
+            });
+        }
+        library
+    }
+
+    pub fn with_hot_reload(self, hot_reload: bool) -> Self {
+        self.lock().hot_reload = hot_reload;
+        self
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ShaderLibraryInner> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|_| panic!("Shader library lock poisoned."))
+    }
+
+    // Register a shader so others can include it, a registered one is kept as it is.
+    pub fn add(&self, source: ShaderSource) {
+        self.lock()
+            .files
+            .entry(source.name.to_string())
+            .or_insert_with(|| {
+                let path = source.path.map(PathBuf::from);
+                ShaderFile {
+                    source: source.source.to_string(),
+                    #[cfg(not(target_arch = "wasm32"))]
+                    modified: path
+                        .as_ref()
+                        .and_then(|path| std::fs::metadata(path).ok())
+                        .and_then(|metadata| metadata.modified().ok()),
+                    path,
+                }
+            });
+    }
+
+    pub fn preprocess(
+        &self,
+        source: ShaderSource,
+        defines: &[&str],
+    ) -> Result<PreprocessedShader, ShaderError> {
+        self.add(source);
+        let inner = self.lock();
+        preprocess(source.name, defines, &|name| {
+            inner.files.get(name).map(|file| file.source.clone())
+        })
+    }
+
+    // Preprocess and validate a shader, on native the errors point into the original files.
+    pub fn compile(&self, source: ShaderSource, defines: &[&str]) -> Result<String, ShaderError> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let shader = {
+            let mut shader = self.preprocess(source, defines)?;
+            shader.source = downlevel_attributes(&shader.source);
+            validate(&shader)?;
+            shader
+        };
+        #[cfg(target_arch = "wasm32")]
+        let shader = self.preprocess(source, defines)?;
+        Ok(shader.source)
+    }
+
+    // Compile a shader and check it against the layouts of its pipeline, meant for tests.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn check_interface(
+        &self,
+        source: ShaderSource,
+        defines: &[&str],
+        interface: &PipelineInterface,
+    ) -> Result<(), ShaderError> {
+        let mut shader = self.preprocess(source, defines)?;
+        shader.source = downlevel_attributes(&shader.source);
+        let (module, info) = validate(&shader)?;
+        check_interface(source.name, &module, &info, interface)
+    }
+
+    // Create the shader module, a shader that stopped compiling after a reload logs the error
+    // and keeps its last valid version.
+    pub fn create_shader_module(
+        &self,
+        device: &wgpu::Device,
+        source: ShaderSource,
+        defines: &[&str],
+    ) -> wgpu::ShaderModule {
+        let key = (
+            source.name.to_string(),
+            defines.iter().map(|define| define.to_string()).collect(),
+        );
+        let wgsl = match self.compile(source, defines) {
+            Ok(wgsl) => {
+                self.lock().valid.insert(key, wgsl.clone());
+                wgsl
+            }
+            Err(e) => match self.lock().valid.get(&key) {
+                Some(wgsl) => {
+                    log::error!("{}", e);
+                    wgsl.clone()
+                }
+                None => panic!("Failed to compile shader {}: {}", source.name, e),
+            },
+        };
+        device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: Some(source.name),
+            source: wgpu::ShaderSource::Wgsl(wgsl.into()),
+        })
+    }
+
+    // Reload the files changed on disk, true when the pipelines should be recreated.
+    // Checks at most twice per second.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn poll_changes(&self) -> bool {
+        const INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+        let mut inner = self.lock();
+        if !inner.hot_reload
+            || inner
+                .last_poll
+                .is_some_and(|last| last.elapsed() < INTERVAL)
+        {
+            return false;
+        }
+        inner.last_poll = Some(std::time::Instant::now());
+
+        let mut changed = false;
+        for (name, file) in &mut inner.files {
+            let path = match &file.path {
+                Some(path) => path,
+                None => continue,
+            };
+            let modified = std::fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .ok();
+            if modified.is_none() || modified == file.modified {
+                continue;
+            }
+            match std::fs::read_to_string(path) {
+                Ok(source) => {
+                    log::info!("Reloaded shader {}", name);
+                    file.source = source;
+                    file.modified = modified;
+                    changed = true;
+                }
+                Err(e) => log::warn!("Failed to reload shader {}: {}", name, e),
+            }
+        }
+        changed
+    }
+
+    // Shaders are not watched on the web.
+    #[cfg(target_arch = "wasm32")]
+    pub fn poll_changes(&self) -> bool {
+        false
+    }
+}
+impl Default for ShaderLibrary {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHADER: ShaderSource = ShaderSource {
+        name: "test.wgsl",
+        source: "#include \"fullscreen.wgsl\"\n#include \"color.wgsl\"\n\
+            @vertex\n\
+            fn vs_main(@builtin(vertex_index) i: u32) -> @builtin(position) vec4<f32> {\n\
+            #ifdef BROKEN\n    return missing;\n#else\n    return fullscreen_position(fullscreen_uv(i));\n#endif\n}\n",
+        path: None,
+    };
+
+    #[test]
+    fn it_should_compile_shaders_with_snippets() {
+        let library = ShaderLibrary::new();
+        let wgsl = library.compile(SHADER, &[]).unwrap();
+        assert!(wgsl.contains("fn linear_to_srgb"));
+        assert!(!wgsl.contains("return missing"));
+    }
+
+    #[test]
+    fn it_should_report_errors_in_the_original_file() {
+        let library = ShaderLibrary::new();
+        match library.compile(SHADER, &["BROKEN"]) {
+            Err(ShaderError::ParseError(location, _))
+            | Err(ShaderError::ValidationError(location, _)) => {
+                assert_eq!((location.file.as_str(), location.line), ("test.wgsl", 6));
+            }
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+}
@@ -0,0 +1,64 @@
+// Color conversions shared by the shaders.
+// Include with `#include "color.wgsl"`.
+
+fn linear_to_srgb(color: vec3<f32>) -> vec3<f32> {
+    let low = color * 12.92;
+    let high = 1.055 * pow(color, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, color <= vec3<f32>(0.0031308));
+}
+
+fn srgb_to_linear(color: vec3<f32>) -> vec3<f32> {
+    let low = color / 12.92;
+    let high = pow((color + 0.055) / 1.055, vec3<f32>(2.4));
+    return select(high, low, color <= vec3<f32>(0.04045));
+}
+
+fn luminance(color: vec3<f32>) -> f32 {
+    return dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+}
+
+// Hue in degrees, saturation and value in [0, 1] to gamma encoded RGB.
+fn hsv_to_gamma_rgb(hsv: vec3<f32>) -> vec3<f32> {
+    let c = hsv.z * hsv.y;
+    let x = c * (1.0 -  abs((hsv.x / 60.0) % 2.0 - 1.0));
+    let m = hsv.z - c;
+    let h = (hsv.x + 360.0) % 360.0;
+    if (h < 60.0) {
+        return vec3<f32>(c + m, x + m, 0.0 + m);
+    } else if (h < 120.0) {
+        return vec3<f32>(x + m, c + m, 0.0 + m);
+    } else if (h < 180.0) {
+        return vec3<f32>(0.0 + m, c + m, x + m);
+    } else if (h < 240.0) {
+        return vec3<f32>(0.0 + m, x + m, c + m);
+    } else if (h < 300.0) {
+        return vec3<f32>(x + m, 0.0 + m, c + m);
+    } else {
+        return vec3<f32>(c + m, 0.0 + m, x + m);
+    }
+}
+
+fn gamma_rgb_to_hsv(rgb: vec3<f32>) -> vec3<f32> {
+    let cmax = max(max(rgb.r, rgb.g), rgb.b);
+    let cmin = min(min(rgb.r, rgb.g), rgb.b);
+    let delta = cmax - cmin;
+    var h: f32;
+    if (delta == 0.0) {
+        h = 0.0;
+    } else if (cmax == rgb.r) {
+        h = 60.0 * ((rgb.g - rgb.b) / delta % 6.0);
+        h = (h + 360.0) % 360.0;
+    } else if (cmax == rgb.g) {
+        h = 60.0 * ((rgb.b - rgb.r) / delta + 2.0);
+    } else if (cmax == rgb.b) {
+        h = 60.0 * ((rgb.r - rgb.g) / delta + 4.0);
+    }
+    var s: f32;
+    if (cmax == 0.0) {
+        s = 0.0;
+    } else {
+        s = delta / cmax;
+    }
+    let v = cmax;
+    return vec3<f32>(h, s, v);
+}
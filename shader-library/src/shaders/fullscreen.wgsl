@@ -0,0 +1,12 @@
+// Full screen triangle drawn with 3 vertices and no vertex buffer.
+// Include with `#include "fullscreen.wgsl"`.
+
+// Texture coordinates of the vertex, (0, 0) at the top left.
+fn fullscreen_uv(vertex_index: u32) -> vec2<f32> {
+    return vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+}
+
+// Clip position covering the screen, matches `fullscreen_uv`.
+fn fullscreen_position(uv: vec2<f32>) -> vec4<f32> {
+    return vec4<f32>(uv * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0), 0.0, 1.0);
+}
@@ -0,0 +1,31 @@
+use std::path::Path;
+
+// Content hashes of the files in `assets`, the resources catalog expects them
+// so cached copies of an asset changed under the same path are not used.
+fn main() {
+    println!("cargo:rerun-if-changed=assets");
+
+    let mut assets = std::fs::read_dir("assets")
+        .unwrap_or_else(|_| panic!("Failed to read assets."))
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .collect::<Vec<_>>();
+    assets.sort();
+
+    let mut source = String::from("const ASSET_CONTENT_HASHES: &[(&str, u64)] = &[\n");
+    for path in assets {
+        let bytes =
+            std::fs::read(&path).unwrap_or_else(|_| panic!("Failed to read {}.", path.display()));
+        source.push_str(&format!(
+            "    ({:?}, 0x{:016x}),\n",
+            path.to_string_lossy().replace('\\', "/"),
+            const_fnv1a_hash::fnv1a_hash_64(&bytes, None)
+        ));
+    }
+    source.push_str("];\n");
+
+    let out_dir = std::env::var("OUT_DIR").unwrap_or_else(|_| panic!("Failed to get OUT_DIR."));
+    std::fs::write(Path::new(&out_dir).join("asset_content_hashes.rs"), source)
+        .unwrap_or_else(|_| panic!("Failed to write asset content hashes."));
+}
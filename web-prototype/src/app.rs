@@ -1,8 +1,9 @@
-use winit::event::{ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent};
+use winit::event::{Event, WindowEvent};
 use winit::event_loop::{ControlFlow, EventLoop};
 use winit::window::{Window, WindowBuilder};
 
 use crate::resources::*;
+use crate::settings::UserSettings;
 use crate::state::*;
 
 pub struct App {
@@ -16,7 +17,15 @@ impl App {
         let window = WindowBuilder::new()
             .build(&event_loop)
             .unwrap_or_else(|_| panic!("Failed to create window."));
-        let resources_loader = ResourcesLoader::new();
+        let resources_loader = ResourcesLoader::with_cache(ResourcesCacheConfig::default());
+        #[cfg(not(target_arch = "wasm32"))]
+        let resources_loader = match std::env::var("WEB_PROTOTYPE_NETWORK_SIMULATOR_SEED") {
+            Ok(seed) => resources_loader.with_network_simulator(NetworkSimulatorConfig {
+                seed: seed.parse().unwrap_or_default(),
+                ..Default::default()
+            }),
+            Err(_) => resources_loader,
+        };
 
         #[cfg(target_arch = "wasm32")]
         let window = {
@@ -63,23 +72,29 @@ impl App {
         let surface = unsafe { instance.create_surface(&window) };
 
         runtime.block_on(async {
-            let mut state = State::new(size, instance, surface, resources_loader).await;
+            let mut state = match State::new(
+                size,
+                window.scale_factor(),
+                instance,
+                surface,
+                resources_loader,
+                UserSettings::load(),
+            )
+            .await
+            {
+                Ok(state) => state,
+                Err(e) => {
+                    log::error!("Failed to initialize the renderer: {}", e);
+                    return;
+                }
+            };
+            let mut cursor_icon = egui::CursorIcon::Default;
             event_loop.run(move |event, _, control_flow| {
                 *control_flow = ControlFlow::Poll;
                 state.handle_event(&event);
                 match &event {
                     Event::WindowEvent {
-                        event:
-                            WindowEvent::CloseRequested
-                            | WindowEvent::KeyboardInput {
-                                input:
-                                    KeyboardInput {
-                                        state: ElementState::Pressed,
-                                        virtual_keycode: Some(VirtualKeyCode::Escape),
-                                        ..
-                                    },
-                                ..
-                            },
+                        event: WindowEvent::CloseRequested,
                         ..
                     } => *control_flow = ControlFlow::Exit,
                     Event::WindowEvent { ref event, .. } => match event {
@@ -92,15 +107,29 @@ impl App {
                         _ => {}
                     },
                     Event::RedrawRequested(_) => {
+                        if state.is_lost() {
+                            if let Err(e) = futures::executor::block_on(state.recover()) {
+                                log::error!("Failed to recover the lost device: {}", e);
+                                *control_flow = ControlFlow::Exit;
+                                return;
+                            }
+                        }
                         state.update();
+                        if state.is_quit_requested() {
+                            *control_flow = ControlFlow::Exit;
+                            return;
+                        }
                         match state.render() {
                             Ok(_) => {}
                             Err(wgpu::SurfaceError::Lost) => state.resize(state.size()),
-                            Err(wgpu::SurfaceError::OutOfMemory) => {
-                                *control_flow = ControlFlow::Exit
-                            }
+                            // The device is recreated on the next frame.
+                            Err(wgpu::SurfaceError::OutOfMemory) => {}
                             Err(e) => eprintln!("{:?}", e),
                         }
+                        if state.cursor_icon() != cursor_icon {
+                            cursor_icon = state.cursor_icon();
+                            set_cursor_icon(&window, cursor_icon);
+                        }
                     }
                     Event::MainEventsCleared => window.request_redraw(),
                     _ => (),
@@ -112,6 +141,8 @@ impl App {
     #[cfg(target_arch = "wasm32")]
     pub fn run(self) {
         use instant::Duration;
+        use std::cell::Cell;
+        use std::rc::Rc;
 
         let App {
             event_loop,
@@ -121,18 +152,48 @@ impl App {
 
         let mut runtime = crate::runtime::Runtime::new();
         let (event_tx, event_rx) = std::sync::mpsc::channel();
+        // Set by the quit action of the state, which lives in the spawned task.
+        let quit = Rc::new(Cell::new(false));
+        // Requested by egui in the spawned task, the window stays in the event loop.
+        let cursor_icon = Rc::new(Cell::new(egui::CursorIcon::Default));
 
         resources_loader.start_load(runtime.clone(), get_catalog());
 
         let size = window.inner_size();
+        let scale_factor = window.scale_factor();
         let instance = wgpu::Instance::new(wgpu::Backends::all());
         let surface = unsafe { instance.create_surface(&window) };
 
         runtime.spawn({
             let runtime = runtime.clone();
+            let quit = quit.clone();
+            let cursor_icon = cursor_icon.clone();
             async move {
-                let mut state = State::new(size, instance, surface, resources_loader).await;
+                let mut state = match State::new(
+                    size,
+                    scale_factor,
+                    instance,
+                    surface,
+                    resources_loader,
+                    UserSettings::load(),
+                )
+                .await
+                {
+                    Ok(state) => state,
+                    Err(e) => {
+                        log::error!("Failed to initialize the renderer: {}", e);
+                        show_error(&format!("Failed to initialize the renderer: {}", e));
+                        return;
+                    }
+                };
                 loop {
+                    if state.is_lost() {
+                        if let Err(e) = state.recover().await {
+                            log::error!("Failed to recover the lost device: {}", e);
+                            show_error(&format!("The GPU device was lost: {}", e));
+                            return;
+                        }
+                    }
                     for event in event_rx.try_iter() {
                         state.handle_event(&event);
                         match &event {
@@ -147,14 +208,15 @@ impl App {
                             },
                             Event::RedrawRequested(_) => {
                                 state.update();
+                                quit.set(state.is_quit_requested());
                                 match state.render() {
                                     Ok(_) => {}
                                     Err(wgpu::SurfaceError::Lost) => state.resize(state.size()),
-                                    Err(wgpu::SurfaceError::OutOfMemory) => {
-                                        // *control_flow = ControlFlow::Exit
-                                    }
+                                    // The device is recreated on the next iteration.
+                                    Err(wgpu::SurfaceError::OutOfMemory) => {}
                                     Err(e) => eprintln!("{:?}", e),
                                 }
+                                cursor_icon.set(state.cursor_icon());
                             }
                             _ => (),
                         }
@@ -164,22 +226,20 @@ impl App {
             }
         });
 
+        let mut applied_cursor_icon = egui::CursorIcon::Default;
         event_loop.run(move |event, _, control_flow| {
             *control_flow = ControlFlow::Poll;
             runtime.step();
+            if quit.get() {
+                *control_flow = ControlFlow::Exit;
+            }
+            if cursor_icon.get() != applied_cursor_icon {
+                applied_cursor_icon = cursor_icon.get();
+                set_cursor_icon(&window, applied_cursor_icon);
+            }
             match &event {
                 Event::WindowEvent {
-                    event:
-                        WindowEvent::CloseRequested
-                        | WindowEvent::KeyboardInput {
-                            input:
-                                KeyboardInput {
-                                    state: ElementState::Pressed,
-                                    virtual_keycode: Some(VirtualKeyCode::Escape),
-                                    ..
-                                },
-                            ..
-                        },
+                    event: WindowEvent::CloseRequested,
                     ..
                 } => *control_flow = ControlFlow::Exit,
                 Event::MainEventsCleared => window.request_redraw(),
@@ -189,3 +249,36 @@ impl App {
         });
     }
 }
+
+fn set_cursor_icon(window: &Window, cursor_icon: egui::CursorIcon) {
+    match crate::pass::winit_cursor_icon(cursor_icon) {
+        Some(cursor_icon) => {
+            window.set_cursor_visible(true);
+            window.set_cursor_icon(cursor_icon);
+        }
+        None => window.set_cursor_visible(false),
+    }
+}
+
+// Replace the canvas with a readable message, e.g. when WebGPU is not available.
+#[cfg(target_arch = "wasm32")]
+fn show_error(message: &str) {
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .unwrap_or_else(|| panic!("Failed to get document"));
+    let element = document
+        .create_element("p")
+        .unwrap_or_else(|_| panic!("Failed to create element"));
+    element.set_text_content(Some(message));
+    match document.get_element_by_id("canvas") {
+        Some(canvas) => canvas
+            .replace_with_with_node_1(&element)
+            .unwrap_or_else(|_| panic!("Failed to replace canvas")),
+        None => {
+            document
+                .body()
+                .and_then(|body| body.append_child(&element).ok())
+                .unwrap_or_else(|| panic!("Failed to add error message"));
+        }
+    }
+}
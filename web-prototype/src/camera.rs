@@ -0,0 +1,9 @@
+mod camera;
+mod camera_controller;
+mod fly_camera_controller;
+mod orbit_camera_controller;
+
+pub use camera::Camera;
+pub use camera_controller::{CameraController, CameraControllerKind};
+pub use fly_camera_controller::FlyCameraController;
+pub use orbit_camera_controller::OrbitCameraController;
@@ -0,0 +1,65 @@
+#[derive(Debug, Clone, PartialEq)]
+pub struct Camera {
+    pub position: vek::Vec3<f32>,
+    pub target: vek::Vec3<f32>,
+    pub up: vek::Vec3<f32>,
+    pub fovy: f32,
+    pub aspect: f32,
+    pub znear: f32,
+    pub zfar: f32,
+}
+impl Camera {
+    pub fn new(size: winit::dpi::PhysicalSize<u32>) -> Self {
+        Self {
+            position: vek::Vec3::new(0.0, 1.5, 5.0),
+            target: vek::Vec3::zero(),
+            up: vek::Vec3::unit_y(),
+            fovy: 45.0_f32.to_radians(),
+            aspect: size.width as f32 / size.height.max(1) as f32,
+            znear: 0.1,
+            zfar: 100.0,
+        }
+    }
+
+    pub fn resize(&mut self, size: winit::dpi::PhysicalSize<u32>) {
+        if size.width > 0 && size.height > 0 {
+            self.aspect = size.width as f32 / size.height as f32;
+        }
+    }
+
+    pub fn view(&self) -> vek::Mat4<f32> {
+        vek::Mat4::look_at_rh(self.position, self.target, self.up)
+    }
+
+    // Right handed projection with wgpu's [0, 1] depth range.
+    pub fn projection(&self) -> vek::Mat4<f32> {
+        vek::Mat4::perspective_rh_zo(self.fovy, self.aspect, self.znear, self.zfar)
+    }
+
+    pub fn view_projection(&self) -> vek::Mat4<f32> {
+        self.projection() * self.view()
+    }
+
+    // Camera between two fixed updates, the projection is taken from `current`.
+    pub fn interpolate(previous: &Self, current: &Self, alpha: f32) -> Self {
+        Self {
+            position: vek::Vec3::lerp(previous.position, current.position, alpha),
+            target: vek::Vec3::lerp(previous.target, current.target, alpha),
+            ..current.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_project_target_to_screen_center() {
+        let camera = Camera::new(winit::dpi::PhysicalSize::new(1280, 720));
+        let clip = camera.view_projection() * vek::Vec4::from_point(camera.target);
+        let ndc = clip.xyz() / clip.w;
+        assert!(ndc.x.abs() < 1e-5 && ndc.y.abs() < 1e-5);
+        assert!(ndc.z > 0.0 && ndc.z < 1.0);
+    }
+}
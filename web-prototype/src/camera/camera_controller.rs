@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+use crate::camera::{Camera, FlyCameraController, OrbitCameraController};
+use crate::input::Input;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum CameraControllerKind {
+    Orbit,
+    Fly,
+}
+
+#[derive(Debug, Clone)]
+pub enum CameraController {
+    Orbit(OrbitCameraController),
+    Fly(FlyCameraController),
+}
+impl CameraController {
+    pub fn new(kind: CameraControllerKind) -> Self {
+        match kind {
+            CameraControllerKind::Orbit => Self::Orbit(OrbitCameraController::new()),
+            CameraControllerKind::Fly => Self::Fly(FlyCameraController::new()),
+        }
+    }
+
+    pub fn kind(&self) -> CameraControllerKind {
+        match self {
+            Self::Orbit(_) => CameraControllerKind::Orbit,
+            Self::Fly(_) => CameraControllerKind::Fly,
+        }
+    }
+
+    // Motion driven by held actions, e.g. keys and gamepad sticks.
+    pub fn fixed_update(&mut self, camera: &mut Camera, input: &Input, delta_time: f32) {
+        match self {
+            Self::Orbit(controller) => controller.fixed_update(camera, input, delta_time),
+            Self::Fly(controller) => controller.fixed_update(camera, input, delta_time),
+        }
+    }
+
+    // Motion driven by the pointer and the wheel of this frame.
+    pub fn update(&mut self, camera: &mut Camera, input: &Input) {
+        match self {
+            Self::Orbit(controller) => controller.update(camera, input),
+            Self::Fly(controller) => controller.update(camera, input),
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use crate::camera::Camera;
+use crate::input::{actions, Input};
+
+// Moves the camera with the move actions and looks around while the look action is held
+// or with the look actions.
+#[derive(Debug, Clone)]
+pub struct FlyCameraController {
+    pub move_speed: f32,
+    pub look_speed: f32,
+    // Radians per second of the look actions, e.g. a gamepad stick.
+    pub stick_look_speed: f32,
+}
+impl FlyCameraController {
+    pub fn new() -> Self {
+        Self {
+            move_speed: 2.0,
+            look_speed: 0.003,
+            stick_look_speed: 2.0,
+        }
+    }
+
+    pub fn fixed_update(&mut self, camera: &mut Camera, input: &Input, delta_time: f32) {
+        let yaw_delta = input.axis(actions::LOOK_LEFT, actions::LOOK_RIGHT);
+        let pitch_delta = input.axis(actions::LOOK_UP, actions::LOOK_DOWN);
+        let forward = Self::turn(
+            camera,
+            yaw_delta * self.stick_look_speed * delta_time,
+            pitch_delta * self.stick_look_speed * delta_time,
+        );
+        let right = forward.cross(camera.up).normalized();
+
+        let velocity = forward * input.axis(actions::MOVE_FORWARD, actions::MOVE_BACKWARD)
+            + right * input.axis(actions::MOVE_RIGHT, actions::MOVE_LEFT)
+            + camera.up * input.axis(actions::MOVE_UP, actions::MOVE_DOWN);
+        camera.position += velocity * self.move_speed * delta_time;
+        camera.target = camera.position + forward;
+    }
+
+    pub fn update(&mut self, camera: &mut Camera, input: &Input) {
+        if input.is_pressed(actions::CAMERA_LOOK) {
+            let pointer_delta = input.pointer_delta();
+            Self::turn(
+                camera,
+                -pointer_delta.x * self.look_speed,
+                -pointer_delta.y * self.look_speed,
+            );
+        }
+    }
+
+    // Rotate the view direction and return it.
+    fn turn(camera: &mut Camera, yaw_delta: f32, pitch_delta: f32) -> vek::Vec3<f32> {
+        let direction = (camera.target - camera.position).normalized();
+        let yaw = direction.x.atan2(-direction.z) - yaw_delta;
+        let pitch = (direction.y.asin() + pitch_delta).clamp(
+            -std::f32::consts::FRAC_PI_2 + 0.01,
+            std::f32::consts::FRAC_PI_2 - 0.01,
+        );
+        let forward = vek::Vec3::new(
+            pitch.cos() * yaw.sin(),
+            pitch.sin(),
+            -pitch.cos() * yaw.cos(),
+        );
+        camera.target = camera.position + forward;
+        forward
+    }
+}
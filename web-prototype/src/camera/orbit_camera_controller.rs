@@ -0,0 +1,65 @@
+use crate::camera::Camera;
+use crate::input::{actions, Input};
+
+// Rotates the camera around its target while the rotate action is held or with the look actions
+// and zooms with the zoom actions.
+#[derive(Debug, Clone)]
+pub struct OrbitCameraController {
+    pub rotate_speed: f32,
+    // Radians per second of the look actions, e.g. a gamepad stick.
+    pub look_speed: f32,
+    pub zoom_speed: f32,
+}
+impl OrbitCameraController {
+    pub fn new() -> Self {
+        Self {
+            rotate_speed: 0.005,
+            look_speed: 2.0,
+            zoom_speed: 0.1,
+        }
+    }
+
+    pub fn fixed_update(&mut self, camera: &mut Camera, input: &Input, delta_time: f32) {
+        let yaw_delta = input.axis(actions::LOOK_LEFT, actions::LOOK_RIGHT);
+        let pitch_delta = input.axis(actions::LOOK_UP, actions::LOOK_DOWN);
+        Self::orbit(
+            camera,
+            yaw_delta * self.look_speed * delta_time,
+            pitch_delta * self.look_speed * delta_time,
+            0.0,
+        );
+    }
+
+    pub fn update(&mut self, camera: &mut Camera, input: &Input) {
+        let pointer_delta = if input.is_pressed(actions::CAMERA_ROTATE) {
+            input.pointer_delta()
+        } else {
+            vek::Vec2::zero()
+        };
+        Self::orbit(
+            camera,
+            -pointer_delta.x * self.rotate_speed,
+            -pointer_delta.y * self.rotate_speed,
+            input.axis(actions::ZOOM_IN, actions::ZOOM_OUT) * self.zoom_speed,
+        );
+    }
+
+    fn orbit(camera: &mut Camera, yaw_delta: f32, pitch_delta: f32, zoom: f32) {
+        let offset = camera.position - camera.target;
+        let radius = offset.magnitude();
+        let yaw = offset.x.atan2(offset.z) + yaw_delta;
+        let pitch = (offset.y / radius).asin() + pitch_delta;
+        let pitch = pitch.clamp(
+            -std::f32::consts::FRAC_PI_2 + 0.01,
+            std::f32::consts::FRAC_PI_2 - 0.01,
+        );
+        let radius = (radius * (1.0 - zoom)).max(camera.znear * 2.0);
+
+        camera.position = camera.target
+            + vek::Vec3::new(
+                radius * pitch.cos() * yaw.sin(),
+                radius * pitch.sin(),
+                radius * pitch.cos() * yaw.cos(),
+            );
+    }
+}
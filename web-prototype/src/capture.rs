@@ -0,0 +1,5 @@
+mod frame_capture;
+mod save_capture;
+
+pub use frame_capture::{CaptureRequest, CaptureSettings, FrameCapture};
+pub use save_capture::save_capture;
@@ -0,0 +1,291 @@
+use image::ImageEncoder;
+use instant::Duration;
+use serde::{Deserialize, Serialize};
+use shader_library::ShaderLibrary;
+use std::collections::HashMap;
+
+use crate::capture::save_capture;
+use crate::capture::save_capture::timestamp;
+use crate::pass::BlitPass;
+use crate::render_target::{OffscreenFrame, OffscreenTarget};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureRequest {
+    Screenshot,
+    // Frames stepped by a fixed `1 / fps` seconds, however long they take to render.
+    Sequence { frames: u32, fps: u32 },
+}
+
+// Capture controls of the renderer window, the sequence length is kept in the user settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CaptureSettings {
+    pub frames: u32,
+    pub fps: u32,
+    // Taken by the main state and passed on to the frame capture.
+    #[serde(skip)]
+    pub request: Option<CaptureRequest>,
+    // Frames of the sequence still to record.
+    #[serde(skip)]
+    pub remaining_frames: u32,
+}
+impl Default for CaptureSettings {
+    fn default() -> Self {
+        Self {
+            frames: 120,
+            fps: 60,
+            request: None,
+            remaining_frames: 0,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CaptureSequence {
+    name: String,
+    frame: u32,
+    frames: u32,
+    frame_time: Duration,
+    // Set once the frames step with `frame_time`, the frame it was requested in does not.
+    recording: bool,
+}
+
+#[derive(Debug)]
+struct CaptureTarget {
+    target: OffscreenTarget,
+    blit: BlitPass,
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+}
+impl CaptureTarget {
+    fn new(
+        device: &wgpu::Device,
+        shaders: &ShaderLibrary,
+        config: &wgpu::SurfaceConfiguration,
+    ) -> Self {
+        let target = OffscreenTarget::new(device, config);
+        let blit = BlitPass::new(device, shaders, config.format, &target.create_view());
+        Self {
+            target,
+            blit,
+            format: config.format,
+            width: config.width,
+            height: config.height,
+        }
+    }
+
+    fn matches(&self, config: &wgpu::SurfaceConfiguration) -> bool {
+        (self.format, self.width, self.height) == (config.format, config.width, config.height)
+    }
+}
+
+// Saves the frames shown on the surface as PNGs.
+// Surfaces can not be copied from on every backend, so captured frames are rendered
+// into an offscreen target, blitted to the surface and read back asynchronously.
+#[derive(Debug, Default)]
+pub struct FrameCapture {
+    target: Option<CaptureTarget>,
+    screenshot: bool,
+    sequence: Option<CaptureSequence>,
+    // File names of the frames in flight by their index in the target.
+    pending: HashMap<u64, String>,
+    presented: u64,
+}
+impl FrameCapture {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn request(&mut self, request: CaptureRequest) {
+        match request {
+            CaptureRequest::Screenshot => self.screenshot = true,
+            CaptureRequest::Sequence { .. } if self.sequence.is_some() => {
+                log::warn!("Already recording a sequence");
+            }
+            CaptureRequest::Sequence { frames, fps } => {
+                self.sequence = Some(CaptureSequence {
+                    name: format!("sequence_{}", timestamp()),
+                    frame: 0,
+                    frames,
+                    frame_time: Duration::from_secs(1) / fps.max(1),
+                    recording: false,
+                });
+            }
+        }
+    }
+
+    pub fn remaining_frames(&self) -> u32 {
+        self.sequence
+            .as_ref()
+            .map(|sequence| sequence.frames - sequence.frame)
+            .unwrap_or(0)
+    }
+
+    // Called before the update, the fixed frame time while a sequence records.
+    pub fn begin_frame(&mut self) -> Option<Duration> {
+        let sequence = self.sequence.as_mut()?;
+        sequence.recording = true;
+        Some(sequence.frame_time)
+    }
+
+    // File name of the frame about to render, `None` when it is not captured.
+    pub fn next_frame(&mut self) -> Option<String> {
+        match &mut self.sequence {
+            Some(sequence) if sequence.recording => {
+                let name = format!("{}_{:04}.png", sequence.name, sequence.frame);
+                sequence.frame += 1;
+                if sequence.frame >= sequence.frames {
+                    self.sequence = None;
+                }
+                // A screenshot requested meanwhile is the same frame.
+                self.screenshot = false;
+                Some(name)
+            }
+            _ if std::mem::take(&mut self.screenshot) => {
+                Some(format!("screenshot_{}.png", timestamp()))
+            }
+            _ => None,
+        }
+    }
+
+    // View the states render a captured frame into instead of the surface.
+    pub fn view(
+        &mut self,
+        device: &wgpu::Device,
+        shaders: &ShaderLibrary,
+        config: &wgpu::SurfaceConfiguration,
+    ) -> wgpu::TextureView {
+        if !self
+            .target
+            .as_ref()
+            .map(|target| target.matches(config))
+            .unwrap_or(false)
+        {
+            // Frames of the old target still have to be saved before it is dropped.
+            self.save_frames(device, true);
+            self.target = Some(CaptureTarget::new(device, shaders, config));
+            self.presented = 0;
+        }
+        self.target().target.create_view()
+    }
+
+    // Show the captured frame on the surface and start reading it back.
+    pub fn present(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        view: &wgpu::TextureView,
+        name: String,
+    ) {
+        let target = self.target();
+        target.blit.draw(device, queue, view);
+        target.target.present(device, queue);
+        self.pending.insert(self.presented, name);
+        self.presented += 1;
+    }
+
+    // Encode and save the frames whose readback finished.
+    // With `wait` the device is polled until every frame in flight is saved.
+    pub fn save_frames(&mut self, device: &wgpu::Device, wait: bool) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let target = match &mut self.target {
+            Some(target) => target,
+            None => return,
+        };
+        for frame in target.target.frames(device, wait) {
+            let name = match self.pending.remove(&frame.index) {
+                Some(name) => name,
+                None => continue,
+            };
+            let frame = to_rgba(frame, target.format);
+            match encode_png(&frame) {
+                Ok(png) => save_capture(&name, &png),
+                Err(e) => log::warn!("Failed to encode {}: {}", name, e),
+            }
+        }
+    }
+
+    // The target belongs to the old device, the frames in flight are lost with it.
+    pub fn recreate(&mut self) {
+        if !self.pending.is_empty() {
+            log::warn!("Lost {} captured frames", self.pending.len());
+        }
+        self.target = None;
+        self.pending.clear();
+        self.presented = 0;
+    }
+
+    fn target(&mut self) -> &mut CaptureTarget {
+        self.target
+            .as_mut()
+            .unwrap_or_else(|| panic!("Capture target is not created."))
+    }
+}
+
+// Surfaces are often BGRA, the PNGs are RGBA.
+fn to_rgba(mut frame: OffscreenFrame, format: wgpu::TextureFormat) -> OffscreenFrame {
+    if matches!(
+        format,
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+    ) {
+        for pixel in frame.rgba.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+    frame
+}
+
+fn encode_png(frame: &OffscreenFrame) -> Result<Vec<u8>, image::ImageError> {
+    let mut png = vec![];
+    image::codecs::png::PngEncoder::new(&mut png).write_image(
+        &frame.rgba,
+        frame.width,
+        frame.height,
+        image::ColorType::Rgba8,
+    )?;
+    Ok(png)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_record_sequence_after_request_frame() {
+        let mut capture = FrameCapture::new();
+        capture.request(CaptureRequest::Sequence { frames: 2, fps: 30 });
+        capture.request(CaptureRequest::Screenshot);
+        // The frame of the request already stepped with the real frame time.
+        let screenshot = capture.next_frame().unwrap();
+        assert!(screenshot.starts_with("screenshot_"));
+        assert_eq!(capture.remaining_frames(), 2);
+
+        assert_eq!(capture.begin_frame(), Some(Duration::from_secs(1) / 30));
+        assert!(capture.next_frame().unwrap().ends_with("_0000.png"));
+        assert_eq!(capture.begin_frame(), Some(Duration::from_secs(1) / 30));
+        assert!(capture.next_frame().unwrap().ends_with("_0001.png"));
+
+        assert_eq!(capture.remaining_frames(), 0);
+        assert_eq!(capture.begin_frame(), None);
+        assert_eq!(capture.next_frame(), None);
+    }
+
+    #[test]
+    fn it_should_encode_bgra_frames_as_rgba_png() {
+        let frame = OffscreenFrame {
+            index: 0,
+            width: 1,
+            height: 1,
+            rgba: vec![1, 2, 3, 4],
+        };
+        let frame = to_rgba(frame, wgpu::TextureFormat::Bgra8UnormSrgb);
+        assert_eq!(frame.rgba, vec![3, 2, 1, 4]);
+
+        let png = encode_png(&frame).unwrap();
+        let image = image::load_from_memory(&png).unwrap().to_rgba8();
+        assert_eq!(image.into_raw(), vec![3, 2, 1, 4]);
+    }
+}
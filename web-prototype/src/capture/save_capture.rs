@@ -0,0 +1,51 @@
+// Directory the captures are written to, relative to the working directory.
+#[cfg(not(target_arch = "wasm32"))]
+const CAPTURE_DIR: &str = "captures";
+
+// Write the capture to `captures/<file_name>`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_capture(file_name: &str, bytes: &[u8]) {
+    let path = std::path::Path::new(CAPTURE_DIR).join(file_name);
+    let result = std::fs::create_dir_all(CAPTURE_DIR).and_then(|()| std::fs::write(&path, bytes));
+    match result {
+        Ok(()) => log::info!("Saved {}", path.display()),
+        Err(e) => log::warn!("Failed to save {}: {}", path.display(), e),
+    }
+}
+
+// Download the capture as `file_name`.
+#[cfg(target_arch = "wasm32")]
+pub fn save_capture(file_name: &str, bytes: &[u8]) {
+    use wasm_bindgen::JsCast;
+
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+    let blob = web_sys::Blob::new_with_u8_array_sequence(&parts)
+        .unwrap_or_else(|_| panic!("Failed to create capture blob"));
+    let url = web_sys::Url::create_object_url_with_blob(&blob)
+        .unwrap_or_else(|_| panic!("Failed to create capture url"));
+    let anchor = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.create_element("a").ok())
+        .and_then(|element| element.dyn_into::<web_sys::HtmlAnchorElement>().ok())
+        .unwrap_or_else(|| panic!("Failed to create download link"));
+    anchor.set_href(&url);
+    anchor.set_download(file_name);
+    anchor.click();
+    if web_sys::Url::revoke_object_url(&url).is_err() {
+        log::warn!("Failed to revoke capture url");
+    }
+}
+
+// Milliseconds since the Unix epoch, keeps the captures of different runs apart.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn timestamp() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn timestamp() -> u64 {
+    js_sys::Date::now() as u64
+}
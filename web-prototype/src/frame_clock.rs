@@ -0,0 +1,89 @@
+use instant::{Duration, Instant};
+
+// Timing of one frame, `fixed_steps` fixed updates are due before the variable update.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameTick {
+    pub delta_time: f32,
+    pub fixed_steps: u32,
+    // How far the frame is between the last two fixed updates, for interpolation.
+    pub alpha: f32,
+}
+
+// Splits the frame time into fixed simulation steps and a remainder to interpolate with.
+// The same clock runs on native and wasm, driven by the redraws of the window.
+#[derive(Debug)]
+pub struct FrameClock {
+    pub fixed_delta: Duration,
+    // Longer frames are cut so a slow frame does not need ever more steps to catch up.
+    pub max_frame_time: Duration,
+    accumulator: Duration,
+    last_tick: Option<Instant>,
+}
+impl FrameClock {
+    pub fn new(fixed_delta: Duration) -> Self {
+        Self {
+            fixed_delta,
+            max_frame_time: Duration::from_millis(250),
+            accumulator: Duration::ZERO,
+            last_tick: None,
+        }
+    }
+
+    // The first tick has no previous frame and does not step.
+    pub fn tick(&mut self) -> FrameTick {
+        let now = Instant::now();
+        let frame_time = self
+            .last_tick
+            .map(|last_tick| now - last_tick)
+            .unwrap_or_default();
+        self.last_tick = Some(now);
+        self.advance(frame_time)
+    }
+
+    pub fn advance(&mut self, frame_time: Duration) -> FrameTick {
+        let frame_time = frame_time.min(self.max_frame_time);
+        self.accumulator += frame_time;
+
+        let mut fixed_steps = 0;
+        while self.accumulator >= self.fixed_delta {
+            self.accumulator -= self.fixed_delta;
+            fixed_steps += 1;
+        }
+
+        FrameTick {
+            delta_time: frame_time.as_secs_f32(),
+            fixed_steps,
+            alpha: self.accumulator.as_secs_f32() / self.fixed_delta.as_secs_f32(),
+        }
+    }
+}
+impl Default for FrameClock {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(1) / 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_step_and_interpolate() {
+        let mut clock = FrameClock::new(Duration::from_millis(10));
+        let tick = clock.advance(Duration::from_millis(25));
+        assert_eq!(tick.fixed_steps, 2);
+        assert!((tick.alpha - 0.5).abs() < 1e-4);
+
+        let tick = clock.advance(Duration::from_millis(5));
+        assert_eq!(tick.fixed_steps, 1);
+        assert!(tick.alpha.abs() < 1e-4);
+    }
+
+    #[test]
+    fn it_should_clamp_catch_up_steps() {
+        let mut clock = FrameClock::new(Duration::from_millis(10));
+        let tick = clock.advance(Duration::from_secs(10));
+        assert_eq!(tick.fixed_steps, 25);
+        assert!((tick.delta_time - 0.25).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,169 @@
+use instant::Duration;
+use std::path::PathBuf;
+use thiserror::Error;
+
+use crate::profiler::*;
+use crate::render_target::OffscreenFrame;
+use crate::renderer_config::RendererConfig;
+use crate::resources::*;
+use crate::runtime::Runtime;
+use crate::state::*;
+
+#[derive(Debug, Error)]
+pub enum HeadlessError {
+    #[error("Failed to create output directory: {0}")]
+    CreateDirError(std::io::Error),
+    #[error("Failed to save frame: {0}")]
+    SaveError(image::ImageError),
+    #[error("Failed to save trace: {0}")]
+    TraceError(std::io::Error),
+}
+
+#[derive(Debug, Clone)]
+pub struct HeadlessConfig {
+    pub width: u32,
+    pub height: u32,
+    pub frames: u32,
+    pub output_dir: PathBuf,
+    pub view_state: Option<MainStateViewState>,
+    pub renderer_config: RendererConfig,
+    // Write the CPU and GPU timings of the frames to `<output_dir>/trace.json`.
+    pub profile: bool,
+}
+impl HeadlessConfig {
+    pub fn new(output_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            width: 800,
+            height: 600,
+            frames: 1,
+            output_dir: output_dir.into(),
+            view_state: None,
+            renderer_config: RendererConfig::default(),
+            profile: false,
+        }
+    }
+
+    // WEB_PROTOTYPE_HEADLESS=<output dir>
+    // WEB_PROTOTYPE_HEADLESS_SIZE=<width>x<height>
+    // WEB_PROTOTYPE_HEADLESS_FRAMES=<count>
+    // WEB_PROTOTYPE_HEADLESS_VIEW=triangle|texture|mesh
+    // WEB_PROTOTYPE_HEADLESS_MSAA=<sample count>
+    // WEB_PROTOTYPE_HEADLESS_PROFILE=1
+    pub fn from_env() -> Option<Self> {
+        let mut config = Self::new(std::env::var("WEB_PROTOTYPE_HEADLESS").ok()?);
+        if let Some((width, height)) =
+            std::env::var("WEB_PROTOTYPE_HEADLESS_SIZE")
+                .ok()
+                .and_then(|size| {
+                    let (width, height) = size.split_once('x')?;
+                    Some((width.parse().ok()?, height.parse().ok()?))
+                })
+        {
+            config.width = width;
+            config.height = height;
+        }
+        if let Some(frames) = std::env::var("WEB_PROTOTYPE_HEADLESS_FRAMES")
+            .ok()
+            .and_then(|frames| frames.parse().ok())
+        {
+            config.frames = frames;
+        }
+        config.view_state = match std::env::var("WEB_PROTOTYPE_HEADLESS_VIEW").as_deref() {
+            Ok("triangle") => Some(MainStateViewState::triangle()),
+            Ok("texture") => Some(MainStateViewState::texture()),
+            Ok("mesh") => Some(MainStateViewState::mesh()),
+            _ => None,
+        };
+        if let Some(sample_count) = std::env::var("WEB_PROTOTYPE_HEADLESS_MSAA")
+            .ok()
+            .and_then(|sample_count| sample_count.parse().ok())
+        {
+            config.renderer_config.sample_count = sample_count;
+        }
+        config.profile = matches!(
+            std::env::var("WEB_PROTOTYPE_HEADLESS_PROFILE").as_deref(),
+            Ok("1") | Ok("true")
+        );
+        Some(config)
+    }
+}
+
+// Render frames of the main state without a window.
+// Waits until every resource is loaded so the frames do not contain placeholders.
+// Also returns the profiled frames, the loading frames are left out.
+pub fn render(config: &HeadlessConfig) -> (Vec<OffscreenFrame>, Vec<ProfilerFrame>) {
+    let runtime = Runtime::new();
+
+    let mut resources_loader = ResourcesLoader::new();
+    resources_loader.start_load(runtime.clone(), get_catalog());
+
+    let size = winit::dpi::PhysicalSize::new(config.width, config.height);
+    let instance = wgpu::Instance::new(wgpu::Backends::all());
+
+    let mut frames = vec![];
+    let mut profile = vec![];
+    runtime.block_on(async {
+        let mut state = State::new_headless(
+            size,
+            instance,
+            resources_loader,
+            config.renderer_config.clone(),
+        )
+        .await
+        .unwrap_or_else(|e| panic!("Failed to create GPU context: {}", e));
+        while !state.is_ready() {
+            state.update();
+            runtime.delay(Duration::from_millis(1)).await;
+        }
+        // Stream the resources finished during the last update.
+        state.update();
+        if let Some(view_state) = config.view_state.clone() {
+            state.set_view_state(view_state);
+        }
+        state.profiler().clear();
+
+        for _ in 0..config.frames {
+            state.update();
+            state
+                .render()
+                .unwrap_or_else(|e| panic!("Failed to render headless frame: {:?}", e));
+            frames.extend(state.offscreen_frames(false));
+        }
+        frames.extend(state.offscreen_frames(true));
+        // Finish the last frame, its GPU timestamps are collected during the next frame and are missing.
+        state.profiler().begin_frame();
+        profile = state.profiler().frames();
+    });
+    (frames, profile)
+}
+
+// Render frames and write them to `<output_dir>/frame_0000.png`, ...
+pub fn run(config: &HeadlessConfig) -> Result<Vec<PathBuf>, HeadlessError> {
+    std::fs::create_dir_all(&config.output_dir).map_err(HeadlessError::CreateDirError)?;
+    let (frames, profile) = render(config);
+    if config.profile {
+        log::info!("Frame times: {}", FrameStats::new(&profile));
+        for scope in ScopeStats::new(&profile, 1) {
+            log::info!(
+                "{}: cpu {:.3} ms, gpu {:.3} ms",
+                scope.name,
+                scope.cpu.unwrap_or_default(),
+                scope.gpu.unwrap_or_default()
+            );
+        }
+        let path = config.output_dir.join("trace.json");
+        std::fs::write(&path, chrome_trace(&profile)).map_err(HeadlessError::TraceError)?;
+        log::info!("Saved {}", path.display());
+    }
+    frames
+        .into_iter()
+        .map(|frame| {
+            let path = config
+                .output_dir
+                .join(format!("frame_{:04}.png", frame.index));
+            frame.save_png(&path).map_err(HeadlessError::SaveError)?;
+            log::info!("Saved {}", path.display());
+            Ok(path)
+        })
+        .collect()
+}
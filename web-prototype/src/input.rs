@@ -0,0 +1,6 @@
+pub mod actions;
+mod input;
+mod input_bindings;
+
+pub use input::Input;
+pub use input_bindings::{Binding, InputBindings};
@@ -0,0 +1,22 @@
+// Names of the actions in `assets/input_bindings.txt`.
+pub const QUIT: &str = "quit";
+pub const PAUSE: &str = "pause";
+pub const TOGGLE_PROFILER: &str = "toggle_profiler";
+pub const CAPTURE_SCREENSHOT: &str = "capture_screenshot";
+
+pub const CAMERA_ROTATE: &str = "camera_rotate";
+pub const CAMERA_LOOK: &str = "camera_look";
+pub const ZOOM_IN: &str = "zoom_in";
+pub const ZOOM_OUT: &str = "zoom_out";
+
+pub const MOVE_FORWARD: &str = "move_forward";
+pub const MOVE_BACKWARD: &str = "move_backward";
+pub const MOVE_RIGHT: &str = "move_right";
+pub const MOVE_LEFT: &str = "move_left";
+pub const MOVE_UP: &str = "move_up";
+pub const MOVE_DOWN: &str = "move_down";
+
+pub const LOOK_RIGHT: &str = "look_right";
+pub const LOOK_LEFT: &str = "look_left";
+pub const LOOK_UP: &str = "look_up";
+pub const LOOK_DOWN: &str = "look_down";
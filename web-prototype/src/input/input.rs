@@ -0,0 +1,306 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Debug};
+
+use winit::event::{
+    ElementState, Event, KeyboardInput, MouseScrollDelta, Touch, TouchPhase, WindowEvent,
+};
+
+use crate::input::*;
+
+// Keyboard, mouse, touch and gamepad state of the current frame, queried through named actions.
+// Input the UI wants is not seen by the actions, releases always are so nothing gets stuck.
+pub struct Input {
+    bindings: InputBindings,
+    gamepads: Option<gilrs::Gilrs>,
+
+    held: HashSet<Binding>,
+    pressed: HashSet<Binding>,
+    axes: HashMap<gilrs::Axis, f32>,
+    wheel: f32,
+    pointer_delta: vek::Vec2<f32>,
+    cursor_position: Option<winit::dpi::PhysicalPosition<f64>>,
+    touches: HashMap<u64, winit::dpi::PhysicalPosition<f64>>,
+
+    ui_wants_pointer: bool,
+    ui_wants_keyboard: bool,
+}
+impl Input {
+    const AXIS_DEAD_ZONE: f32 = 0.15;
+    // Pixels of pinch distance per wheel line.
+    const PINCH_SCALE: f32 = 100.0;
+
+    pub fn new(bindings: InputBindings) -> Self {
+        Self {
+            bindings,
+            gamepads: None,
+
+            held: HashSet::new(),
+            pressed: HashSet::new(),
+            axes: HashMap::new(),
+            wheel: 0.0,
+            pointer_delta: vek::Vec2::zero(),
+            cursor_position: None,
+            touches: HashMap::new(),
+
+            ui_wants_pointer: false,
+            ui_wants_keyboard: false,
+        }
+    }
+
+    // Poll connected gamepads, without them only keyboard, mouse and touch are tracked.
+    pub fn with_gamepads(self) -> Self {
+        let gamepads = gilrs::Gilrs::new()
+            .map_err(|e| log::warn!("Gamepads are not available: {}", e))
+            .ok();
+        Self { gamepads, ..self }
+    }
+
+    // Set from the UI of the top state after it was laid out.
+    pub fn set_ui_capture(&mut self, wants_pointer: bool, wants_keyboard: bool) {
+        self.ui_wants_pointer = wants_pointer;
+        self.ui_wants_keyboard = wants_keyboard;
+    }
+
+    fn press(&mut self, binding: Binding) {
+        if self.held.insert(binding) {
+            self.pressed.insert(binding);
+        }
+    }
+
+    fn release(&mut self, binding: Binding) {
+        self.held.remove(&binding);
+    }
+
+    pub fn handle_event(&mut self, winit_event: &Event<()>) {
+        let event = match winit_event {
+            Event::WindowEvent { event, .. } => event,
+            _ => return,
+        };
+        match event {
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state,
+                        virtual_keycode: Some(keycode),
+                        ..
+                    },
+                ..
+            } => match state {
+                ElementState::Pressed if !self.ui_wants_keyboard => {
+                    self.press(Binding::Key(*keycode))
+                }
+                ElementState::Pressed => {}
+                ElementState::Released => self.release(Binding::Key(*keycode)),
+            },
+            WindowEvent::MouseInput { state, button, .. } => match state {
+                ElementState::Pressed if !self.ui_wants_pointer => {
+                    self.press(Binding::Mouse(*button))
+                }
+                ElementState::Pressed => {}
+                ElementState::Released => self.release(Binding::Mouse(*button)),
+            },
+            WindowEvent::CursorMoved { position, .. } => {
+                if let (false, Some(previous)) = (self.ui_wants_pointer, self.cursor_position) {
+                    self.pointer_delta += vek::Vec2::new(
+                        (position.x - previous.x) as f32,
+                        (position.y - previous.y) as f32,
+                    );
+                }
+                self.cursor_position = Some(*position);
+            }
+            WindowEvent::MouseWheel { delta, .. } if !self.ui_wants_pointer => {
+                self.wheel += match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y,
+                    MouseScrollDelta::PixelDelta(position) => position.y as f32 / 100.0,
+                };
+            }
+            WindowEvent::Touch(touch) => self.handle_touch(touch),
+            WindowEvent::Focused(false) => {
+                self.held.clear();
+                self.touches.clear();
+            }
+            _ => {}
+        }
+    }
+
+    // One finger drags like a mouse button, two fingers pinch like the wheel.
+    fn handle_touch(&mut self, touch: &Touch) {
+        match touch.phase {
+            TouchPhase::Started => {
+                if self.ui_wants_pointer {
+                    return;
+                }
+                self.touches.insert(touch.id, touch.location);
+                if self.touches.len() == 1 {
+                    self.press(Binding::Touch);
+                } else {
+                    self.release(Binding::Touch);
+                }
+            }
+            TouchPhase::Moved => {
+                let pinch_distance = self.pinch_distance();
+                let previous = match self.touches.get_mut(&touch.id) {
+                    Some(previous) => std::mem::replace(previous, touch.location),
+                    None => return,
+                };
+                match (pinch_distance, self.pinch_distance()) {
+                    (Some(before), Some(after)) => {
+                        self.wheel += (after - before) / Self::PINCH_SCALE
+                    }
+                    _ => {
+                        self.pointer_delta += vek::Vec2::new(
+                            (touch.location.x - previous.x) as f32,
+                            (touch.location.y - previous.y) as f32,
+                        )
+                    }
+                }
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                self.touches.remove(&touch.id);
+                self.release(Binding::Touch);
+            }
+        }
+    }
+
+    fn pinch_distance(&self) -> Option<f32> {
+        let mut touches = self.touches.values();
+        match (touches.next(), touches.next(), touches.next()) {
+            (Some(a), Some(b), None) => Some(((a.x - b.x) as f32).hypot((a.y - b.y) as f32)),
+            _ => None,
+        }
+    }
+
+    // Read the gamepad events since the last frame, call before the states update.
+    pub fn poll_gamepads(&mut self) {
+        while let Some(gilrs::Event { event, .. }) = self
+            .gamepads
+            .as_mut()
+            .and_then(|gamepads| gamepads.next_event())
+        {
+            match event {
+                gilrs::EventType::ButtonPressed(button, _) => {
+                    self.press(Binding::GamepadButton(button))
+                }
+                gilrs::EventType::ButtonReleased(button, _) => {
+                    self.release(Binding::GamepadButton(button))
+                }
+                gilrs::EventType::AxisChanged(axis, value, _) => {
+                    self.axes.insert(axis, value);
+                }
+                gilrs::EventType::Disconnected => {
+                    self.held
+                        .retain(|binding| !matches!(binding, Binding::GamepadButton(_)));
+                    self.axes.clear();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Forget the presses and motion of this frame, call after the states updated.
+    pub fn end_frame(&mut self) {
+        self.pressed.clear();
+        self.wheel = 0.0;
+        self.pointer_delta = vek::Vec2::zero();
+    }
+
+    fn binding_value(&self, binding: &Binding) -> f32 {
+        match binding {
+            Binding::Wheel(direction) => direction.apply(self.wheel),
+            Binding::GamepadAxis(axis, direction) => {
+                let value = self.axes.get(axis).copied().unwrap_or_default();
+                if value.abs() < Self::AXIS_DEAD_ZONE {
+                    0.0
+                } else {
+                    direction.apply(value)
+                }
+            }
+            binding => {
+                if self.held.contains(binding) {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+
+    // Strength of the action, 1 for held buttons, the axis value or the wheel lines of this frame.
+    pub fn value(&self, action: &str) -> f32 {
+        self.bindings
+            .bindings(action)
+            .iter()
+            .map(|binding| self.binding_value(binding))
+            .fold(0.0, f32::max)
+    }
+
+    pub fn is_pressed(&self, action: &str) -> bool {
+        self.value(action) > 0.0
+    }
+
+    // Whether a button of the action went down this frame.
+    pub fn just_pressed(&self, action: &str) -> bool {
+        self.bindings
+            .bindings(action)
+            .iter()
+            .any(|binding| self.pressed.contains(binding))
+    }
+
+    // Difference of the positive and the negative action, e.g. for movement axes.
+    pub fn axis(&self, positive: &str, negative: &str) -> f32 {
+        self.value(positive) - self.value(negative)
+    }
+
+    // Pointer motion of this frame in pixels, from the mouse or a single finger.
+    pub fn pointer_delta(&self) -> vek::Vec2<f32> {
+        self.pointer_delta
+    }
+}
+impl Debug for Input {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Input")
+            .field("bindings", &self.bindings)
+            .field("held", &self.held)
+            .field("axes", &self.axes)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[allow(deprecated)]
+    fn mouse_event(state: ElementState) -> Event<'static, ()> {
+        Event::WindowEvent {
+            window_id: unsafe { winit::window::WindowId::dummy() },
+            event: WindowEvent::MouseInput {
+                device_id: unsafe { winit::event::DeviceId::dummy() },
+                state,
+                button: winit::event::MouseButton::Left,
+                modifiers: Default::default(),
+            },
+        }
+    }
+
+    #[test]
+    fn it_should_ignore_pointer_input_captured_by_ui() {
+        let mut input = Input::new(InputBindings::parse("rotate = mouse:Left").unwrap());
+
+        input.set_ui_capture(true, false);
+        input.handle_event(&mouse_event(ElementState::Pressed));
+        assert!(!input.is_pressed("rotate"));
+
+        input.set_ui_capture(false, false);
+        input.handle_event(&mouse_event(ElementState::Pressed));
+        assert!(input.just_pressed("rotate"));
+        input.end_frame();
+        assert!(input.is_pressed("rotate"));
+        assert!(!input.just_pressed("rotate"));
+
+        // Releases reach the actions even while the UI wants the pointer.
+        input.set_ui_capture(true, false);
+        input.handle_event(&mouse_event(ElementState::Released));
+        assert!(!input.is_pressed("rotate"));
+    }
+}
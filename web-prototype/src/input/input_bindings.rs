@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+
+use thiserror::Error;
+use winit::event::{MouseButton, VirtualKeyCode};
+
+#[derive(Debug, Error)]
+pub enum InputBindingsError {
+    #[error("Line {line}: expected `action = binding, ...`")]
+    SyntaxError { line: usize },
+    #[error("Line {line}: unknown binding `{binding}`")]
+    UnknownBindingError { line: usize, binding: String },
+    #[error("Failed to read input bindings: {0}")]
+    ReadError(#[from] std::io::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AxisDirection {
+    Positive,
+    Negative,
+}
+impl AxisDirection {
+    fn parse(sign: char) -> Option<Self> {
+        match sign {
+            '+' => Some(Self::Positive),
+            '-' => Some(Self::Negative),
+            _ => None,
+        }
+    }
+
+    // The half of the axis value pointing in this direction.
+    pub fn apply(self, value: f32) -> f32 {
+        match self {
+            Self::Positive => value.max(0.0),
+            Self::Negative => (-value).max(0.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Binding {
+    Key(VirtualKeyCode),
+    Mouse(MouseButton),
+    Wheel(AxisDirection),
+    Touch,
+    GamepadButton(gilrs::Button),
+    GamepadAxis(gilrs::Axis, AxisDirection),
+}
+impl Binding {
+    fn parse(binding: &str) -> Option<Self> {
+        let (kind, name) = binding.split_once(':').unwrap_or((binding, ""));
+        match kind {
+            "key" => key_from_name(name).map(Self::Key),
+            "mouse" => match name {
+                "Left" => Some(Self::Mouse(MouseButton::Left)),
+                "Right" => Some(Self::Mouse(MouseButton::Right)),
+                "Middle" => Some(Self::Mouse(MouseButton::Middle)),
+                _ => None,
+            },
+            "wheel" => AxisDirection::parse(name.chars().next()?).map(Self::Wheel),
+            "touch" if name.is_empty() => Some(Self::Touch),
+            "gamepad" => gamepad_button_from_name(name).map(Self::GamepadButton),
+            "axis" => {
+                let direction = AxisDirection::parse(name.chars().next()?)?;
+                gamepad_axis_from_name(&name[1..]).map(|axis| Self::GamepadAxis(axis, direction))
+            }
+            _ => None,
+        }
+    }
+}
+
+// Named actions and the bindings triggering them.
+#[derive(Debug, Clone, Default)]
+pub struct InputBindings {
+    actions: HashMap<String, Vec<Binding>>,
+}
+impl InputBindings {
+    const DEFAULT: &'static str = include_str!("../../assets/input_bindings.txt");
+
+    pub fn parse(source: &str) -> Result<Self, InputBindingsError> {
+        let mut actions = HashMap::new();
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (action, bindings) = line
+                .split_once('=')
+                .ok_or(InputBindingsError::SyntaxError { line: line_number })?;
+            let action = action.trim();
+            if action.is_empty() {
+                return Err(InputBindingsError::SyntaxError { line: line_number });
+            }
+            let bindings = bindings
+                .split(',')
+                .map(str::trim)
+                .filter(|binding| !binding.is_empty())
+                .map(|binding| {
+                    Binding::parse(binding).ok_or_else(|| InputBindingsError::UnknownBindingError {
+                        line: line_number,
+                        binding: binding.to_string(),
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            actions
+                .entry(action.to_string())
+                .or_insert_with(Vec::new)
+                .extend(bindings);
+        }
+        Ok(Self { actions })
+    }
+
+    // The bindings file set by `WEB_PROTOTYPE_INPUT_BINDINGS`, the bundled one otherwise.
+    // Invalid files fall back to the bundled bindings.
+    pub fn load() -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Ok(path) = std::env::var("WEB_PROTOTYPE_INPUT_BINDINGS") {
+            match std::fs::read_to_string(&path)
+                .map_err(InputBindingsError::from)
+                .and_then(|source| Self::parse(&source))
+            {
+                Ok(bindings) => return bindings,
+                Err(e) => log::warn!("{}: {}, using the default bindings.", path, e),
+            }
+        }
+        Self::parse(Self::DEFAULT)
+            .unwrap_or_else(|e| panic!("Failed to parse default input bindings: {}", e))
+    }
+
+    pub fn bindings(&self, action: &str) -> &[Binding] {
+        match self.actions.get(action) {
+            Some(bindings) => bindings,
+            None => &[],
+        }
+    }
+}
+
+macro_rules! from_name {
+    ($name:expr, $ty:path, [$($variant:ident),* $(,)?]) => {
+        match $name {
+            $(stringify!($variant) => Some(<$ty>::$variant),)*
+            _ => None,
+        }
+    };
+}
+
+fn key_from_name(name: &str) -> Option<VirtualKeyCode> {
+    from_name!(
+        name,
+        VirtualKeyCode,
+        [
+            Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9, Key0, A, B, C, D, E, F, G, H, I,
+            J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, Escape, F1, F2, F3, F4, F5, F6, F7,
+            F8, F9, F10, F11, F12, Insert, Home, Delete, End, PageDown, PageUp, Left, Up, Right,
+            Down, Back, Return, Space, Tab, LAlt, LControl, LShift, RAlt, RControl, RShift,
+        ]
+    )
+}
+
+fn gamepad_button_from_name(name: &str) -> Option<gilrs::Button> {
+    from_name!(
+        name,
+        gilrs::Button,
+        [
+            South,
+            East,
+            North,
+            West,
+            C,
+            Z,
+            LeftTrigger,
+            LeftTrigger2,
+            RightTrigger,
+            RightTrigger2,
+            Select,
+            Start,
+            Mode,
+            LeftThumb,
+            RightThumb,
+            DPadUp,
+            DPadDown,
+            DPadLeft,
+            DPadRight,
+        ]
+    )
+}
+
+fn gamepad_axis_from_name(name: &str) -> Option<gilrs::Axis> {
+    from_name!(
+        name,
+        gilrs::Axis,
+        [
+            LeftStickX,
+            LeftStickY,
+            LeftZ,
+            RightStickX,
+            RightStickY,
+            RightZ,
+            DPadX,
+            DPadY
+        ]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_parse_input_bindings() {
+        let bindings = InputBindings::parse(
+            "# comment\n\
+             jump = key:Space, gamepad:South # trailing comment\n\
+             \n\
+             move_left = axis:-LeftStickX, wheel:+\n\
+             jump = mouse:Left, touch\n",
+        )
+        .unwrap();
+        assert_eq!(
+            bindings.bindings("jump"),
+            &[
+                Binding::Key(VirtualKeyCode::Space),
+                Binding::GamepadButton(gilrs::Button::South),
+                Binding::Mouse(MouseButton::Left),
+                Binding::Touch,
+            ]
+        );
+        assert_eq!(
+            bindings.bindings("move_left"),
+            &[
+                Binding::GamepadAxis(gilrs::Axis::LeftStickX, AxisDirection::Negative),
+                Binding::Wheel(AxisDirection::Positive),
+            ]
+        );
+        assert!(bindings.bindings("unbound").is_empty());
+
+        assert!(matches!(
+            InputBindings::parse("jump key:Space"),
+            Err(InputBindingsError::SyntaxError { line: 1 })
+        ));
+        assert!(matches!(
+            InputBindings::parse("\njump = key:Spacebar"),
+            Err(InputBindingsError::UnknownBindingError { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn it_should_parse_default_input_bindings() {
+        InputBindings::parse(InputBindings::DEFAULT).unwrap();
+    }
+}
@@ -0,0 +1,149 @@
+use std::f32::consts::PI;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirectionalLight {
+    // Direction the light travels in, in world space.
+    pub direction: vek::Vec3<f32>,
+    pub color: vek::Rgb<f32>,
+    pub intensity: f32,
+}
+impl Default for DirectionalLight {
+    fn default() -> Self {
+        Self {
+            direction: vek::Vec3::new(-0.4, -1.0, -0.3).normalized(),
+            color: vek::Rgb::new(1.0, 0.96, 0.9),
+            intensity: 3.0,
+        }
+    }
+}
+
+// Diffuse image based light stored as 9 spherical harmonics coefficients.
+// The coefficients are already convolved with the clamped cosine lobe and divided by PI,
+// so evaluating them at a normal gives the Lambertian outgoing radiance for a white albedo.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AmbientLight {
+    pub sh: [vek::Rgb<f32>; 9],
+}
+impl AmbientLight {
+    fn basis(dir: vek::Vec3<f32>) -> [f32; 9] {
+        let vek::Vec3 { x, y, z } = dir;
+        [
+            0.282095,
+            0.488603 * y,
+            0.488603 * z,
+            0.488603 * x,
+            1.092548 * x * y,
+            1.092548 * y * z,
+            0.315392 * (3.0 * z * z - 1.0),
+            1.092548 * x * z,
+            0.546274 * (x * x - y * y),
+        ]
+    }
+
+    // Project an equirectangular radiance image (rows from +Y to -Y) onto the SH basis.
+    pub fn from_equirectangular(width: usize, height: usize, radiance: &[vek::Rgb<f32>]) -> Self {
+        const BAND_FACTORS: [f32; 9] = [
+            PI,
+            2.0 * PI / 3.0,
+            2.0 * PI / 3.0,
+            2.0 * PI / 3.0,
+            PI / 4.0,
+            PI / 4.0,
+            PI / 4.0,
+            PI / 4.0,
+            PI / 4.0,
+        ];
+
+        let mut sh = [vek::Rgb::zero(); 9];
+        for row in 0..height {
+            let theta = (row as f32 + 0.5) / height as f32 * PI;
+            let solid_angle = theta.sin() * (PI / height as f32) * (2.0 * PI / width as f32);
+            for column in 0..width {
+                let phi = (column as f32 + 0.5) / width as f32 * 2.0 * PI;
+                let dir = vek::Vec3::new(
+                    theta.sin() * phi.sin(),
+                    theta.cos(),
+                    theta.sin() * phi.cos(),
+                );
+                let color = radiance[row * width + column];
+                for (coefficient, basis) in sh.iter_mut().zip(Self::basis(dir)) {
+                    *coefficient += color * basis * solid_angle;
+                }
+            }
+        }
+        for (coefficient, band_factor) in sh.iter_mut().zip(BAND_FACTORS) {
+            *coefficient *= band_factor / PI;
+        }
+        Self { sh }
+    }
+
+    // Procedural sky gradient used until environment maps are available.
+    pub fn sky(sky: vek::Rgb<f32>, horizon: vek::Rgb<f32>, ground: vek::Rgb<f32>) -> Self {
+        let (width, height) = (64, 32);
+        let radiance = (0..height)
+            .flat_map(|row| {
+                let y = 1.0 - 2.0 * (row as f32 + 0.5) / height as f32;
+                let color = if y > 0.0 {
+                    vek::Lerp::lerp(horizon, sky, y)
+                } else {
+                    vek::Lerp::lerp(horizon, ground, -y)
+                };
+                vec![color; width]
+            })
+            .collect::<Vec<_>>();
+        Self::from_equirectangular(width, height, &radiance)
+    }
+}
+impl Default for AmbientLight {
+    fn default() -> Self {
+        Self::sky(
+            vek::Rgb::new(0.35, 0.45, 0.6),
+            vek::Rgb::new(0.5, 0.5, 0.5),
+            vek::Rgb::new(0.15, 0.13, 0.12),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Irradiance the mesh shader computes from the coefficients.
+    fn evaluate(ambient: &AmbientLight, normal: vek::Vec3<f32>) -> vek::Rgb<f32> {
+        ambient
+            .sh
+            .iter()
+            .zip(AmbientLight::basis(normal))
+            .fold(vek::Rgb::zero(), |sum, (coefficient, basis)| {
+                sum + *coefficient * basis
+            })
+    }
+
+    #[test]
+    fn it_should_reproduce_constant_environment() {
+        let radiance = vec![vek::Rgb::new(1.0, 0.5, 0.25); 64 * 32];
+        let ambient = AmbientLight::from_equirectangular(64, 32, &radiance);
+        for normal in [
+            vek::Vec3::unit_x(),
+            vek::Vec3::unit_y(),
+            -vek::Vec3::unit_z(),
+        ] {
+            let color = evaluate(&ambient, normal);
+            assert!((color.r - 1.0).abs() < 1e-2);
+            assert!((color.g - 0.5).abs() < 1e-2);
+            assert!((color.b - 0.25).abs() < 1e-2);
+        }
+    }
+
+    #[test]
+    fn it_should_be_brighter_towards_bright_sky() {
+        let ambient = AmbientLight::sky(
+            vek::Rgb::new(1.0, 1.0, 1.0),
+            vek::Rgb::new(0.5, 0.5, 0.5),
+            vek::Rgb::new(0.0, 0.0, 0.0),
+        );
+        assert!(
+            evaluate(&ambient, vek::Vec3::unit_y()).r > evaluate(&ambient, -vek::Vec3::unit_y()).r
+        );
+    }
+}
@@ -1,8 +1,22 @@
 mod app;
+mod camera;
+mod capture;
+mod frame_clock;
+#[cfg(not(target_arch = "wasm32"))]
+mod headless;
+mod input;
+mod light;
 mod pass;
+mod profiler;
+mod render_graph;
+mod render_target;
+mod renderer_config;
 mod resources;
 mod runtime;
+mod scene;
+mod settings;
 mod state;
+mod ui;
 mod utils;
 
 use app::App;
@@ -18,6 +32,11 @@ fn main() {
     {
         std::env::set_var("RUST_LOG", "info");
         env_logger::init();
+
+        if let Some(config) = headless::HeadlessConfig::from_env() {
+            headless::run(&config).unwrap_or_else(|e| panic!("Headless rendering failed: {}", e));
+            return;
+        }
     }
 
     let app = App::new();
@@ -1,9 +1,25 @@
+mod blit_pass;
+mod crossfade_pass;
 mod egui_pass;
-mod loading_egui_pass;
+mod egui_renderer;
+#[cfg(test)]
+mod golden_tests;
+mod material_bind_groups;
+mod mesh_pass;
+mod post_process;
+mod scene_target;
 mod texture_pass;
+mod tonemap_pass;
 mod triangle_pass;
 
-pub use egui_pass::*;
-pub use loading_egui_pass::*;
+pub use blit_pass::BlitPass;
+pub use crossfade_pass::CrossfadePass;
+pub use egui_pass::{winit_cursor_icon, EguiPass};
+pub use egui_renderer::{EguiRenderer, EguiScreen};
+pub use material_bind_groups::MaterialBindGroups;
+pub use mesh_pass::MeshPass;
+pub use post_process::*;
+pub use scene_target::SceneTarget;
 pub use texture_pass::TexturePass;
+pub use tonemap_pass::{TonemapOperator, TonemapPass, TonemapSettings};
 pub use triangle_pass::TrianglePass;
@@ -0,0 +1,42 @@
+#include "fullscreen.wgsl"
+
+// Vertex shader
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    let uv = fullscreen_uv(vertex_index);
+    var out: VertexOutput;
+    out.clip_position = fullscreen_position(uv);
+    out.uv = uv;
+    return out;
+}
+
+// Fragment shader
+
+struct CrossfadeUniform {
+    progress: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> crossfade: CrossfadeUniform;
+
+@group(0) @binding(1)
+var t_from: texture_2d<f32>;
+
+@group(0) @binding(2)
+var t_to: texture_2d<f32>;
+
+@group(0) @binding(3)
+var s_linear: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let from = textureSample(t_from, s_linear, in.uv);
+    let to = textureSample(t_to, s_linear, in.uv);
+    return mix(from, to, crossfade.progress);
+}
@@ -1,42 +1,67 @@
+use std::collections::HashMap;
 use std::fmt::{self, Debug};
 
 use egui::FontDefinitions;
-#[cfg(not(target_arch = "wasm32"))]
-use egui_wgpu_backend::{RenderPass, ScreenDescriptor};
-#[cfg(target_arch = "wasm32")]
-use egui_wgpu_backend_old::{RenderPass, ScreenDescriptor};
 use egui_winit_platform::{Platform, PlatformDescriptor};
 use instant::Instant;
+use shader_library::ShaderLibrary;
 
-use crate::state::*;
+use crate::pass::{EguiRenderer, EguiScreen};
+use crate::render_graph::*;
+use crate::resources::Texture;
 
-pub struct EguiPass {
+type EguiUi<T> = Box<dyn FnMut(&egui::CtxRef, &mut T) + Send>;
+
+// Pixels of a user texture, kept to upload it again when the device is recreated.
+struct EguiUserTexture {
+    rgba: Vec<u8>,
+    width: u32,
+    height: u32,
+}
+
+// Egui on top of the surface for any state, `ui` builds the windows from the data
+// the state renders its graph with.
+// The pass follows the window size and scale factor, hands copied text to the clipboard
+// and remembers the cursor icon egui asked for. Loaded `Texture`s can be shown with
+// `ui.image(egui::TextureId::User(texture.hash), size)` once they are set with `set_texture`.
+pub struct EguiPass<T> {
     platform: Platform,
-    egui_render_pass: RenderPass,
+    egui_renderer: EguiRenderer,
     start_time: Instant,
     size: winit::dpi::PhysicalSize<u32>,
+    scale_factor: f64,
+    cursor_icon: egui::CursorIcon,
+    user_textures: HashMap<u64, EguiUserTexture>,
+    ui: EguiUi<T>,
 }
-impl EguiPass {
+impl<T> EguiPass<T> {
     pub fn new(
         device: &wgpu::Device,
+        shaders: &ShaderLibrary,
         config: &wgpu::SurfaceConfiguration,
         size: winit::dpi::PhysicalSize<u32>,
+        scale_factor: f64,
+        ui: impl FnMut(&egui::CtxRef, &mut T) + Send + 'static,
     ) -> Self {
         let platform = Platform::new(PlatformDescriptor {
             physical_width: size.width,
             physical_height: size.height,
-            scale_factor: 1.0,
+            scale_factor,
             font_definitions: FontDefinitions::default(),
             style: Default::default(),
         });
 
-        let egui_render_pass = RenderPass::new(&device, config.format, 1);
+        let egui_renderer = EguiRenderer::new(device, shaders, config.format);
 
         Self {
             platform,
-            egui_render_pass,
+            egui_renderer,
             start_time: Instant::now(),
             size,
+            scale_factor,
+            cursor_icon: egui::CursorIcon::Default,
+            user_textures: HashMap::new(),
+            ui: Box::new(ui),
         }
     }
 
@@ -49,106 +74,228 @@ impl EguiPass {
         self.platform.update_time(elapsed_seconds);
     }
 
-    pub fn resize(&mut self, size: winit::dpi::PhysicalSize<u32>) {
+    // Whether egui uses the pointer and the keyboard, they should not reach the actions then.
+    pub fn wants_input(&self) -> (bool, bool) {
+        let context = self.platform.context();
+        (
+            context.wants_pointer_input(),
+            context.wants_keyboard_input(),
+        )
+    }
+
+    // Cursor icon of the last frame, the window shows it while this pass is on top.
+    pub fn cursor_icon(&self) -> egui::CursorIcon {
+        self.cursor_icon
+    }
+
+    // Follow the window, also when it changed while the state was not on top
+    // and the platform did not see the events.
+    pub fn resize(&mut self, size: winit::dpi::PhysicalSize<u32>, scale_factor: f64) {
+        let window_id = unsafe { winit::window::WindowId::dummy() };
+        if scale_factor != self.scale_factor {
+            let mut new_inner_size = size;
+            let event: winit::event::Event<()> = winit::event::Event::WindowEvent {
+                window_id,
+                event: winit::event::WindowEvent::ScaleFactorChanged {
+                    scale_factor,
+                    new_inner_size: &mut new_inner_size,
+                },
+            };
+            self.platform.handle_event(&event);
+        }
+        let event: winit::event::Event<()> = winit::event::Event::WindowEvent {
+            window_id,
+            event: winit::event::WindowEvent::Resized(size),
+        };
+        self.platform.handle_event(&event);
         self.size = size;
+        self.scale_factor = scale_factor;
+    }
+
+    // Upload a texture for `egui::TextureId::User(texture.hash)`, replacing an earlier one.
+    pub fn set_texture(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, texture: &Texture) {
+        let user_texture = EguiUserTexture {
+            rgba: texture.rgba.clone(),
+            width: texture.width,
+            height: texture.height,
+        };
+        self.egui_renderer.set_user_texture(
+            device,
+            queue,
+            texture.hash,
+            user_texture.width,
+            user_texture.height,
+            &user_texture.rgba,
+        );
+        self.user_textures.insert(texture.hash, user_texture);
     }
 
-    pub fn render(
+    pub fn has_texture(&self, hash: u64) -> bool {
+        self.user_textures.contains_key(&hash)
+    }
+
+    // Recreate the egui pipeline and textures for a new surface format or device.
+    pub fn recreate(
         &mut self,
-        encoder: &mut wgpu::CommandEncoder,
-        view_state: &mut MainStateViewState,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
-        view: &wgpu::TextureView,
+        shaders: &ShaderLibrary,
+        format: wgpu::TextureFormat,
     ) {
-        self.platform.begin_frame();
+        self.egui_renderer = EguiRenderer::new(device, shaders, format);
+        for (hash, user_texture) in &self.user_textures {
+            self.egui_renderer.set_user_texture(
+                device,
+                queue,
+                *hash,
+                user_texture.width,
+                user_texture.height,
+                &user_texture.rgba,
+            );
+        }
+    }
 
-        match view_state.clone() {
-            MainStateViewState::Triangle { label, clear_color } => {
-                egui::Window::new("Test")
-                    .resizable(true)
-                    .scroll2([true, true])
-                    .show(&self.platform.context(), |ui| {
-                        egui::ComboBox::from_id_source("combo")
-                            .selected_text(label)
-                            .show_ui(ui, |ui| {
-                                ui.selectable_value(
-                                    view_state,
-                                    MainStateViewState::triangle(),
-                                    "Triangle",
-                                );
-                                ui.selectable_value(
-                                    view_state,
-                                    MainStateViewState::texture(),
-                                    "Texture",
-                                );
-                            });
-                        ui.separator();
-                        ui.horizontal(|ui| {
-                            ui.label("Scene Clear Color");
-                            let mut hsva = egui::color::Hsva::from_rgba_premultiplied(
-                                clear_color.r,
-                                clear_color.g,
-                                clear_color.b,
-                                clear_color.a,
-                            );
-                            egui::color_picker::color_edit_button_hsva(
-                                ui,
-                                &mut hsva,
-                                egui::color_picker::Alpha::OnlyBlend,
-                            );
-                            if let MainStateViewState::Triangle { clear_color, .. } = view_state {
-                                *clear_color = vek::Rgba::from(hsva.to_rgba_premultiplied());
-                            }
-                        });
-                    });
-            }
-            MainStateViewState::Texture { label } => {
-                egui::Window::new("Test")
-                    .resizable(true)
-                    .scroll2([true, true])
-                    .show(&self.platform.context(), |ui| {
-                        egui::ComboBox::from_id_source("combo")
-                            .selected_text(label)
-                            .show_ui(ui, |ui| {
-                                ui.selectable_value(
-                                    view_state,
-                                    MainStateViewState::triangle(),
-                                    "Triangle",
-                                );
-                                ui.selectable_value(
-                                    view_state,
-                                    MainStateViewState::texture(),
-                                    "Texture",
-                                );
-                            });
-                        ui.label("Show base color texture");
-                    });
-            }
+    fn handle_output(&mut self, output: egui::Output) {
+        self.cursor_icon = output.cursor_icon;
+        if !output.copied_text.is_empty() {
+            set_clipboard_text(&output.copied_text);
         }
-        let (_output, paint_commands) = self.platform.end_frame(None);
+    }
+}
+impl<T> RenderGraphPass<T> for EguiPass<T> {
+    fn name(&self) -> &str {
+        "Egui Pass"
+    }
 
-        let paint_jobs = self.platform.context().tessellate(paint_commands);
+    fn setup(&self, builder: &mut RenderGraphPassBuilder) {
+        builder
+            .read(RenderGraphTexture::SURFACE)
+            .write(RenderGraphTexture::SURFACE);
+    }
 
-        let screen_descriptor = ScreenDescriptor {
-            physical_width: self.size.width,
-            physical_height: self.size.height,
-            scale_factor: 1.0,
-        };
-        self.egui_render_pass
-            .update_texture(device, queue, &self.platform.context().font_image());
-        self.egui_render_pass
-            .update_buffers(device, queue, &paint_jobs, &screen_descriptor);
-        self.egui_render_pass
-            .execute(encoder, &view, &paint_jobs, &screen_descriptor, None)
-            .unwrap();
+    fn execute(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        context: &RenderGraphContext,
+        data: &mut T,
+    ) {
+        self.platform.begin_frame();
+        (self.ui)(&self.platform.context(), data);
+        let (output, shapes) = self.platform.end_frame(None);
+        self.handle_output(output);
+
+        let egui_context = self.platform.context();
+        let meshes = egui_context.tessellate(shapes);
+        self.egui_renderer.render(
+            context.device,
+            context.queue,
+            encoder,
+            context.view(RenderGraphTexture::SURFACE),
+            &egui_context.font_image(),
+            &meshes,
+            &EguiScreen {
+                physical_width: self.size.width,
+                physical_height: self.size.height,
+                scale_factor: egui_context.pixels_per_point(),
+            },
+        );
     }
 }
-impl Debug for EguiPass {
+impl<T> Debug for EguiPass<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("LoadingEguiPass")
+        f.debug_struct("EguiPass")
             .field("start_time", &self.start_time)
             .field("size", &self.size)
+            .field("scale_factor", &self.scale_factor)
+            .field("cursor_icon", &self.cursor_icon)
             .finish()
     }
 }
+
+#[cfg(not(target_arch = "wasm32"))]
+fn set_clipboard_text(text: &str) {
+    use copypasta::ClipboardProvider;
+
+    match copypasta::ClipboardContext::new() {
+        Ok(mut clipboard) => {
+            if let Err(e) = clipboard.set_contents(text.to_string()) {
+                log::warn!("Failed to copy to the clipboard: {}", e);
+            }
+        }
+        Err(e) => log::warn!("Failed to open the clipboard: {}", e),
+    }
+}
+
+// `navigator.clipboard` is looked up at runtime, web-sys only has it behind unstable APIs.
+#[cfg(target_arch = "wasm32")]
+fn set_clipboard_text(text: &str) {
+    use wasm_bindgen::JsCast;
+
+    let write_text = web_sys::window()
+        .and_then(|window| js_sys::Reflect::get(&window.navigator(), &"clipboard".into()).ok())
+        .filter(|clipboard| !clipboard.is_undefined())
+        .and_then(|clipboard| {
+            let write_text = js_sys::Reflect::get(&clipboard, &"writeText".into()).ok()?;
+            Some((clipboard, write_text.dyn_into::<js_sys::Function>().ok()?))
+        });
+    match write_text {
+        Some((clipboard, write_text)) => {
+            if write_text.call1(&clipboard, &text.into()).is_err() {
+                log::warn!("Failed to copy to the clipboard.");
+            }
+        }
+        None => log::warn!("The clipboard is not available."),
+    }
+}
+
+// Window cursor for an egui cursor icon, `None` hides the cursor.
+pub fn winit_cursor_icon(cursor_icon: egui::CursorIcon) -> Option<winit::window::CursorIcon> {
+    use egui::CursorIcon as Egui;
+    use winit::window::CursorIcon as Winit;
+
+    Some(match cursor_icon {
+        Egui::None => return None,
+        Egui::Default => Winit::Default,
+        Egui::ContextMenu => Winit::ContextMenu,
+        Egui::Help => Winit::Help,
+        Egui::PointingHand => Winit::Hand,
+        Egui::Progress => Winit::Progress,
+        Egui::Wait => Winit::Wait,
+        Egui::Cell => Winit::Cell,
+        Egui::Crosshair => Winit::Crosshair,
+        Egui::Text => Winit::Text,
+        Egui::VerticalText => Winit::VerticalText,
+        Egui::Alias => Winit::Alias,
+        Egui::Copy => Winit::Copy,
+        Egui::Move => Winit::Move,
+        Egui::NoDrop => Winit::NoDrop,
+        Egui::NotAllowed => Winit::NotAllowed,
+        Egui::Grab => Winit::Grab,
+        Egui::Grabbing => Winit::Grabbing,
+        Egui::AllScroll => Winit::AllScroll,
+        Egui::ResizeHorizontal => Winit::EwResize,
+        Egui::ResizeNeSw => Winit::NeswResize,
+        Egui::ResizeNwSe => Winit::NwseResize,
+        Egui::ResizeVertical => Winit::NsResize,
+        Egui::ZoomIn => Winit::ZoomIn,
+        Egui::ZoomOut => Winit::ZoomOut,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_map_cursor_icons() {
+        assert_eq!(winit_cursor_icon(egui::CursorIcon::None), None);
+        assert_eq!(
+            winit_cursor_icon(egui::CursorIcon::PointingHand),
+            Some(winit::window::CursorIcon::Hand)
+        );
+        assert_eq!(
+            winit_cursor_icon(egui::CursorIcon::ResizeHorizontal),
+            Some(winit::window::CursorIcon::EwResize)
+        );
+    }
+}
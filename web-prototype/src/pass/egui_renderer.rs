@@ -0,0 +1,474 @@
+use std::collections::HashMap;
+
+use shader_library::{shader_source, ShaderLibrary};
+use wgpu::util::DeviceExt;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct EguiVertex {
+    position: [f32; 2],
+    uv: [f32; 2],
+    color: [u8; 4],
+}
+impl EguiVertex {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 3] =
+        wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Unorm8x4];
+
+    fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<EguiVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct EguiUniform {
+    screen_size: [f32; 2],
+    encode_srgb: u32,
+    padding: u32,
+}
+
+// The surface egui draws to, egui works in points of `scale_factor` pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EguiScreen {
+    pub physical_width: u32,
+    pub physical_height: u32,
+    pub scale_factor: f32,
+}
+
+// A mesh of the frame in the shared vertex and index buffers.
+struct EguiDraw {
+    clip_rect: egui::Rect,
+    indices: std::ops::Range<u32>,
+    base_vertex: i32,
+    texture_id: egui::TextureId,
+}
+
+// Draws the tessellated egui meshes, with the same WGSL on native and on the web.
+// Colors are blended premultiplied in linear space and encoded to sRGB in the shader
+// for surfaces without an sRGB format.
+#[derive(Debug)]
+pub struct EguiRenderer {
+    render_pipeline: wgpu::RenderPipeline,
+    uniform_buffer: wgpu::Buffer,
+    uniform_bind_group: wgpu::BindGroup,
+    texture_bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    textures: HashMap<egui::TextureId, wgpu::BindGroup>,
+    font_image_version: Option<u64>,
+    encode_srgb: bool,
+}
+impl EguiRenderer {
+    const UNIFORM_BIND_GROUP_LAYOUT: [wgpu::BindGroupLayoutEntry; 1] =
+        [wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        }];
+
+    const TEXTURE_BIND_GROUP_LAYOUT: [wgpu::BindGroupLayoutEntry; 2] = [
+        wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                multisampled: false,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            },
+            count: None,
+        },
+        wgpu::BindGroupLayoutEntry {
+            binding: 1,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+            count: None,
+        },
+    ];
+
+    pub fn new(
+        device: &wgpu::Device,
+        shaders: &ShaderLibrary,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        let shader = shaders.create_shader_module(
+            device,
+            shader_source!("pass/egui_renderer_shader.wgsl"),
+            &[],
+        );
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Egui Uniform Buffer"),
+            size: std::mem::size_of::<EguiUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let uniform_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &Self::UNIFORM_BIND_GROUP_LAYOUT,
+                label: Some("egui_uniform_bind_group_layout"),
+            });
+        let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &uniform_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+            label: Some("egui_uniform_bind_group"),
+        });
+        let texture_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &Self::TEXTURE_BIND_GROUP_LAYOUT,
+                label: Some("egui_texture_bind_group_layout"),
+            });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Egui Pipeline Layout"),
+                bind_group_layouts: &[&uniform_bind_group_layout, &texture_bind_group_layout],
+                push_constant_ranges: &[],
+            });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Egui Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[EguiVertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState {
+                        color: wgpu::BlendComponent {
+                            src_factor: wgpu::BlendFactor::One,
+                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                            operation: wgpu::BlendOperation::Add,
+                        },
+                        alpha: wgpu::BlendComponent {
+                            src_factor: wgpu::BlendFactor::OneMinusDstAlpha,
+                            dst_factor: wgpu::BlendFactor::One,
+                            operation: wgpu::BlendOperation::Add,
+                        },
+                    }),
+                    write_mask: wgpu::ColorWrites::ALL,
+                }],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        Self {
+            render_pipeline,
+            uniform_buffer,
+            uniform_bind_group,
+            texture_bind_group_layout,
+            sampler,
+            textures: HashMap::new(),
+            font_image_version: None,
+            encode_srgb: !format.describe().srgb,
+        }
+    }
+
+    // Texture and bind group for premultiplied sRGB pixels.
+    fn create_texture_bind_group(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        label: &str,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+    ) -> wgpu::BindGroup {
+        let texture = device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            },
+            pixels,
+        );
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.texture_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+            label: Some(label),
+        })
+    }
+
+    // Upload the font atlas when egui changed it, its coverage becomes premultiplied white.
+    fn update_font_image(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        font_image: &egui::FontImage,
+    ) {
+        if self.font_image_version == Some(font_image.version) {
+            return;
+        }
+        let pixels: Vec<u8> = font_image
+            .srgba_pixels(1.0)
+            .flat_map(|color| color.to_array())
+            .collect();
+        let bind_group = self.create_texture_bind_group(
+            device,
+            queue,
+            "egui_font_texture",
+            font_image.width as u32,
+            font_image.height as u32,
+            &pixels,
+        );
+        self.textures.insert(egui::TextureId::Egui, bind_group);
+        self.font_image_version = Some(font_image.version);
+    }
+
+    // Upload straight alpha sRGB pixels for `egui::TextureId::User(id)`.
+    pub fn set_user_texture(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        id: u64,
+        width: u32,
+        height: u32,
+        rgba: &[u8],
+    ) {
+        let pixels = premultiply_alpha(rgba);
+        let bind_group = self.create_texture_bind_group(
+            device,
+            queue,
+            "egui_user_texture",
+            width,
+            height,
+            &pixels,
+        );
+        self.textures.insert(egui::TextureId::User(id), bind_group);
+    }
+
+    // Draw the meshes over the content of the view.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        font_image: &egui::FontImage,
+        meshes: &[egui::ClippedMesh],
+        screen: &EguiScreen,
+    ) {
+        self.update_font_image(device, queue, font_image);
+
+        let mut vertices = vec![];
+        let mut indices = vec![];
+        let mut draws = vec![];
+        for egui::ClippedMesh(clip_rect, mesh) in meshes {
+            if mesh.indices.is_empty() {
+                continue;
+            }
+            draws.push(EguiDraw {
+                clip_rect: *clip_rect,
+                indices: indices.len() as u32..(indices.len() + mesh.indices.len()) as u32,
+                base_vertex: vertices.len() as i32,
+                texture_id: mesh.texture_id,
+            });
+            indices.extend_from_slice(&mesh.indices);
+            vertices.extend(mesh.vertices.iter().map(|vertex| EguiVertex {
+                position: [vertex.pos.x, vertex.pos.y],
+                uv: [vertex.uv.x, vertex.uv.y],
+                color: vertex.color.to_array(),
+            }));
+        }
+        if draws.is_empty() {
+            return;
+        }
+
+        queue.write_buffer(
+            &self.uniform_buffer,
+            0,
+            bytemuck::cast_slice(&[EguiUniform {
+                screen_size: [
+                    screen.physical_width as f32 / screen.scale_factor,
+                    screen.physical_height as f32 / screen.scale_factor,
+                ],
+                encode_srgb: self.encode_srgb as u32,
+                padding: 0,
+            }]),
+        );
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Egui Vertex Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Egui Index Buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Egui Pass"),
+            color_attachments: &[wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        for draw in draws {
+            let (x, y, width, height) = match scissor_rect(draw.clip_rect, screen) {
+                Some(rect) => rect,
+                None => continue,
+            };
+            let bind_group = match self.textures.get(&draw.texture_id) {
+                Some(bind_group) => bind_group,
+                None => {
+                    log::warn!("Egui texture {:?} is not registered", draw.texture_id);
+                    continue;
+                }
+            };
+            render_pass.set_scissor_rect(x, y, width, height);
+            render_pass.set_bind_group(1, bind_group, &[]);
+            render_pass.draw_indexed(draw.indices, draw.base_vertex, 0..1);
+        }
+    }
+}
+
+// Egui blends premultiplied colors, multiplying the sRGB encoded values is close enough for previews.
+fn premultiply_alpha(rgba: &[u8]) -> Vec<u8> {
+    rgba.chunks_exact(4)
+        .flat_map(|pixel| {
+            let alpha = pixel[3] as u32;
+            let premultiply = |value: u8| ((value as u32 * alpha + 127) / 255) as u8;
+            [
+                premultiply(pixel[0]),
+                premultiply(pixel[1]),
+                premultiply(pixel[2]),
+                pixel[3],
+            ]
+        })
+        .collect()
+}
+
+// Clip rectangle of a mesh in pixels, cut to the screen, none when nothing is left.
+fn scissor_rect(clip_rect: egui::Rect, screen: &EguiScreen) -> Option<(u32, u32, u32, u32)> {
+    let to_pixels =
+        |points: f32, max: u32| ((points * screen.scale_factor).round().max(0.0) as u32).min(max);
+    let min_x = to_pixels(clip_rect.min.x, screen.physical_width);
+    let min_y = to_pixels(clip_rect.min.y, screen.physical_height);
+    let max_x = to_pixels(clip_rect.max.x, screen.physical_width);
+    let max_y = to_pixels(clip_rect.max.y, screen.physical_height);
+    if max_x <= min_x || max_y <= min_y {
+        return None;
+    }
+    Some((min_x, min_y, max_x - min_x, max_y - min_y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shader_library::PipelineInterface;
+
+    #[test]
+    fn it_should_match_shader_interface() {
+        ShaderLibrary::new()
+            .check_interface(
+                shader_source!("pass/egui_renderer_shader.wgsl"),
+                &[],
+                &PipelineInterface {
+                    vertex_entry_point: "vs_main",
+                    fragment_entry_point: Some("fs_main"),
+                    vertex_buffers: &[EguiVertex::desc()],
+                    bind_group_layouts: &[
+                        &EguiRenderer::UNIFORM_BIND_GROUP_LAYOUT,
+                        &EguiRenderer::TEXTURE_BIND_GROUP_LAYOUT,
+                    ],
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn it_should_premultiply_alpha() {
+        assert_eq!(
+            premultiply_alpha(&[255, 128, 0, 128, 10, 20, 30, 255]),
+            vec![128, 64, 0, 128, 10, 20, 30, 255]
+        );
+    }
+
+    #[test]
+    fn it_should_cut_scissor_rect_to_screen() {
+        let screen = EguiScreen {
+            physical_width: 200,
+            physical_height: 100,
+            scale_factor: 2.0,
+        };
+        let rect = |min: [f32; 2], max: [f32; 2]| {
+            egui::Rect::from_min_max(egui::pos2(min[0], min[1]), egui::pos2(max[0], max[1]))
+        };
+        assert_eq!(
+            scissor_rect(rect([10.0, -5.0], [150.0, 20.0]), &screen),
+            Some((20, 0, 180, 40))
+        );
+        assert_eq!(
+            scissor_rect(rect([120.0, 0.0], [150.0, 20.0]), &screen),
+            None
+        );
+    }
+}
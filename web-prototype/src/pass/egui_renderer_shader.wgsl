@@ -0,0 +1,57 @@
+#include "color.wgsl"
+
+// Vertex shader
+
+struct EguiUniform {
+    screen_size: vec2<f32>,
+    encode_srgb: u32,
+    padding: u32,
+}
+
+@group(0) @binding(0)
+var<uniform> egui: EguiUniform;
+
+struct VertexInput {
+    // In points, (0, 0) at the top left.
+    @location(0) position: vec2<f32>,
+    @location(1) uv: vec2<f32>,
+    // Premultiplied sRGB.
+    @location(2) color: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(
+        2.0 * in.position.x / egui.screen_size.x - 1.0,
+        1.0 - 2.0 * in.position.y / egui.screen_size.y,
+        0.0,
+        1.0
+    );
+    out.uv = in.uv;
+    out.color = vec4<f32>(srgb_to_linear(in.color.rgb), in.color.a);
+    return out;
+}
+
+// Fragment shader
+
+@group(1) @binding(0)
+var t_egui: texture_2d<f32>;
+
+@group(1) @binding(1)
+var s_egui: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = in.color * textureSample(t_egui, s_egui, in.uv);
+    if (egui.encode_srgb != 0u) {
+        return vec4<f32>(linear_to_srgb(color.rgb), color.a);
+    }
+    return color;
+}
@@ -0,0 +1,330 @@
+use std::collections::HashMap;
+
+use wgpu::util::DeviceExt;
+
+use crate::resources::*;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct MaterialUniform {
+    base_color_factor: [f32; 4],
+    metallic_factor: f32,
+    roughness_factor: f32,
+    normal_scale: f32,
+    metallic_channel: u32,
+    roughness_channel: u32,
+    _padding: [u32; 3],
+}
+impl MaterialUniform {
+    fn new(material: &Material) -> Self {
+        Self {
+            base_color_factor: material.base_color_factor.into_array(),
+            metallic_factor: material.metallic_factor,
+            roughness_factor: material.roughness_factor,
+            normal_scale: material.normal_scale,
+            metallic_channel: material.metallic_channel,
+            roughness_channel: material.roughness_channel,
+            _padding: [0; 3],
+        }
+    }
+}
+
+#[derive(Debug)]
+struct GpuTexture {
+    // Whether the texture was uploaded from a placeholder and has to be replaced later.
+    placeholder: bool,
+    view: wgpu::TextureView,
+}
+
+#[derive(Debug)]
+struct MaterialBindGroup {
+    material: Material,
+    placeholder: bool,
+    bind_group: wgpu::BindGroup,
+}
+
+const fn texture_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Texture {
+            multisampled: false,
+            view_dimension: wgpu::TextureViewDimension::D2,
+            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+        },
+        count: None,
+    }
+}
+
+// Bind group layout and per-material bind groups for the metallic-roughness material.
+//
+// group layout:
+//   binding 0: material uniform
+//   binding 1: base color texture (sRGB)
+//   binding 2: metallic texture
+//   binding 3: roughness texture
+//   binding 4: normal texture
+//   binding 5: sampler
+//
+// Missing textures are bound to 1x1 fallback textures, and bind groups built with
+// streaming placeholder textures are rebuilt once the real textures are loaded.
+#[derive(Debug)]
+pub struct MaterialBindGroups {
+    layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    white_texture: wgpu::TextureView,
+    normal_texture: wgpu::TextureView,
+    textures: HashMap<(u64, bool), GpuTexture>,
+    bind_groups: HashMap<u64, MaterialBindGroup>,
+}
+impl MaterialBindGroups {
+    pub const BIND_GROUP_LAYOUT: [wgpu::BindGroupLayoutEntry; 6] = [
+        wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        },
+        texture_entry(1),
+        texture_entry(2),
+        texture_entry(3),
+        texture_entry(4),
+        wgpu::BindGroupLayoutEntry {
+            binding: 5,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+            count: None,
+        },
+    ];
+
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &Self::BIND_GROUP_LAYOUT,
+            label: Some("material_bind_group_layout"),
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::Repeat,
+            address_mode_w: wgpu::AddressMode::Repeat,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let white_texture = Self::create_texture_view(
+            device,
+            queue,
+            &Texture {
+                hash: 0,
+                rgba: vec![255, 255, 255, 255],
+                width: 1,
+                height: 1,
+            },
+            false,
+        );
+        let normal_texture = Self::create_texture_view(
+            device,
+            queue,
+            &Texture {
+                hash: 0,
+                rgba: vec![128, 128, 255, 255],
+                width: 1,
+                height: 1,
+            },
+            false,
+        );
+
+        Self {
+            layout,
+            sampler,
+            white_texture,
+            normal_texture,
+            textures: HashMap::new(),
+            bind_groups: HashMap::new(),
+        }
+    }
+
+    pub fn layout(&self) -> &wgpu::BindGroupLayout {
+        &self.layout
+    }
+
+    fn create_texture_view(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        texture: &Texture,
+        srgb: bool,
+    ) -> wgpu::TextureView {
+        let texture_size = wgpu::Extent3d {
+            width: texture.width,
+            height: texture.height,
+            depth_or_array_layers: 1,
+        };
+        let gpu_texture = device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                size: texture_size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: if srgb {
+                    wgpu::TextureFormat::Rgba8UnormSrgb
+                } else {
+                    wgpu::TextureFormat::Rgba8Unorm
+                },
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                label: Some("material_texture"),
+            },
+            &texture.rgba,
+        );
+        gpu_texture.create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    fn upload_texture(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        resources: &Resources,
+        hash: u64,
+        srgb: bool,
+    ) {
+        let loaded = resources.is_texture_loaded(hash);
+        let stale = self
+            .textures
+            .get(&(hash, srgb))
+            .map(|texture| texture.placeholder && loaded)
+            .unwrap_or(true);
+        if stale {
+            if let Some(texture) = resources.try_texture(hash) {
+                let view = Self::create_texture_view(device, queue, texture, srgb);
+                self.textures.insert(
+                    (hash, srgb),
+                    GpuTexture {
+                        placeholder: !loaded,
+                        view,
+                    },
+                );
+            }
+        }
+    }
+
+    // Uploaded view of the texture, `fallback` while the material has none.
+    fn view<'a>(
+        &'a self,
+        texture: Option<u64>,
+        srgb: bool,
+        fallback: &'a wgpu::TextureView,
+    ) -> &'a wgpu::TextureView {
+        texture
+            .and_then(|texture| self.textures.get(&(texture, srgb)))
+            .map(|texture| &texture.view)
+            .unwrap_or(fallback)
+    }
+
+    // Build the bind group of the material on first use or when the material
+    // or one of its streaming textures has changed.
+    pub fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        resources: &Resources,
+        material_hash: u64,
+    ) {
+        let material = resources.material(material_hash);
+        let placeholder = material
+            .textures()
+            .any(|texture| !resources.is_texture_loaded(texture));
+        let rebuild = match self.bind_groups.get(&material_hash) {
+            Some(bind_group) => {
+                &bind_group.material != material || (bind_group.placeholder && !placeholder)
+            }
+            None => true,
+        };
+
+        if rebuild {
+            if let Some(texture) = material.base_color_texture {
+                self.upload_texture(device, queue, resources, texture, true);
+            }
+            for texture in [
+                material.metallic_texture,
+                material.roughness_texture,
+                material.normal_texture,
+            ]
+            .into_iter()
+            .flatten()
+            {
+                self.upload_texture(device, queue, resources, texture, false);
+            }
+
+            let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Material Uniform Buffer"),
+                contents: bytemuck::cast_slice(&[MaterialUniform::new(material)]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &self.layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: uniform_buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(self.view(
+                            material.base_color_texture,
+                            true,
+                            &self.white_texture,
+                        )),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::TextureView(self.view(
+                            material.metallic_texture,
+                            false,
+                            &self.white_texture,
+                        )),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: wgpu::BindingResource::TextureView(self.view(
+                            material.roughness_texture,
+                            false,
+                            &self.white_texture,
+                        )),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 4,
+                        resource: wgpu::BindingResource::TextureView(self.view(
+                            material.normal_texture,
+                            false,
+                            &self.normal_texture,
+                        )),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 5,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler),
+                    },
+                ],
+                label: Some("material_bind_group"),
+            });
+            self.bind_groups.insert(
+                material_hash,
+                MaterialBindGroup {
+                    material: material.clone(),
+                    placeholder,
+                    bind_group,
+                },
+            );
+        }
+    }
+
+    // Returns the bind group built by `prepare`.
+    pub fn bind_group(&self, material_hash: u64) -> &wgpu::BindGroup {
+        &self.bind_groups[&material_hash].bind_group
+    }
+}
@@ -0,0 +1,358 @@
+use std::collections::HashMap;
+
+use shader_library::{shader_source, ShaderLibrary};
+use wgpu::util::DeviceExt;
+
+use crate::camera::Camera;
+use crate::light::{AmbientLight, DirectionalLight};
+use crate::pass::{MaterialBindGroups, SceneTarget, TonemapPass};
+use crate::render_graph::*;
+use crate::resources::*;
+use crate::state::*;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct SceneUniform {
+    view_proj: [[f32; 4]; 4],
+    camera_position: [f32; 4],
+    light_direction: [f32; 4],
+    light_color: [f32; 4],
+    ambient_sh: [[f32; 4]; 9],
+}
+impl SceneUniform {
+    fn new(camera: &Camera, light: &DirectionalLight, ambient: &AmbientLight) -> Self {
+        let light_color = light.color * light.intensity;
+        let mut ambient_sh = [[0.0; 4]; 9];
+        for (dst, src) in ambient_sh.iter_mut().zip(ambient.sh.iter()) {
+            *dst = [src.r, src.g, src.b, 0.0];
+        }
+        Self {
+            view_proj: camera.view_projection().into_col_arrays(),
+            camera_position: vek::Vec4::from_point(camera.position).into_array(),
+            light_direction: vek::Vec4::from_direction(light.direction).into_array(),
+            light_color: [light_color.r, light_color.g, light_color.b, 1.0],
+            ambient_sh,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct InstanceData {
+    model: [[f32; 4]; 4],
+    normal: [[f32; 3]; 3],
+}
+impl InstanceData {
+    fn new(transform: vek::Mat4<f32>) -> Self {
+        let normal = transform.inverted().transposed();
+        Self {
+            model: transform.into_col_arrays(),
+            normal: [0, 1, 2].map(|i| normal.cols[i].xyz().into_array()),
+        }
+    }
+}
+
+fn vertex_desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 4] = wgpu::vertex_attr_array![
+        0 => Float32x3,
+        1 => Float32x3,
+        2 => Float32x2,
+        3 => Float32x4,
+    ];
+    wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<MeshVertex>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &ATTRIBUTES,
+    }
+}
+
+fn instance_desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 7] = wgpu::vertex_attr_array![
+        4 => Float32x4,
+        5 => Float32x4,
+        6 => Float32x4,
+        7 => Float32x4,
+        8 => Float32x3,
+        9 => Float32x3,
+        10 => Float32x3,
+    ];
+    wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<InstanceData>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Instance,
+        attributes: &ATTRIBUTES,
+    }
+}
+
+#[derive(Debug)]
+struct GpuPrimitive {
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    index_count: u32,
+    material: u64,
+}
+
+// Forward renders the meshes of the scene graph with metallic-roughness materials,
+// one directional light and spherical harmonics ambient light.
+// Each draw is one instance with the world transform of its node.
+#[derive(Debug)]
+pub struct MeshPass {
+    render_pipeline: wgpu::RenderPipeline,
+    scene_buffer: wgpu::Buffer,
+    scene_bind_group: wgpu::BindGroup,
+    material_bind_groups: MaterialBindGroups,
+    target: SceneTarget,
+    meshes: HashMap<u64, Vec<GpuPrimitive>>,
+    instance_buffer: wgpu::Buffer,
+    instance_capacity: usize,
+}
+impl MeshPass {
+    pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+    const SCENE_BIND_GROUP_LAYOUT: [wgpu::BindGroupLayoutEntry; 1] = [wgpu::BindGroupLayoutEntry {
+        binding: 0,
+        visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+        ty: wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    }];
+
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        shaders: &ShaderLibrary,
+        target: SceneTarget,
+    ) -> Self {
+        let shader =
+            shaders.create_shader_module(device, shader_source!("pass/mesh_pass_shader.wgsl"), &[]);
+
+        let scene_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Scene Uniform Buffer"),
+            size: std::mem::size_of::<SceneUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let scene_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &Self::SCENE_BIND_GROUP_LAYOUT,
+                label: Some("scene_bind_group_layout"),
+            });
+        let scene_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &scene_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: scene_buffer.as_entire_binding(),
+            }],
+            label: Some("scene_bind_group"),
+        });
+
+        let material_bind_groups = MaterialBindGroups::new(device, queue);
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Mesh Pipeline Layout"),
+                bind_group_layouts: &[&scene_bind_group_layout, material_bind_groups.layout()],
+                push_constant_ranges: &[],
+            });
+
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Mesh Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[vertex_desc(), instance_desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[wgpu::ColorTargetState {
+                    format: TonemapPass::HDR_FORMAT,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                }],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Self::DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: target.multisample(),
+            multiview: None,
+        });
+
+        Self {
+            render_pipeline,
+            scene_buffer,
+            scene_bind_group,
+            material_bind_groups,
+            target,
+            meshes: HashMap::new(),
+            instance_buffer: Self::create_instance_buffer(device, 1),
+            instance_capacity: 1,
+        }
+    }
+
+    fn create_instance_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Mesh Instance Buffer"),
+            size: (capacity * std::mem::size_of::<InstanceData>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn prepare_mesh(&mut self, device: &wgpu::Device, resources: &Resources, mesh_hash: u64) {
+        if self.meshes.contains_key(&mesh_hash) {
+            return;
+        }
+        if let Some(mesh) = resources.try_mesh(mesh_hash) {
+            let primitives = mesh
+                .primitives
+                .iter()
+                .map(|primitive| GpuPrimitive {
+                    vertex_buffer: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some("Mesh Vertex Buffer"),
+                        contents: bytemuck::cast_slice(&primitive.vertices),
+                        usage: wgpu::BufferUsages::VERTEX,
+                    }),
+                    index_buffer: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some("Mesh Index Buffer"),
+                        contents: bytemuck::cast_slice(&primitive.indices),
+                        usage: wgpu::BufferUsages::INDEX,
+                    }),
+                    index_count: primitive.indices.len() as u32,
+                    material: primitive.material,
+                })
+                .collect();
+            self.meshes.insert(mesh_hash, primitives);
+        }
+    }
+}
+impl RenderGraphPass<MainStateScene> for MeshPass {
+    fn name(&self) -> &str {
+        "Mesh Pass"
+    }
+
+    fn setup(&self, builder: &mut RenderGraphPassBuilder) {
+        self.target.setup(builder, true);
+    }
+
+    fn execute(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        context: &RenderGraphContext,
+        scene: &mut MainStateScene,
+    ) {
+        let device = context.device;
+        let queue = context.queue;
+        let resources = &scene.resources;
+        let draws = scene.scene_graph.draws();
+
+        queue.write_buffer(
+            &self.scene_buffer,
+            0,
+            bytemuck::cast_slice(&[SceneUniform::new(
+                &scene.camera,
+                &scene.light,
+                &scene.ambient,
+            )]),
+        );
+
+        if draws.len() > self.instance_capacity {
+            self.instance_capacity = draws.len().next_power_of_two();
+            self.instance_buffer = Self::create_instance_buffer(device, self.instance_capacity);
+        }
+        let instances = draws
+            .iter()
+            .map(|draw| InstanceData::new(draw.transform))
+            .collect::<Vec<_>>();
+        queue.write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(&instances));
+
+        for draw in &draws {
+            self.prepare_mesh(device, resources, draw.mesh);
+            for primitive in self.meshes.get(&draw.mesh).into_iter().flatten() {
+                self.material_bind_groups.prepare(
+                    device,
+                    queue,
+                    resources,
+                    draw.material.unwrap_or(primitive.material),
+                );
+            }
+        }
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Mesh Pass"),
+            color_attachments: &[self.target.color_attachment(
+                context,
+                wgpu::LoadOp::Clear(wgpu::Color {
+                    r: 0.1,
+                    g: 0.1,
+                    b: 0.1,
+                    a: 1.0,
+                }),
+            )],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: context.view(self.target.depth),
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: true,
+                }),
+                stencil_ops: None,
+            }),
+        });
+
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.scene_bind_group, &[]);
+        render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+        for (instance, draw) in draws.iter().enumerate() {
+            let instance = instance as u32;
+            for primitive in self.meshes.get(&draw.mesh).into_iter().flatten() {
+                let material = draw.material.unwrap_or(primitive.material);
+                render_pass.set_bind_group(1, self.material_bind_groups.bind_group(material), &[]);
+                render_pass.set_vertex_buffer(0, primitive.vertex_buffer.slice(..));
+                render_pass
+                    .set_index_buffer(primitive.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                render_pass.draw_indexed(0..primitive.index_count, 0, instance..instance + 1);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shader_library::PipelineInterface;
+
+    #[test]
+    fn it_should_match_shader_interface() {
+        ShaderLibrary::new()
+            .check_interface(
+                shader_source!("pass/mesh_pass_shader.wgsl"),
+                &[],
+                &PipelineInterface {
+                    vertex_entry_point: "vs_main",
+                    fragment_entry_point: Some("fs_main"),
+                    vertex_buffers: &[vertex_desc(), instance_desc()],
+                    bind_group_layouts: &[
+                        &MeshPass::SCENE_BIND_GROUP_LAYOUT,
+                        &MaterialBindGroups::BIND_GROUP_LAYOUT,
+                    ],
+                },
+            )
+            .unwrap();
+    }
+}
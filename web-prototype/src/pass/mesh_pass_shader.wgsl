@@ -0,0 +1,175 @@
+// Vertex shader
+
+struct SceneUniform {
+    view_proj: mat4x4<f32>,
+    camera_position: vec4<f32>,
+    light_direction: vec4<f32>,
+    light_color: vec4<f32>,
+    ambient_sh: array<vec4<f32>, 9>,
+}
+
+@group(0) @binding(0)
+var<uniform> scene: SceneUniform;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+    @location(3) tangent: vec4<f32>,
+}
+
+// Model matrix and the inverse transpose of its upper 3x3 for normals, per draw.
+struct InstanceInput {
+    @location(4) model_0: vec4<f32>,
+    @location(5) model_1: vec4<f32>,
+    @location(6) model_2: vec4<f32>,
+    @location(7) model_3: vec4<f32>,
+    @location(8) normal_0: vec3<f32>,
+    @location(9) normal_1: vec3<f32>,
+    @location(10) normal_2: vec3<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) world_position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+    @location(3) tangent: vec4<f32>,
+}
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+    instance: InstanceInput,
+) -> VertexOutput {
+    let model_matrix = mat4x4<f32>(instance.model_0, instance.model_1, instance.model_2, instance.model_3);
+    let normal_matrix = mat3x3<f32>(instance.normal_0, instance.normal_1, instance.normal_2);
+    let world_position = model_matrix * vec4<f32>(model.position, 1.0);
+
+    var out: VertexOutput;
+    out.world_position = world_position.xyz;
+    out.normal = normal_matrix * model.normal;
+    out.uv = model.uv;
+    out.tangent = vec4<f32>((model_matrix * vec4<f32>(model.tangent.xyz, 0.0)).xyz, model.tangent.w);
+    out.clip_position = scene.view_proj * world_position;
+    return out;
+}
+
+// Fragment shader
+
+struct MaterialUniform {
+    base_color_factor: vec4<f32>,
+    metallic_factor: f32,
+    roughness_factor: f32,
+    normal_scale: f32,
+    metallic_channel: u32,
+    roughness_channel: u32,
+}
+
+@group(1) @binding(0)
+var<uniform> material: MaterialUniform;
+
+@group(1) @binding(1)
+var t_base_color: texture_2d<f32>;
+
+@group(1) @binding(2)
+var t_metallic: texture_2d<f32>;
+
+@group(1) @binding(3)
+var t_roughness: texture_2d<f32>;
+
+@group(1) @binding(4)
+var t_normal: texture_2d<f32>;
+
+@group(1) @binding(5)
+var s_material: sampler;
+
+let PI: f32 = 3.14159265359;
+
+fn channel(color: vec4<f32>, index: u32) -> f32 {
+    if (index == 0u) {
+        return color.r;
+    }
+    if (index == 1u) {
+        return color.g;
+    }
+    if (index == 2u) {
+        return color.b;
+    }
+    return color.a;
+}
+
+fn ambient(n: vec3<f32>) -> vec3<f32> {
+    let sh = scene.ambient_sh;
+    var color = sh[0].rgb * 0.282095;
+    color = color + sh[1].rgb * (0.488603 * n.y);
+    color = color + sh[2].rgb * (0.488603 * n.z);
+    color = color + sh[3].rgb * (0.488603 * n.x);
+    color = color + sh[4].rgb * (1.092548 * n.x * n.y);
+    color = color + sh[5].rgb * (1.092548 * n.y * n.z);
+    color = color + sh[6].rgb * (0.315392 * (3.0 * n.z * n.z - 1.0));
+    color = color + sh[7].rgb * (1.092548 * n.x * n.z);
+    color = color + sh[8].rgb * (0.546274 * (n.x * n.x - n.y * n.y));
+    return max(color, vec3<f32>(0.0));
+}
+
+fn distribution_ggx(n_dot_h: f32, roughness: f32) -> f32 {
+    let a = roughness * roughness;
+    let a2 = a * a;
+    let d = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+    return a2 / (PI * d * d);
+}
+
+fn geometry_smith(n_dot_v: f32, n_dot_l: f32, roughness: f32) -> f32 {
+    let k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+    let g_v = n_dot_v / (n_dot_v * (1.0 - k) + k);
+    let g_l = n_dot_l / (n_dot_l * (1.0 - k) + k);
+    return g_v * g_l;
+}
+
+fn fresnel_schlick(cos_theta: f32, f0: vec3<f32>) -> vec3<f32> {
+    return f0 + (vec3<f32>(1.0) - f0) * pow(1.0 - cos_theta, 5.0);
+}
+
+// Analytic approximation of the split sum environment BRDF (Karis 2014).
+fn env_brdf(n_dot_v: f32, roughness: f32, f0: vec3<f32>) -> vec3<f32> {
+    let c0 = vec4<f32>(-1.0, -0.0275, -0.572, 0.022);
+    let c1 = vec4<f32>(1.0, 0.0425, 1.04, -0.04);
+    let r = roughness * c0 + c1;
+    let a004 = min(r.x * r.x, exp2(-9.28 * n_dot_v)) * r.x + r.y;
+    let ab = vec2<f32>(-1.04, 1.04) * a004 + r.zw;
+    return f0 * ab.x + vec3<f32>(ab.y);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let base_color = textureSample(t_base_color, s_material, in.uv) * material.base_color_factor;
+    let metallic = channel(textureSample(t_metallic, s_material, in.uv), material.metallic_channel) * material.metallic_factor;
+    let roughness = clamp(channel(textureSample(t_roughness, s_material, in.uv), material.roughness_channel) * material.roughness_factor, 0.04, 1.0);
+
+    let vertex_normal = normalize(in.normal);
+    let t = normalize(in.tangent.xyz - vertex_normal * dot(vertex_normal, in.tangent.xyz));
+    let b = cross(vertex_normal, t) * in.tangent.w;
+    var tangent_normal = textureSample(t_normal, s_material, in.uv).xyz * 2.0 - vec3<f32>(1.0);
+    tangent_normal = vec3<f32>(tangent_normal.xy * material.normal_scale, tangent_normal.z);
+    let n = normalize(mat3x3<f32>(t, b, vertex_normal) * tangent_normal);
+
+    let v = normalize(scene.camera_position.xyz - in.world_position);
+    let l = normalize(-scene.light_direction.xyz);
+    let h = normalize(v + l);
+    let n_dot_v = max(dot(n, v), 0.0001);
+    let n_dot_l = max(dot(n, l), 0.0);
+    let n_dot_h = max(dot(n, h), 0.0);
+
+    let f0 = mix(vec3<f32>(0.04), base_color.rgb, vec3<f32>(metallic));
+    let f = fresnel_schlick(max(dot(h, v), 0.0), f0);
+    let specular = distribution_ggx(n_dot_h, roughness) * geometry_smith(n_dot_v, n_dot_l, roughness) * f / (4.0 * n_dot_v * max(n_dot_l, 0.0001));
+    let diffuse = (vec3<f32>(1.0) - f) * (1.0 - metallic) * base_color.rgb / PI;
+    let direct = (diffuse + specular) * scene.light_color.rgb * n_dot_l;
+
+    let ambient_diffuse = ambient(n) * base_color.rgb * (1.0 - metallic);
+    let ambient_specular = ambient(reflect(-v, n)) * env_brdf(n_dot_v, roughness, f0);
+    let color = direct + ambient_diffuse + ambient_specular;
+
+    return vec4<f32>(color, base_color.a);
+}
@@ -0,0 +1,5 @@
+mod bloom_pass;
+mod post_process_pass;
+
+pub use bloom_pass::{BloomPass, BloomSettings};
+pub use post_process_pass::{ColorGradingSettings, PostProcessPass, PostProcessSettings};
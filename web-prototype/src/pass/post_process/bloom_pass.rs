@@ -0,0 +1,334 @@
+use serde::{Deserialize, Serialize};
+use shader_library::{shader_source, ShaderLibrary};
+
+use crate::pass::TonemapPass;
+use crate::render_graph::*;
+use crate::state::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BloomSettings {
+    pub enabled: bool,
+    // Colors brighter than the threshold bloom, with a soft knee below it.
+    pub threshold: f32,
+    pub knee: f32,
+    pub intensity: f32,
+    // Upsample filter radius in texels.
+    pub radius: f32,
+}
+impl Default for BloomSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: 1.0,
+            knee: 0.5,
+            intensity: 0.1,
+            radius: 1.0,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct BloomUniform {
+    threshold: f32,
+    knee: f32,
+    intensity: f32,
+    radius: f32,
+}
+
+const ADDITIVE: wgpu::BlendState = wgpu::BlendState {
+    color: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::One,
+        dst_factor: wgpu::BlendFactor::One,
+        operation: wgpu::BlendOperation::Add,
+    },
+    alpha: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::Zero,
+        dst_factor: wgpu::BlendFactor::One,
+        operation: wgpu::BlendOperation::Add,
+    },
+};
+
+// Bloom on the HDR target.
+// The bright parts are downsampled into a mip chain of half sized textures,
+// upsampled back while accumulating every mip and added to the HDR target.
+#[derive(Debug)]
+pub struct BloomPass {
+    prefilter_pipeline: wgpu::RenderPipeline,
+    downsample_pipeline: wgpu::RenderPipeline,
+    upsample_pipeline: wgpu::RenderPipeline,
+    composite_pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    uniform_buffer: wgpu::Buffer,
+    sampler: wgpu::Sampler,
+    hdr: RenderGraphTexture,
+    mips: Vec<RenderGraphTexture>,
+}
+impl BloomPass {
+    pub const MIP_COUNT: u32 = 5;
+
+    const BIND_GROUP_LAYOUT: [wgpu::BindGroupLayoutEntry; 3] = [
+        wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        },
+        wgpu::BindGroupLayoutEntry {
+            binding: 1,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                multisampled: false,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            },
+            count: None,
+        },
+        wgpu::BindGroupLayoutEntry {
+            binding: 2,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+            count: None,
+        },
+    ];
+
+    pub fn new(
+        device: &wgpu::Device,
+        shaders: &ShaderLibrary,
+        render_graph: &mut RenderGraph,
+        hdr: RenderGraphTexture,
+    ) -> Self {
+        let mips = (1..=Self::MIP_COUNT)
+            .map(|level| {
+                render_graph.create_texture(
+                    RenderGraphTextureDescriptor::new(
+                        format!("bloom_mip_{}", level),
+                        TonemapPass::HDR_FORMAT,
+                    )
+                    .with_divisor(1 << level),
+                )
+            })
+            .collect();
+
+        let shader = shaders.create_shader_module(
+            device,
+            shader_source!("pass/post_process/bloom_pass_shader.wgsl"),
+            &[],
+        );
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Bloom Uniform Buffer"),
+            size: std::mem::size_of::<BloomUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &Self::BIND_GROUP_LAYOUT,
+            label: Some("bloom_bind_group_layout"),
+        });
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Bloom Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+        let create_pipeline = |entry_point: &str, blend: wgpu::BlendState| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Bloom Pipeline"),
+                layout: Some(&render_pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_main",
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point,
+                    targets: &[wgpu::ColorTargetState {
+                        format: TonemapPass::HDR_FORMAT,
+                        blend: Some(blend),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    }],
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: None,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    unclipped_depth: false,
+                    conservative: false,
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState {
+                    count: 1,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+            })
+        };
+
+        Self {
+            prefilter_pipeline: create_pipeline("fs_prefilter", wgpu::BlendState::REPLACE),
+            downsample_pipeline: create_pipeline("fs_downsample", wgpu::BlendState::REPLACE),
+            upsample_pipeline: create_pipeline("fs_upsample", ADDITIVE),
+            composite_pipeline: create_pipeline("fs_composite", ADDITIVE),
+            bind_group_layout,
+            uniform_buffer,
+            sampler,
+            hdr,
+            mips,
+        }
+    }
+
+    fn draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        context: &RenderGraphContext,
+        pipeline: &wgpu::RenderPipeline,
+        source: RenderGraphTexture,
+        target: RenderGraphTexture,
+        load: wgpu::LoadOp<wgpu::Color>,
+    ) {
+        let bind_group = context
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &self.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: self.uniform_buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(context.view(source)),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler),
+                    },
+                ],
+                label: Some("bloom_bind_group"),
+            });
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Bloom Pass"),
+            color_attachments: &[wgpu::RenderPassColorAttachment {
+                view: context.view(target),
+                resolve_target: None,
+                ops: wgpu::Operations { load, store: true },
+            }],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+impl RenderGraphPass<MainStateScene> for BloomPass {
+    fn name(&self) -> &str {
+        "Bloom Pass"
+    }
+
+    fn setup(&self, builder: &mut RenderGraphPassBuilder) {
+        builder.read(self.hdr).write(self.hdr);
+        for &mip in &self.mips {
+            builder.write(mip);
+        }
+    }
+
+    fn execute(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        context: &RenderGraphContext,
+        scene: &mut MainStateScene,
+    ) {
+        let settings = &scene.post_process.bloom;
+        context.queue.write_buffer(
+            &self.uniform_buffer,
+            0,
+            bytemuck::cast_slice(&[BloomUniform {
+                threshold: settings.threshold,
+                knee: settings.knee.max(0.0),
+                intensity: settings.intensity,
+                radius: settings.radius,
+            }]),
+        );
+
+        let clear = wgpu::LoadOp::Clear(wgpu::Color::BLACK);
+        let mut source = self.hdr;
+        for (level, &mip) in self.mips.iter().enumerate() {
+            let pipeline = if level == 0 {
+                &self.prefilter_pipeline
+            } else {
+                &self.downsample_pipeline
+            };
+            self.draw(encoder, context, pipeline, source, mip, clear);
+            source = mip;
+        }
+        for mips in self.mips.windows(2).rev() {
+            self.draw(
+                encoder,
+                context,
+                &self.upsample_pipeline,
+                mips[1],
+                mips[0],
+                wgpu::LoadOp::Load,
+            );
+        }
+        self.draw(
+            encoder,
+            context,
+            &self.composite_pipeline,
+            self.mips[0],
+            self.hdr,
+            wgpu::LoadOp::Load,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shader_library::PipelineInterface;
+
+    #[test]
+    fn it_should_match_shader_interface() {
+        let shaders = ShaderLibrary::new();
+        for entry_point in [
+            "fs_prefilter",
+            "fs_downsample",
+            "fs_upsample",
+            "fs_composite",
+        ] {
+            shaders
+                .check_interface(
+                    shader_source!("pass/post_process/bloom_pass_shader.wgsl"),
+                    &[],
+                    &PipelineInterface {
+                        vertex_entry_point: "vs_main",
+                        fragment_entry_point: Some(entry_point),
+                        vertex_buffers: &[],
+                        bind_group_layouts: &[&BloomPass::BIND_GROUP_LAYOUT],
+                    },
+                )
+                .unwrap();
+        }
+    }
+}
@@ -0,0 +1,101 @@
+#include "fullscreen.wgsl"
+
+// Vertex shader
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    let uv = fullscreen_uv(vertex_index);
+    var out: VertexOutput;
+    out.clip_position = fullscreen_position(uv);
+    out.uv = uv;
+    return out;
+}
+
+// Fragment shader
+
+struct BloomUniform {
+    threshold: f32,
+    knee: f32,
+    intensity: f32,
+    radius: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> bloom: BloomUniform;
+
+@group(0) @binding(1)
+var t_source: texture_2d<f32>;
+
+@group(0) @binding(2)
+var s_source: sampler;
+
+fn texel_size() -> vec2<f32> {
+    return 1.0 / vec2<f32>(textureDimensions(t_source));
+}
+
+fn sample_source(uv: vec2<f32>, offset: vec2<f32>) -> vec3<f32> {
+    return textureSample(t_source, s_source, uv + offset * texel_size()).rgb;
+}
+
+// 13 tap downsample from Call of Duty: Advanced Warfare
+fn downsample(uv: vec2<f32>) -> vec3<f32> {
+    let a = sample_source(uv, vec2<f32>(-2.0, -2.0));
+    let b = sample_source(uv, vec2<f32>(0.0, -2.0));
+    let c = sample_source(uv, vec2<f32>(2.0, -2.0));
+    let d = sample_source(uv, vec2<f32>(-1.0, -1.0));
+    let e = sample_source(uv, vec2<f32>(1.0, -1.0));
+    let f = sample_source(uv, vec2<f32>(-2.0, 0.0));
+    let g = sample_source(uv, vec2<f32>(0.0, 0.0));
+    let h = sample_source(uv, vec2<f32>(2.0, 0.0));
+    let i = sample_source(uv, vec2<f32>(-1.0, 1.0));
+    let j = sample_source(uv, vec2<f32>(1.0, 1.0));
+    let k = sample_source(uv, vec2<f32>(-2.0, 2.0));
+    let l = sample_source(uv, vec2<f32>(0.0, 2.0));
+    let m = sample_source(uv, vec2<f32>(2.0, 2.0));
+    return g * 0.125
+        + (a + c + k + m) * 0.03125
+        + (b + f + h + l) * 0.0625
+        + (d + e + i + j) * 0.125;
+}
+
+// Soft threshold, only the part of the color above the threshold blooms.
+@fragment
+fn fs_prefilter(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = min(downsample(in.uv), vec3<f32>(65000.0));
+    let brightness = max(color.r, max(color.g, color.b));
+    var soft = clamp(brightness - bloom.threshold + bloom.knee, 0.0, 2.0 * bloom.knee);
+    soft = soft * soft / (4.0 * bloom.knee + 0.00001);
+    let contribution = max(soft, brightness - bloom.threshold) / max(brightness, 0.00001);
+    return vec4<f32>(color * contribution, 1.0);
+}
+
+@fragment
+fn fs_downsample(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(downsample(in.uv), 1.0);
+}
+
+// 3x3 tent filter, blended additively into the next larger mip
+@fragment
+fn fs_upsample(in: VertexOutput) -> @location(0) vec4<f32> {
+    let r = bloom.radius;
+    let color = sample_source(in.uv, vec2<f32>(0.0, 0.0)) * 4.0
+        + (sample_source(in.uv, vec2<f32>(0.0, -r))
+            + sample_source(in.uv, vec2<f32>(-r, 0.0))
+            + sample_source(in.uv, vec2<f32>(r, 0.0))
+            + sample_source(in.uv, vec2<f32>(0.0, r))) * 2.0
+        + sample_source(in.uv, vec2<f32>(-r, -r))
+        + sample_source(in.uv, vec2<f32>(r, -r))
+        + sample_source(in.uv, vec2<f32>(-r, r))
+        + sample_source(in.uv, vec2<f32>(r, r));
+    return vec4<f32>(color / 16.0, 1.0);
+}
+
+@fragment
+fn fs_composite(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(sample_source(in.uv, vec2<f32>(0.0, 0.0)) * bloom.intensity, 0.0);
+}
@@ -0,0 +1,464 @@
+use serde::{Deserialize, Serialize};
+use shader_library::{shader_source, ShaderLibrary};
+
+use crate::pass::BloomSettings;
+use crate::render_graph::*;
+use crate::resources::*;
+use crate::state::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VignetteSettings {
+    pub enabled: bool,
+    pub intensity: f32,
+    // Distance from the center where the darkening starts, 1.0 is the corner.
+    pub radius: f32,
+    pub smoothness: f32,
+}
+impl Default for VignetteSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            intensity: 0.5,
+            radius: 0.5,
+            smoothness: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColorGradingSettings {
+    pub enabled: bool,
+    // Texture hash of a LUT in `ColorGradingSettings::LUTS`.
+    pub lut: u64,
+    pub strength: f32,
+}
+impl ColorGradingSettings {
+    // LUTs in the resource catalog, stored as horizontal strips of N slices of NxN.
+    pub const LUTS: [(&'static str, u64); 2] =
+        [("Warm", hash("lut_warm")), ("Cool", hash("lut_cool"))];
+}
+impl Default for ColorGradingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            lut: Self::LUTS[0].1,
+            strength: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PostProcessSettings {
+    pub bloom: BloomSettings,
+    pub fxaa: bool,
+    pub vignette: VignetteSettings,
+    pub color_grading: ColorGradingSettings,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct PostProcessUniform {
+    fxaa: u32,
+    vignette: u32,
+    color_grading: u32,
+    encode_srgb: u32,
+    vignette_intensity: f32,
+    vignette_radius: f32,
+    vignette_smoothness: f32,
+    lut_strength: f32,
+}
+
+fn create_lut(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    size: u32,
+    rgba: &[u8],
+) -> wgpu::TextureView {
+    let extent = wgpu::Extent3d {
+        width: size,
+        height: size,
+        depth_or_array_layers: size,
+    };
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("color_grading_lut"),
+        size: extent,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D3,
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+    });
+    queue.write_texture(
+        wgpu::ImageCopyTexture {
+            texture: &texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        rgba,
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: std::num::NonZeroU32::new(4 * size),
+            rows_per_image: std::num::NonZeroU32::new(size),
+        },
+        extent,
+    );
+    texture.create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+// 2x2x2 LUT mapping every color to itself.
+fn identity_lut() -> Vec<u8> {
+    let mut rgba = vec![];
+    for b in 0..2 {
+        for g in 0..2 {
+            for r in 0..2 {
+                rgba.extend_from_slice(&[255 * r, 255 * g, 255 * b, 255]);
+            }
+        }
+    }
+    rgba
+}
+
+// Rearrange a LUT strip (blue slices side by side) into the slices of a 3D texture.
+fn lut_from_strip(texture: &Texture) -> Option<(u32, Vec<u8>)> {
+    let size = texture.height;
+    if size < 2 || texture.width != size * size {
+        return None;
+    }
+    let size = size as usize;
+    let mut rgba = Vec::with_capacity(4 * size * size * size);
+    for b in 0..size {
+        for g in 0..size {
+            let start = 4 * (g * size * size + b * size);
+            rgba.extend_from_slice(&texture.rgba[start..start + 4 * size]);
+        }
+    }
+    Some((size as u32, rgba))
+}
+
+// Final pass from the tonemapped LDR texture to the surface.
+// Applies FXAA, LUT color grading and vignette, each of them can be disabled.
+#[derive(Debug)]
+pub struct PostProcessPass {
+    render_pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    uniform_buffer: wgpu::Buffer,
+    sampler: wgpu::Sampler,
+    identity_lut: wgpu::TextureView,
+    // Hash and view of the uploaded LUT.
+    lut: Option<(u64, wgpu::TextureView)>,
+    encode_srgb: bool,
+    ldr: RenderGraphTexture,
+}
+impl PostProcessPass {
+    pub const LDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+    const BIND_GROUP_LAYOUT: [wgpu::BindGroupLayoutEntry; 4] = [
+        wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        },
+        wgpu::BindGroupLayoutEntry {
+            binding: 1,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                multisampled: false,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            },
+            count: None,
+        },
+        wgpu::BindGroupLayoutEntry {
+            binding: 2,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+            count: None,
+        },
+        wgpu::BindGroupLayoutEntry {
+            binding: 3,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                multisampled: false,
+                view_dimension: wgpu::TextureViewDimension::D3,
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            },
+            count: None,
+        },
+    ];
+
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        shaders: &ShaderLibrary,
+        config: &wgpu::SurfaceConfiguration,
+        ldr: RenderGraphTexture,
+    ) -> Self {
+        let shader = shaders.create_shader_module(
+            device,
+            shader_source!("pass/post_process/post_process_pass_shader.wgsl"),
+            &[],
+        );
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Post Process Uniform Buffer"),
+            size: std::mem::size_of::<PostProcessUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        // Used until the selected LUT is loaded.
+        let identity_lut = create_lut(device, queue, 2, &identity_lut());
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &Self::BIND_GROUP_LAYOUT,
+            label: Some("post_process_bind_group_layout"),
+        });
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Post Process Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Post Process Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[wgpu::ColorTargetState {
+                    format: config.format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                }],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        Self {
+            render_pipeline,
+            bind_group_layout,
+            uniform_buffer,
+            sampler,
+            identity_lut,
+            lut: None,
+            encode_srgb: !config.format.describe().srgb,
+            ldr,
+        }
+    }
+
+    // Upload the selected LUT once it has been streamed in.
+    fn prepare_lut(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        resources: &Resources,
+        hash: u64,
+    ) {
+        if matches!(&self.lut, Some((uploaded, _)) if *uploaded == hash)
+            || !resources.is_texture_loaded(hash)
+        {
+            return;
+        }
+        match lut_from_strip(resources.texture(hash)) {
+            Some((size, rgba)) => {
+                self.lut = Some((hash, create_lut(device, queue, size, &rgba)));
+            }
+            None => {
+                log::warn!("LUT {:x} is not a strip of N slices of NxN.", hash);
+                self.lut = Some((hash, create_lut(device, queue, 2, &identity_lut())));
+            }
+        }
+    }
+}
+impl RenderGraphPass<MainStateScene> for PostProcessPass {
+    fn name(&self) -> &str {
+        "Post Process Pass"
+    }
+
+    fn setup(&self, builder: &mut RenderGraphPassBuilder) {
+        builder.read(self.ldr).write(RenderGraphTexture::SURFACE);
+    }
+
+    fn execute(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        context: &RenderGraphContext,
+        scene: &mut MainStateScene,
+    ) {
+        let device = context.device;
+        let queue = context.queue;
+        let settings = scene.post_process;
+
+        if settings.color_grading.enabled {
+            self.prepare_lut(device, queue, &scene.resources, settings.color_grading.lut);
+        }
+        let lut = match &self.lut {
+            Some((hash, view)) if *hash == settings.color_grading.lut => view,
+            _ => &self.identity_lut,
+        };
+
+        queue.write_buffer(
+            &self.uniform_buffer,
+            0,
+            bytemuck::cast_slice(&[PostProcessUniform {
+                fxaa: settings.fxaa as u32,
+                vignette: settings.vignette.enabled as u32,
+                color_grading: settings.color_grading.enabled as u32,
+                encode_srgb: self.encode_srgb as u32,
+                vignette_intensity: settings.vignette.intensity,
+                vignette_radius: settings.vignette.radius,
+                vignette_smoothness: settings.vignette.smoothness,
+                lut_strength: settings.color_grading.strength,
+            }]),
+        );
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(context.view(self.ldr)),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::TextureView(lut),
+                },
+            ],
+            label: Some("post_process_bind_group"),
+        });
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Post Process Pass"),
+            color_attachments: &[wgpu::RenderPassColorAttachment {
+                view: context.view(RenderGraphTexture::SURFACE),
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+        });
+
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shader_library::PipelineInterface;
+
+    #[test]
+    fn it_should_rearrange_lut_strip_into_slices() {
+        // 2x2x2 strip: x = b * 2 + r, y = g, red channel holds the index of the texel.
+        let mut rgba = vec![];
+        for g in 0..2u8 {
+            for b in 0..2u8 {
+                for r in 0..2u8 {
+                    rgba.extend_from_slice(&[r + 2 * g + 4 * b, 0, 0, 255]);
+                }
+            }
+        }
+        let texture = Texture {
+            hash: hash("lut"),
+            rgba,
+            width: 4,
+            height: 2,
+        };
+        let (size, slices) = lut_from_strip(&texture).unwrap();
+        assert_eq!(size, 2);
+        let indices: Vec<u8> = slices.chunks(4).map(|texel| texel[0]).collect();
+        assert_eq!(indices, (0..8).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn it_should_load_lut_asset() {
+        let texture = Texture::from_image(
+            hash("lut_warm"),
+            include_bytes!("../../../assets/lut_warm.png"),
+        )
+        .unwrap();
+        assert_eq!((texture.width, texture.height), (1024, 32));
+        let (size, slices) = lut_from_strip(&texture).unwrap();
+        assert_eq!(size, 32);
+        assert_eq!(slices.len(), 4 * 32 * 32 * 32);
+    }
+
+    #[test]
+    fn it_should_reject_non_strip_lut() {
+        let texture = Texture {
+            hash: hash("lut"),
+            rgba: vec![0; 4 * 4 * 4],
+            width: 4,
+            height: 4,
+        };
+        assert!(lut_from_strip(&texture).is_none());
+    }
+
+    #[test]
+    fn it_should_match_shader_interface() {
+        ShaderLibrary::new()
+            .check_interface(
+                shader_source!("pass/post_process/post_process_pass_shader.wgsl"),
+                &[],
+                &PipelineInterface {
+                    vertex_entry_point: "vs_main",
+                    fragment_entry_point: Some("fs_main"),
+                    vertex_buffers: &[],
+                    bind_group_layouts: &[&PostProcessPass::BIND_GROUP_LAYOUT],
+                },
+            )
+            .unwrap();
+    }
+}
@@ -0,0 +1,119 @@
+#include "fullscreen.wgsl"
+#include "color.wgsl"
+
+// Vertex shader
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    let uv = fullscreen_uv(vertex_index);
+    var out: VertexOutput;
+    out.clip_position = fullscreen_position(uv);
+    out.uv = uv;
+    return out;
+}
+
+// Fragment shader
+
+struct PostProcessUniform {
+    fxaa: u32,
+    vignette: u32,
+    color_grading: u32,
+    encode_srgb: u32,
+    vignette_intensity: f32,
+    vignette_radius: f32,
+    vignette_smoothness: f32,
+    lut_strength: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> post_process: PostProcessUniform;
+
+// sRGB texture, samples are linear.
+@group(0) @binding(1)
+var t_ldr: texture_2d<f32>;
+
+@group(0) @binding(2)
+var s_linear: sampler;
+
+// Color grading LUT, indexed and stored in sRGB.
+@group(0) @binding(3)
+var t_lut: texture_3d<f32>;
+
+fn sample_ldr(uv: vec2<f32>) -> vec3<f32> {
+    return textureSample(t_ldr, s_linear, uv).rgb;
+}
+
+// Perceptual luma, FXAA works on gamma encoded values.
+fn luma(color: vec3<f32>) -> f32 {
+    return sqrt(dot(color, vec3<f32>(0.299, 0.587, 0.114)));
+}
+
+// FXAA 3.11 console quality by Timothy Lottes
+fn fxaa(uv: vec2<f32>) -> vec3<f32> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(t_ldr));
+    let span_max = 8.0;
+    let reduce_mul = 1.0 / 8.0;
+    let reduce_min = 1.0 / 128.0;
+
+    let rgb_m = sample_ldr(uv);
+    let luma_nw = luma(sample_ldr(uv + vec2<f32>(-1.0, -1.0) * texel));
+    let luma_ne = luma(sample_ldr(uv + vec2<f32>(1.0, -1.0) * texel));
+    let luma_sw = luma(sample_ldr(uv + vec2<f32>(-1.0, 1.0) * texel));
+    let luma_se = luma(sample_ldr(uv + vec2<f32>(1.0, 1.0) * texel));
+    let luma_m = luma(rgb_m);
+    let luma_min = min(luma_m, min(min(luma_nw, luma_ne), min(luma_sw, luma_se)));
+    let luma_max = max(luma_m, max(max(luma_nw, luma_ne), max(luma_sw, luma_se)));
+
+    var dir = vec2<f32>(
+        (luma_sw + luma_se) - (luma_nw + luma_ne),
+        (luma_nw + luma_sw) - (luma_ne + luma_se),
+    );
+    let dir_reduce = max((luma_nw + luma_ne + luma_sw + luma_se) * 0.25 * reduce_mul, reduce_min);
+    let rcp_dir_min = 1.0 / (min(abs(dir.x), abs(dir.y)) + dir_reduce);
+    dir = clamp(dir * rcp_dir_min, vec2<f32>(-span_max), vec2<f32>(span_max)) * texel;
+
+    let rgb_a = 0.5 * (sample_ldr(uv + dir * (1.0 / 3.0 - 0.5)) + sample_ldr(uv + dir * (2.0 / 3.0 - 0.5)));
+    let rgb_b = rgb_a * 0.5 + 0.25 * (sample_ldr(uv - dir * 0.5) + sample_ldr(uv + dir * 0.5));
+    let luma_b = luma(rgb_b);
+    return select(rgb_b, rgb_a, luma_b < luma_min || luma_b > luma_max);
+}
+
+fn vignette(uv: vec2<f32>) -> f32 {
+    let distance = length(uv - vec2<f32>(0.5)) * 1.41421356;
+    let t = clamp((distance - post_process.vignette_radius) / max(post_process.vignette_smoothness, 0.0001), 0.0, 1.0);
+    let falloff = t * t * (3.0 - 2.0 * t);
+    return 1.0 - falloff * post_process.vignette_intensity;
+}
+
+fn color_grade(color: vec3<f32>) -> vec3<f32> {
+    let size = f32(textureDimensions(t_lut).x);
+    let encoded = linear_to_srgb(clamp(color, vec3<f32>(0.0), vec3<f32>(1.0)));
+    let uvw = encoded * (size - 1.0) / size + 0.5 / size;
+    let graded = srgb_to_linear(textureSampleLevel(t_lut, s_linear, uvw, 0.0).rgb);
+    return mix(color, graded, post_process.lut_strength);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    var color: vec3<f32>;
+    if (post_process.fxaa != 0u) {
+        color = fxaa(in.uv);
+    } else {
+        color = sample_ldr(in.uv);
+    }
+    if (post_process.color_grading != 0u) {
+        color = color_grade(color);
+    }
+    if (post_process.vignette != 0u) {
+        color = color * vignette(in.uv);
+    }
+    if (post_process.encode_srgb != 0u) {
+        color = linear_to_srgb(color);
+    }
+    return vec4<f32>(color, 1.0);
+}
@@ -0,0 +1,75 @@
+use crate::pass::{MeshPass, TonemapPass};
+use crate::render_graph::*;
+
+// Color and depth targets the scene passes draw into.
+// With MSAA the passes draw into a multisampled texture that is resolved into `hdr`,
+// the passes after them only ever see the resolved texture.
+#[derive(Debug, Clone, Copy)]
+pub struct SceneTarget {
+    pub hdr: RenderGraphTexture,
+    pub msaa: Option<RenderGraphTexture>,
+    pub depth: RenderGraphTexture,
+    pub sample_count: u32,
+}
+impl SceneTarget {
+    pub fn new(render_graph: &mut RenderGraph, sample_count: u32) -> Self {
+        let hdr = render_graph.create_texture(RenderGraphTextureDescriptor::new(
+            "hdr",
+            TonemapPass::HDR_FORMAT,
+        ));
+        let msaa = (sample_count > 1).then(|| {
+            render_graph.create_texture(
+                RenderGraphTextureDescriptor::new("hdr_msaa", TonemapPass::HDR_FORMAT)
+                    .with_sample_count(sample_count),
+            )
+        });
+        let depth = render_graph.create_texture(
+            RenderGraphTextureDescriptor::new("depth", MeshPass::DEPTH_FORMAT)
+                .with_sample_count(sample_count),
+        );
+        Self {
+            hdr,
+            msaa,
+            depth,
+            sample_count,
+        }
+    }
+
+    pub fn setup(&self, builder: &mut RenderGraphPassBuilder, depth: bool) {
+        builder.write(self.hdr);
+        if let Some(msaa) = self.msaa {
+            builder.write(msaa);
+        }
+        if depth {
+            builder.write(self.depth);
+        }
+    }
+
+    pub fn color_attachment<'a>(
+        &self,
+        context: &RenderGraphContext<'a>,
+        load: wgpu::LoadOp<wgpu::Color>,
+    ) -> wgpu::RenderPassColorAttachment<'a> {
+        let ops = wgpu::Operations { load, store: true };
+        match self.msaa {
+            Some(msaa) => wgpu::RenderPassColorAttachment {
+                view: context.view(msaa),
+                resolve_target: Some(context.view(self.hdr)),
+                ops,
+            },
+            None => wgpu::RenderPassColorAttachment {
+                view: context.view(self.hdr),
+                resolve_target: None,
+                ops,
+            },
+        }
+    }
+
+    pub fn multisample(&self) -> wgpu::MultisampleState {
+        wgpu::MultisampleState {
+            count: self.sample_count,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        }
+    }
+}
@@ -1,6 +1,9 @@
+use shader_library::{shader_source, ShaderLibrary};
 use wgpu::util::DeviceExt;
 
-use crate::resources::*;
+use crate::pass::{SceneTarget, TonemapPass};
+use crate::render_graph::*;
+use crate::state::*;
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
@@ -61,9 +64,29 @@ pub struct TexturePass {
     shader: wgpu::ShaderModule,
     vertex_buffer: wgpu::Buffer,
     sampler: wgpu::Sampler,
+    target: SceneTarget,
 }
 impl TexturePass {
-    pub fn new(device: &wgpu::Device) -> Self {
+    const BIND_GROUP_LAYOUT: [wgpu::BindGroupLayoutEntry; 2] = [
+        wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                multisampled: false,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            },
+            count: None,
+        },
+        wgpu::BindGroupLayoutEntry {
+            binding: 1,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+            count: None,
+        },
+    ];
+
+    pub fn new(device: &wgpu::Device, shaders: &ShaderLibrary, target: SceneTarget) -> Self {
         let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
             address_mode_u: wgpu::AddressMode::ClampToEdge,
             address_mode_v: wgpu::AddressMode::ClampToEdge,
@@ -74,10 +97,11 @@ impl TexturePass {
             ..Default::default()
         });
 
-        let shader = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
-            label: Some("Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("texture_pass_shader.wgsl").into()),
-        });
+        let shader = shaders.create_shader_module(
+            device,
+            shader_source!("pass/texture_pass_shader.wgsl"),
+            &[],
+        );
 
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Vertex Buffer"),
@@ -89,18 +113,29 @@ impl TexturePass {
             shader,
             vertex_buffer,
             sampler,
+            target,
         }
     }
+}
+impl RenderGraphPass<MainStateScene> for TexturePass {
+    fn name(&self) -> &str {
+        "Texture Pass"
+    }
 
-    pub fn render(
-        &self,
+    fn setup(&self, builder: &mut RenderGraphPassBuilder) {
+        self.target.setup(builder, false);
+    }
+
+    fn execute(
+        &mut self,
         encoder: &mut wgpu::CommandEncoder,
-        device: &wgpu::Device,
-        queue: &wgpu::Queue,
-        config: &wgpu::SurfaceConfiguration,
-        view: &wgpu::TextureView,
-        resources: &Resources,
+        context: &RenderGraphContext,
+        scene: &mut MainStateScene,
     ) {
+        let device = context.device;
+        let queue = context.queue;
+        let resources = &scene.resources;
+
         let base_color_texture_resources =
             resources.texture(crate::resources::hash("room_tex_base_color"));
         let texture_size = wgpu::Extent3d {
@@ -135,24 +170,7 @@ impl TexturePass {
         let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
 
         let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            entries: &[
-                wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Texture {
-                        multisampled: false,
-                        view_dimension: wgpu::TextureViewDimension::D2,
-                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: 1,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                    count: None,
-                },
-            ],
+            entries: &Self::BIND_GROUP_LAYOUT,
             label: Some("texture_bind_group_layout"),
         });
         let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
@@ -189,7 +207,7 @@ impl TexturePass {
                 module: &self.shader,
                 entry_point: "fs_main",
                 targets: &[wgpu::ColorTargetState {
-                    format: config.format,
+                    format: TonemapPass::HDR_FORMAT,
                     blend: Some(wgpu::BlendState::REPLACE),
                     write_mask: wgpu::ColorWrites::ALL,
                 }],
@@ -204,24 +222,15 @@ impl TexturePass {
                 conservative: false,
             },
             depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
+            multisample: self.target.multisample(),
             multiview: None,
         });
 
         let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("Render Pass"),
-            color_attachments: &[wgpu::RenderPassColorAttachment {
-                view,
-                resolve_target: None,
-                ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Load,
-                    store: true,
-                },
-            }],
+            color_attachments: &[self
+                .target
+                .color_attachment(context, wgpu::LoadOp::Clear(wgpu::Color::BLACK))],
             depth_stencil_attachment: None,
         });
 
@@ -231,3 +240,25 @@ impl TexturePass {
         render_pass.draw(0..6, 0..1);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shader_library::PipelineInterface;
+
+    #[test]
+    fn it_should_match_shader_interface() {
+        ShaderLibrary::new()
+            .check_interface(
+                shader_source!("pass/texture_pass_shader.wgsl"),
+                &[],
+                &PipelineInterface {
+                    vertex_entry_point: "vs_main",
+                    fragment_entry_point: Some("fs_main"),
+                    vertex_buffers: &[Vertex::desc()],
+                    bind_group_layouts: &[&TexturePass::BIND_GROUP_LAYOUT],
+                },
+            )
+            .unwrap();
+    }
+}
@@ -1,16 +1,16 @@
 // Vertex shader
 
 struct VertexInput {
-    [[location(0)]] position: vec2<f32>;
-    [[location(1)]] uv: vec2<f32>;
-};
+    @location(0) position: vec2<f32>,
+    @location(1) uv: vec2<f32>,
+}
 
 struct VertexOutput {
-    [[builtin(position)]] clip_position: vec4<f32>;
-    [[location(0)]] tex_coords: vec2<f32>;
-};
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+}
 
-[[stage(vertex)]]
+@vertex
 fn vs_main(
     model: VertexInput,
 ) -> VertexOutput {
@@ -22,14 +22,14 @@ fn vs_main(
 
 // Fragment shader
 
-[[group(0), binding(0)]]
+@group(0) @binding(0)
 var t_diffuse: texture_2d<f32>;
 
-[[group(0), binding(1)]]
+@group(0) @binding(1)
 var s_diffuse: sampler;
 
-[[stage(fragment)]]
-fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let color = textureSample(t_diffuse, s_diffuse, in.tex_coords).rgb;
-    return vec4<f32>(pow(color, vec3<f32>(1.0/2.2)), 1.0);
+    return vec4<f32>(color, 1.0);
 }
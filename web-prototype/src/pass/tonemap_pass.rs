@@ -0,0 +1,247 @@
+use serde::{Deserialize, Serialize};
+use shader_library::{shader_source, ShaderLibrary};
+
+use crate::render_graph::*;
+use crate::state::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TonemapOperator {
+    Aces,
+    Reinhard,
+    AgX,
+}
+impl TonemapOperator {
+    pub const ALL: [Self; 3] = [Self::Aces, Self::Reinhard, Self::AgX];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Aces => "ACES",
+            Self::Reinhard => "Reinhard",
+            Self::AgX => "AgX",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TonemapSettings {
+    pub operator: TonemapOperator,
+    // Exposure compensation in stops.
+    pub exposure: f32,
+}
+impl Default for TonemapSettings {
+    fn default() -> Self {
+        Self {
+            operator: TonemapOperator::Aces,
+            exposure: 0.0,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct TonemapUniform {
+    exposure: f32,
+    operator: u32,
+    encode_srgb: u32,
+    padding: u32,
+}
+
+// Maps the linear HDR scene color to the display range, into the surface or an LDR texture.
+// Gamma is only applied here, by the hardware for sRGB formats and in the shader otherwise.
+#[derive(Debug)]
+pub struct TonemapPass {
+    render_pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    uniform_buffer: wgpu::Buffer,
+    encode_srgb: bool,
+    hdr: RenderGraphTexture,
+    output: RenderGraphTexture,
+}
+impl TonemapPass {
+    pub const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+    const BIND_GROUP_LAYOUT: [wgpu::BindGroupLayoutEntry; 2] = [
+        wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        },
+        wgpu::BindGroupLayoutEntry {
+            binding: 1,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                multisampled: false,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                sample_type: wgpu::TextureSampleType::Float { filterable: false },
+            },
+            count: None,
+        },
+    ];
+
+    pub fn new(
+        device: &wgpu::Device,
+        shaders: &ShaderLibrary,
+        format: wgpu::TextureFormat,
+        hdr: RenderGraphTexture,
+        output: RenderGraphTexture,
+    ) -> Self {
+        let shader = shaders.create_shader_module(
+            device,
+            shader_source!("pass/tonemap_pass_shader.wgsl"),
+            &[],
+        );
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Tonemap Uniform Buffer"),
+            size: std::mem::size_of::<TonemapUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &Self::BIND_GROUP_LAYOUT,
+            label: Some("tonemap_bind_group_layout"),
+        });
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Tonemap Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Tonemap Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                }],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        Self {
+            render_pipeline,
+            bind_group_layout,
+            uniform_buffer,
+            encode_srgb: !format.describe().srgb,
+            hdr,
+            output,
+        }
+    }
+}
+impl RenderGraphPass<MainStateScene> for TonemapPass {
+    fn name(&self) -> &str {
+        "Tonemap Pass"
+    }
+
+    fn setup(&self, builder: &mut RenderGraphPassBuilder) {
+        builder.read(self.hdr).write(self.output);
+    }
+
+    fn execute(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        context: &RenderGraphContext,
+        scene: &mut MainStateScene,
+    ) {
+        let device = context.device;
+        let queue = context.queue;
+
+        queue.write_buffer(
+            &self.uniform_buffer,
+            0,
+            bytemuck::cast_slice(&[TonemapUniform {
+                exposure: scene.tonemap.exposure,
+                operator: scene.tonemap.operator as u32,
+                encode_srgb: self.encode_srgb as u32,
+                padding: 0,
+            }]),
+        );
+
+        // The HDR view may change between frames when the graph reallocates its textures.
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(context.view(self.hdr)),
+                },
+            ],
+            label: Some("tonemap_bind_group"),
+        });
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Tonemap Pass"),
+            color_attachments: &[wgpu::RenderPassColorAttachment {
+                view: context.view(self.output),
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+        });
+
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shader_library::PipelineInterface;
+
+    #[test]
+    fn it_should_match_shader_interface() {
+        ShaderLibrary::new()
+            .check_interface(
+                shader_source!("pass/tonemap_pass_shader.wgsl"),
+                &[],
+                &PipelineInterface {
+                    vertex_entry_point: "vs_main",
+                    fragment_entry_point: Some("fs_main"),
+                    vertex_buffers: &[],
+                    bind_group_layouts: &[&TonemapPass::BIND_GROUP_LAYOUT],
+                },
+            )
+            .unwrap();
+    }
+}
@@ -1,5 +1,10 @@
+use shader_library::{shader_source, ShaderLibrary};
 use wgpu::util::DeviceExt;
 
+use crate::pass::{SceneTarget, TonemapPass};
+use crate::render_graph::*;
+use crate::state::*;
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 struct Vertex {
@@ -46,13 +51,15 @@ const VERTICES: &[Vertex] = &[
 pub struct TrianglePass {
     render_pipeline: wgpu::RenderPipeline,
     vertex_buffer: wgpu::Buffer,
+    target: SceneTarget,
 }
 impl TrianglePass {
-    pub fn new(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> Self {
-        let shader = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
-            label: Some("Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("triangle_pass_shader.wgsl").into()),
-        });
+    pub fn new(device: &wgpu::Device, shaders: &ShaderLibrary, target: SceneTarget) -> Self {
+        let shader = shaders.create_shader_module(
+            device,
+            shader_source!("pass/triangle_pass_shader.wgsl"),
+            &[],
+        );
 
         let render_pipeline_layout =
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
@@ -73,7 +80,7 @@ impl TrianglePass {
                 module: &shader,
                 entry_point: "fs_main",
                 targets: &[wgpu::ColorTargetState {
-                    format: config.format,
+                    format: TonemapPass::HDR_FORMAT,
                     blend: Some(wgpu::BlendState::REPLACE),
                     write_mask: wgpu::ColorWrites::ALL,
                 }],
@@ -88,11 +95,7 @@ impl TrianglePass {
                 conservative: false,
             },
             depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
+            multisample: target.multisample(),
             multiview: None,
         });
 
@@ -105,30 +108,41 @@ impl TrianglePass {
         Self {
             render_pipeline,
             vertex_buffer,
+            target,
         }
     }
+}
+impl RenderGraphPass<MainStateScene> for TrianglePass {
+    fn name(&self) -> &str {
+        "Triangle Pass"
+    }
+
+    fn setup(&self, builder: &mut RenderGraphPassBuilder) {
+        self.target.setup(builder, false);
+    }
 
-    pub fn render(
-        &self,
+    fn execute(
+        &mut self,
         encoder: &mut wgpu::CommandEncoder,
-        clear_color: &vek::Rgba<f32>,
-        view: &wgpu::TextureView,
+        context: &RenderGraphContext,
+        scene: &mut MainStateScene,
     ) {
+        let clear_color = match &scene.view_state {
+            MainStateViewState::Triangle { clear_color, .. } => *clear_color,
+            _ => vek::Rgba::black(),
+        };
+
         let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("Render Pass"),
-            color_attachments: &[wgpu::RenderPassColorAttachment {
-                view,
-                resolve_target: None,
-                ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(wgpu::Color {
-                        r: (clear_color.r as f64).powf(1.0 / 2.2),
-                        g: (clear_color.g as f64).powf(1.0 / 2.2),
-                        b: (clear_color.b as f64).powf(1.0 / 2.2),
-                        a: (clear_color.a as f64),
-                    }),
-                    store: true,
-                },
-            }],
+            color_attachments: &[self.target.color_attachment(
+                context,
+                wgpu::LoadOp::Clear(wgpu::Color {
+                    r: clear_color.r as f64,
+                    g: clear_color.g as f64,
+                    b: clear_color.b as f64,
+                    a: clear_color.a as f64,
+                }),
+            )],
             depth_stencil_attachment: None,
         });
 
@@ -137,3 +151,25 @@ impl TrianglePass {
         render_pass.draw(0..3, 0..1);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shader_library::PipelineInterface;
+
+    #[test]
+    fn it_should_match_shader_interface() {
+        ShaderLibrary::new()
+            .check_interface(
+                shader_source!("pass/triangle_pass_shader.wgsl"),
+                &[],
+                &PipelineInterface {
+                    vertex_entry_point: "vs_main",
+                    fragment_entry_point: Some("fs_main"),
+                    vertex_buffers: &[Vertex::desc()],
+                    bind_group_layouts: &[],
+                },
+            )
+            .unwrap();
+    }
+}
@@ -0,0 +1,9 @@
+mod chrome_trace;
+mod gpu_timer;
+mod profiler;
+mod profiler_stats;
+
+pub use chrome_trace::{chrome_trace, save_chrome_trace};
+pub use gpu_timer::GpuTimer;
+pub use profiler::{Profiler, ProfilerEvent, ProfilerEventKind, ProfilerFrame};
+pub use profiler_stats::{FrameStats, ScopeStats};
@@ -0,0 +1,124 @@
+use std::fmt::Write;
+
+use crate::profiler::*;
+
+// Frames as Chrome trace event JSON, viewable in chrome://tracing or Perfetto.
+// CPU scopes and GPU timestamps are put on separate threads of one process.
+pub fn chrome_trace(frames: &[ProfilerFrame]) -> String {
+    let mut events = vec![];
+    for frame in frames {
+        events.push(trace_event(
+            &format!("Frame {}", frame.index),
+            "frame",
+            frame.start,
+            frame.duration,
+            0,
+        ));
+        for event in &frame.events {
+            let (category, thread) = match event.kind {
+                ProfilerEventKind::Cpu => ("cpu", 1),
+                ProfilerEventKind::Gpu => ("gpu", 2),
+            };
+            events.push(trace_event(
+                &event.name,
+                category,
+                event.start,
+                event.duration,
+                thread,
+            ));
+        }
+    }
+    for (thread, name) in [(0, "Frames"), (1, "CPU"), (2, "GPU")] {
+        events.push(format!(
+            r#"{{"name":"thread_name","ph":"M","pid":1,"tid":{},"args":{{"name":"{}"}}}}"#,
+            thread, name
+        ));
+    }
+    format!("{{\"traceEvents\":[\n{}\n]}}\n", events.join(",\n"))
+}
+
+// Write the trace to `trace.json` in the working directory.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_chrome_trace(trace: &str) {
+    match std::fs::write("trace.json", trace) {
+        Ok(()) => log::info!("Saved trace.json"),
+        Err(e) => log::warn!("Failed to save trace.json: {}", e),
+    }
+}
+
+// Download the trace as `trace.json`.
+#[cfg(target_arch = "wasm32")]
+pub fn save_chrome_trace(trace: &str) {
+    use wasm_bindgen::JsCast;
+
+    let parts = js_sys::Array::of1(&wasm_bindgen::JsValue::from_str(trace));
+    let blob = web_sys::Blob::new_with_str_sequence(&parts)
+        .unwrap_or_else(|_| panic!("Failed to create trace blob"));
+    let url = web_sys::Url::create_object_url_with_blob(&blob)
+        .unwrap_or_else(|_| panic!("Failed to create trace url"));
+    let anchor = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.create_element("a").ok())
+        .and_then(|element| element.dyn_into::<web_sys::HtmlAnchorElement>().ok())
+        .unwrap_or_else(|| panic!("Failed to create download link"));
+    anchor.set_href(&url);
+    anchor.set_download("trace.json");
+    anchor.click();
+    if web_sys::Url::revoke_object_url(&url).is_err() {
+        log::warn!("Failed to revoke trace url");
+    }
+}
+
+fn trace_event(name: &str, category: &str, start: f64, duration: f64, thread: u32) -> String {
+    format!(
+        r#"{{"name":"{}","cat":"{}","ph":"X","ts":{:.3},"dur":{:.3},"pid":1,"tid":{}}}"#,
+        escape(name),
+        category,
+        start,
+        duration,
+        thread
+    )
+}
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => {
+                let _ = write!(escaped, "\\u{:04x}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_export_chrome_trace_events() {
+        let trace = chrome_trace(&[ProfilerFrame {
+            index: 3,
+            start: 10.0,
+            duration: 16000.0,
+            events: vec![ProfilerEvent {
+                name: "Mesh \"Pass\"".into(),
+                kind: ProfilerEventKind::Gpu,
+                start: 20.0,
+                duration: 1.5,
+                depth: 0,
+            }],
+        }]);
+        assert!(trace.starts_with("{\"traceEvents\":["));
+        assert!(trace.contains(
+            r#"{"name":"Frame 3","cat":"frame","ph":"X","ts":10.000,"dur":16000.000,"pid":1,"tid":0}"#
+        ));
+        assert!(trace.contains(
+            r#"{"name":"Mesh \"Pass\"","cat":"gpu","ph":"X","ts":20.000,"dur":1.500,"pid":1,"tid":2}"#
+        ));
+    }
+}
@@ -0,0 +1,208 @@
+use futures::FutureExt;
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::profiler::*;
+
+type MapFuture = Pin<Box<dyn Future<Output = Result<(), wgpu::BufferAsyncError>> + Send>>;
+
+enum ReadbackState {
+    Free,
+    // Resolved in an encoder that is not submitted yet.
+    Resolved,
+    Mapping(MapFuture),
+}
+
+struct Readback {
+    buffer: wgpu::Buffer,
+    state: ReadbackState,
+    frame_index: u64,
+    // CPU time the first timestamp is aligned to.
+    cpu_start: f64,
+    scopes: Vec<String>,
+}
+
+// Timestamp queries around the passes of a render graph, read back without stalling.
+// Only available with `wgpu::Features::TIMESTAMP_QUERY`.
+pub struct GpuTimer {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    // Nanoseconds per timestamp tick.
+    period: f32,
+    readbacks: Vec<Readback>,
+    frame: Option<(u64, f64)>,
+    scopes: Vec<String>,
+}
+impl GpuTimer {
+    const MAX_SCOPES: u32 = 32;
+    const BUFFER_SIZE: wgpu::BufferAddress =
+        (Self::MAX_SCOPES * 2) as wgpu::BufferAddress * std::mem::size_of::<u64>() as u64;
+
+    // `None` when the device cannot write timestamps.
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Option<Self> {
+        if !device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            return None;
+        }
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("gpu_timer_query_set"),
+            ty: wgpu::QueryType::Timestamp,
+            count: Self::MAX_SCOPES * 2,
+        });
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("gpu_timer_resolve_buffer"),
+            size: Self::BUFFER_SIZE,
+            // wgpu 0.12 resolves queries into COPY_DST buffers, the timestamps are copied out for mapping.
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        Some(Self {
+            query_set,
+            resolve_buffer,
+            period: queue.get_timestamp_period(),
+            readbacks: vec![],
+            frame: None,
+            scopes: vec![],
+        })
+    }
+
+    // Start timing the scopes of an encoder recorded in the current frame of the profiler.
+    pub fn begin(&mut self, profiler: &Profiler) {
+        self.scopes.clear();
+        self.frame = profiler
+            .frame_index()
+            .map(|frame_index| (frame_index, profiler.now()));
+    }
+
+    // Query index of the scope start, `None` when the frame is not profiled or full.
+    pub fn begin_scope(&mut self, encoder: &mut wgpu::CommandEncoder) -> Option<u32> {
+        let index = self.scopes.len() as u32;
+        if self.frame.is_none() || index >= Self::MAX_SCOPES {
+            return None;
+        }
+        encoder.write_timestamp(&self.query_set, index * 2);
+        Some(index)
+    }
+
+    pub fn end_scope(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        scope: Option<u32>,
+        name: &str,
+    ) {
+        if let Some(index) = scope {
+            encoder.write_timestamp(&self.query_set, index * 2 + 1);
+            self.scopes.push(name.to_string());
+        }
+    }
+
+    // Copy the timestamps of the encoder into a free readback buffer, call before submitting it.
+    pub fn resolve(&mut self, device: &wgpu::Device, encoder: &mut wgpu::CommandEncoder) {
+        let (frame_index, cpu_start) = match self.frame.take() {
+            Some(frame) if !self.scopes.is_empty() => frame,
+            _ => return,
+        };
+        let query_count = self.scopes.len() as u32 * 2;
+        encoder.resolve_query_set(&self.query_set, 0..query_count, &self.resolve_buffer, 0);
+
+        let free = self
+            .readbacks
+            .iter()
+            .position(|readback| matches!(readback.state, ReadbackState::Free));
+        let index = match free {
+            Some(index) => index,
+            None => {
+                self.readbacks.push(Readback {
+                    buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                        label: Some("gpu_timer_readback_buffer"),
+                        size: Self::BUFFER_SIZE,
+                        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                        mapped_at_creation: false,
+                    }),
+                    state: ReadbackState::Free,
+                    frame_index: 0,
+                    cpu_start: 0.0,
+                    scopes: vec![],
+                });
+                self.readbacks.len() - 1
+            }
+        };
+        let readback = &mut self.readbacks[index];
+        encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &readback.buffer,
+            0,
+            query_count as wgpu::BufferAddress * std::mem::size_of::<u64>() as u64,
+        );
+        readback.state = ReadbackState::Resolved;
+        readback.frame_index = frame_index;
+        readback.cpu_start = cpu_start;
+        readback.scopes = std::mem::take(&mut self.scopes);
+    }
+
+    // Start mapping the resolved timestamps, call after submitting the encoder.
+    pub fn map(&mut self) {
+        for readback in &mut self.readbacks {
+            if let ReadbackState::Resolved = readback.state {
+                let map_future = readback.buffer.slice(..).map_async(wgpu::MapMode::Read);
+                readback.state = ReadbackState::Mapping(Box::pin(map_future));
+            }
+        }
+    }
+
+    // Add the timestamps that finished mapping to the frames they were recorded in.
+    pub fn collect(&mut self, device: &wgpu::Device, profiler: &Profiler) {
+        device.poll(wgpu::Maintain::Poll);
+        for readback in &mut self.readbacks {
+            let ready = match &mut readback.state {
+                ReadbackState::Mapping(map_future) => match map_future.now_or_never() {
+                    Some(result) => {
+                        result.unwrap_or_else(|_| panic!("Failed to map timestamp buffer."));
+                        true
+                    }
+                    None => false,
+                },
+                _ => false,
+            };
+            if !ready {
+                continue;
+            }
+
+            let timestamps = {
+                let mapped = readback.buffer.slice(..).get_mapped_range();
+                mapped
+                    .chunks_exact(std::mem::size_of::<u64>())
+                    .take(readback.scopes.len() * 2)
+                    .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap_or_default()))
+                    .collect::<Vec<_>>()
+            };
+            readback.buffer.unmap();
+            readback.state = ReadbackState::Free;
+
+            // Nanoseconds to the microseconds of the profiler.
+            let scale = self.period as f64 / 1000.0;
+            let base = timestamps[0];
+            let events = readback
+                .scopes
+                .drain(..)
+                .zip(timestamps.chunks(2))
+                .map(|(name, pair)| ProfilerEvent {
+                    name,
+                    kind: ProfilerEventKind::Gpu,
+                    start: readback.cpu_start + pair[0].saturating_sub(base) as f64 * scale,
+                    duration: pair[1].saturating_sub(pair[0]) as f64 * scale,
+                    depth: 0,
+                })
+                .collect();
+            profiler.add_events(readback.frame_index, events);
+        }
+    }
+}
+impl std::fmt::Debug for GpuTimer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GpuTimer")
+            .field("period", &self.period)
+            .field("readbacks", &self.readbacks.len())
+            .finish()
+    }
+}
@@ -0,0 +1,221 @@
+use instant::Instant;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProfilerEventKind {
+    Cpu,
+    Gpu,
+}
+
+// A timed scope, times are in microseconds since the profiler was created.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfilerEvent {
+    pub name: String,
+    pub kind: ProfilerEventKind,
+    pub start: f64,
+    pub duration: f64,
+    pub depth: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfilerFrame {
+    pub index: u64,
+    pub start: f64,
+    pub duration: f64,
+    pub events: Vec<ProfilerEvent>,
+}
+
+#[derive(Debug)]
+struct ProfilerInner {
+    enabled: bool,
+    epoch: Instant,
+    capacity: usize,
+    current: Option<ProfilerFrame>,
+    next_index: u64,
+    depth: u32,
+    history: VecDeque<ProfilerFrame>,
+}
+
+// Records CPU scopes and GPU timestamps per frame and keeps the last frames.
+// Cloning shares the recording, so every render graph can time its passes into it.
+#[derive(Debug, Clone)]
+pub struct Profiler {
+    inner: Arc<Mutex<ProfilerInner>>,
+}
+impl Profiler {
+    pub const DEFAULT_CAPACITY: usize = 240;
+
+    pub fn new() -> Self {
+        Self::with_capacity(Self::DEFAULT_CAPACITY)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(ProfilerInner {
+                enabled: true,
+                epoch: Instant::now(),
+                capacity,
+                current: None,
+                next_index: 0,
+                depth: 0,
+                history: VecDeque::with_capacity(capacity),
+            })),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ProfilerInner> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|_| panic!("Profiler lock poisoned."))
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.lock().enabled
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.lock().enabled = enabled;
+    }
+
+    // Microseconds since the profiler was created.
+    pub fn now(&self) -> f64 {
+        self.lock().epoch.elapsed().as_secs_f64() * 1_000_000.0
+    }
+
+    // Finish the current frame and start the next one.
+    pub fn begin_frame(&self) {
+        let now = self.now();
+        let mut inner = self.lock();
+        if let Some(mut frame) = inner.current.take() {
+            frame.duration = now - frame.start;
+            if inner.history.len() == inner.capacity {
+                inner.history.pop_front();
+            }
+            inner.history.push_back(frame);
+        }
+        if inner.enabled {
+            let index = inner.next_index;
+            inner.next_index += 1;
+            inner.depth = 0;
+            inner.current = Some(ProfilerFrame {
+                index,
+                start: now,
+                duration: 0.0,
+                events: vec![],
+            });
+        }
+    }
+
+    // Index of the frame being recorded, `None` while disabled.
+    pub fn frame_index(&self) -> Option<u64> {
+        self.lock().current.as_ref().map(|frame| frame.index)
+    }
+
+    // Time a CPU scope until the returned guard is dropped.
+    pub fn scope(&self, name: impl Into<String>) -> ProfilerScope {
+        let start = self.now();
+        let mut inner = self.lock();
+        if inner.current.is_none() {
+            return ProfilerScope { active: None };
+        }
+        let depth = inner.depth;
+        inner.depth += 1;
+        ProfilerScope {
+            active: Some((self.clone(), name.into(), start, depth)),
+        }
+    }
+
+    fn end_scope(&self, name: String, start: f64, depth: u32) {
+        let now = self.now();
+        let mut inner = self.lock();
+        inner.depth = inner.depth.saturating_sub(1);
+        if let Some(frame) = &mut inner.current {
+            frame.events.push(ProfilerEvent {
+                name,
+                kind: ProfilerEventKind::Cpu,
+                start,
+                duration: now - start,
+                depth,
+            });
+        }
+    }
+
+    // GPU timestamps arrive a few frames late, they are added to the frame they were recorded in.
+    pub fn add_events(&self, frame_index: u64, events: Vec<ProfilerEvent>) {
+        let mut inner = self.lock();
+        let ProfilerInner {
+            current, history, ..
+        } = &mut *inner;
+        let frame = current
+            .iter_mut()
+            .chain(history.iter_mut().rev())
+            .find(|frame| frame.index == frame_index);
+        if let Some(frame) = frame {
+            frame.events.extend(events);
+        }
+    }
+
+    // The finished frames, oldest first.
+    pub fn frames(&self) -> Vec<ProfilerFrame> {
+        self.lock().history.iter().cloned().collect()
+    }
+
+    pub fn clear(&self) {
+        self.lock().history.clear();
+    }
+}
+impl Default for Profiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Records its scope into the profiler when dropped.
+pub struct ProfilerScope {
+    active: Option<(Profiler, String, f64, u32)>,
+}
+impl Drop for ProfilerScope {
+    fn drop(&mut self) {
+        if let Some((profiler, name, start, depth)) = self.active.take() {
+            profiler.end_scope(name, start, depth);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_record_nested_scopes_per_frame() {
+        let profiler = Profiler::with_capacity(2);
+        for _ in 0..3 {
+            profiler.begin_frame();
+            let _outer = profiler.scope("Render");
+            let _inner = profiler.scope("Pass");
+        }
+        profiler.begin_frame();
+
+        let frames = profiler.frames();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].index, 1);
+        let events = &frames[1].events;
+        assert_eq!(events.len(), 2);
+        assert_eq!((events[0].name.as_str(), events[0].depth), ("Pass", 1));
+        assert_eq!((events[1].name.as_str(), events[1].depth), ("Render", 0));
+        assert!(events[1].duration >= events[0].duration);
+
+        profiler.add_events(
+            1,
+            vec![ProfilerEvent {
+                name: "Pass".into(),
+                kind: ProfilerEventKind::Gpu,
+                start: 0.0,
+                duration: 1.0,
+                depth: 0,
+            }],
+        );
+        assert_eq!(profiler.frames()[0].events.len(), 3);
+    }
+}
@@ -0,0 +1,119 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::profiler::*;
+
+// Frame time statistics in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FrameStats {
+    pub average: f64,
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+impl FrameStats {
+    pub fn new(frames: &[ProfilerFrame]) -> Self {
+        let mut durations = frames
+            .iter()
+            .map(|frame| frame.duration / 1000.0)
+            .collect::<Vec<_>>();
+        if durations.is_empty() {
+            return Self::default();
+        }
+        durations.sort_by(|a, b| a.total_cmp(b));
+        Self {
+            average: durations.iter().sum::<f64>() / durations.len() as f64,
+            p50: percentile(&durations, 50.0),
+            p95: percentile(&durations, 95.0),
+            p99: percentile(&durations, 99.0),
+            max: durations[durations.len() - 1],
+        }
+    }
+}
+
+impl fmt::Display for FrameStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "avg {:.2} ms, p50 {:.2} ms, p95 {:.2} ms, p99 {:.2} ms, max {:.2} ms",
+            self.average, self.p50, self.p95, self.p99, self.max
+        )
+    }
+}
+
+// Nearest rank percentile of sorted values.
+fn percentile(sorted: &[f64], percent: f64) -> f64 {
+    let rank = (percent / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+// Average milliseconds per frame spent in the top level scopes of a name, e.g. a pass.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScopeStats {
+    pub name: String,
+    pub cpu: Option<f64>,
+    pub gpu: Option<f64>,
+}
+impl ScopeStats {
+    // Scopes nested below `depth` are left out, sorted by name.
+    pub fn new(frames: &[ProfilerFrame], depth: u32) -> Vec<Self> {
+        let mut totals = BTreeMap::<&str, (Option<f64>, Option<f64>)>::new();
+        for event in frames.iter().flat_map(|frame| &frame.events) {
+            if event.depth > depth {
+                continue;
+            }
+            let (cpu, gpu) = totals.entry(event.name.as_str()).or_default();
+            let total = match event.kind {
+                ProfilerEventKind::Cpu => cpu,
+                ProfilerEventKind::Gpu => gpu,
+            };
+            *total = Some(total.unwrap_or_default() + event.duration);
+        }
+        let frame_count = frames.len().max(1) as f64;
+        totals
+            .into_iter()
+            .map(|(name, (cpu, gpu))| Self {
+                name: name.to_string(),
+                cpu: cpu.map(|total| total / 1000.0 / frame_count),
+                gpu: gpu.map(|total| total / 1000.0 / frame_count),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(index: u64, duration_ms: f64) -> ProfilerFrame {
+        ProfilerFrame {
+            index,
+            start: 0.0,
+            duration: duration_ms * 1000.0,
+            events: vec![ProfilerEvent {
+                name: "Pass".into(),
+                kind: ProfilerEventKind::Cpu,
+                start: 0.0,
+                duration: duration_ms * 500.0,
+                depth: 0,
+            }],
+        }
+    }
+
+    #[test]
+    fn it_should_compute_frame_percentiles() {
+        let frames = (1..=100).map(|i| frame(i, i as f64)).collect::<Vec<_>>();
+        let stats = FrameStats::new(&frames);
+        assert_eq!(stats.p50, 50.0);
+        assert_eq!(stats.p95, 95.0);
+        assert_eq!(stats.p99, 99.0);
+        assert_eq!(stats.max, 100.0);
+        assert!((stats.average - 50.5).abs() < 1e-9);
+
+        let scopes = ScopeStats::new(&frames, 0);
+        assert_eq!(scopes.len(), 1);
+        assert!((scopes[0].cpu.unwrap() - 25.25).abs() < 1e-9);
+        assert_eq!(scopes[0].gpu, None);
+    }
+}
@@ -0,0 +1,9 @@
+mod render_graph;
+mod render_graph_pass;
+mod render_graph_texture;
+
+pub use render_graph::RenderGraph;
+pub use render_graph_pass::{RenderGraphContext, RenderGraphPass, RenderGraphPassBuilder};
+pub use render_graph_texture::{
+    RenderGraphTexture, RenderGraphTextureDescriptor, RenderGraphTextureSize,
+};
@@ -0,0 +1,519 @@
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+use thiserror::Error;
+
+use crate::profiler::*;
+use crate::render_graph::*;
+
+#[derive(Debug, Error)]
+pub enum RenderGraphError {
+    #[error("Render graph has a cycle between passes: {0:?}")]
+    CycleError(Vec<String>),
+    #[error("Pass {pass} reads {texture} before any pass writes it")]
+    UnwrittenTextureError { pass: String, texture: String },
+    #[error("Pass {pass} uses unknown texture {texture:?}")]
+    UnknownTextureError {
+        pass: String,
+        texture: RenderGraphTexture,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct RenderGraphNode {
+    name: String,
+    reads: Vec<RenderGraphTexture>,
+    writes: Vec<RenderGraphTexture>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct PhysicalTextureSlot {
+    format: wgpu::TextureFormat,
+    size: RenderGraphTextureSize,
+    sample_count: u32,
+}
+
+#[derive(Debug)]
+struct PhysicalTexture {
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+    sample_count: u32,
+    view: wgpu::TextureView,
+}
+
+// Result of ordering, culling and aliasing the passes of one frame.
+#[derive(Debug, Clone)]
+struct CompiledRenderGraph {
+    nodes: Vec<RenderGraphNode>,
+    texture_labels: Vec<String>,
+    // Indices of the live nodes in execution order.
+    order: Vec<usize>,
+    culled: Vec<usize>,
+    // Physical slot of each transient texture, `None` if no live pass uses it.
+    aliases: Vec<Option<usize>>,
+    slots: Vec<PhysicalTextureSlot>,
+}
+impl CompiledRenderGraph {
+    fn compile(
+        nodes: Vec<RenderGraphNode>,
+        textures: &[RenderGraphTextureDescriptor],
+    ) -> Result<Self, RenderGraphError> {
+        let texture_labels = std::iter::once("surface".to_string())
+            .chain(textures.iter().map(|texture| texture.label.clone()))
+            .collect::<Vec<_>>();
+        for node in &nodes {
+            for &texture in node.reads.iter().chain(node.writes.iter()) {
+                if texture.0 >= texture_labels.len() {
+                    return Err(RenderGraphError::UnknownTextureError {
+                        pass: node.name.clone(),
+                        texture,
+                    });
+                }
+            }
+        }
+
+        // Writers of a texture run in the order they were added,
+        // and passes only reading it run after all of its writers.
+        let mut edges = vec![BTreeSet::new(); nodes.len()];
+        for texture in 0..texture_labels.len() {
+            let texture = RenderGraphTexture(texture);
+            let writers = (0..nodes.len())
+                .filter(|&i| nodes[i].writes.contains(&texture))
+                .collect::<Vec<_>>();
+            let readers = (0..nodes.len())
+                .filter(|&i| nodes[i].reads.contains(&texture) && !writers.contains(&i))
+                .collect::<Vec<_>>();
+            for pair in writers.windows(2) {
+                edges[pair[0]].insert(pair[1]);
+            }
+            for &writer in &writers {
+                for &reader in &readers {
+                    edges[writer].insert(reader);
+                }
+            }
+        }
+
+        let mut in_degree = vec![0; nodes.len()];
+        for &to in edges.iter().flatten() {
+            in_degree[to] += 1;
+        }
+        let mut ready = (0..nodes.len())
+            .filter(|&i| in_degree[i] == 0)
+            .collect::<BTreeSet<_>>();
+        let mut sorted = vec![];
+        while let Some(&i) = ready.iter().next() {
+            ready.remove(&i);
+            sorted.push(i);
+            for &to in &edges[i] {
+                in_degree[to] -= 1;
+                if in_degree[to] == 0 {
+                    ready.insert(to);
+                }
+            }
+        }
+        if sorted.len() < nodes.len() {
+            return Err(RenderGraphError::CycleError(
+                (0..nodes.len())
+                    .filter(|i| !sorted.contains(i))
+                    .map(|i| nodes[i].name.clone())
+                    .collect(),
+            ));
+        }
+
+        // Walk backwards from the surface and cull passes whose results are never used.
+        let mut needed = HashSet::from([RenderGraphTexture::SURFACE]);
+        let mut live = vec![false; nodes.len()];
+        for &i in sorted.iter().rev() {
+            let node = &nodes[i];
+            if node.writes.iter().any(|texture| needed.contains(texture)) {
+                live[i] = true;
+                for texture in &node.writes {
+                    if !node.reads.contains(texture) {
+                        needed.remove(texture);
+                    }
+                }
+                needed.extend(node.reads.iter().copied());
+            }
+        }
+        let order = sorted
+            .iter()
+            .copied()
+            .filter(|&i| live[i])
+            .collect::<Vec<_>>();
+        let culled = (0..nodes.len()).filter(|&i| !live[i]).collect();
+
+        // Lifetimes of the transient textures in the live passes.
+        let mut lifetimes = vec![None; textures.len()];
+        let mut written = HashSet::new();
+        for (position, &i) in order.iter().enumerate() {
+            let node = &nodes[i];
+            for texture in &node.reads {
+                if let Some(index) = texture.transient_index() {
+                    if !written.contains(texture) {
+                        return Err(RenderGraphError::UnwrittenTextureError {
+                            pass: node.name.clone(),
+                            texture: textures[index].label.clone(),
+                        });
+                    }
+                }
+            }
+            for texture in node.reads.iter().chain(node.writes.iter()) {
+                if let Some(index) = texture.transient_index() {
+                    let (first, _) = lifetimes[index].unwrap_or((position, position));
+                    lifetimes[index] = Some((first, position));
+                }
+            }
+            written.extend(node.writes.iter().copied());
+        }
+
+        // Textures of the same format, size and sample count
+        // whose lifetimes do not overlap share a slot.
+        let mut transients = (0..textures.len())
+            .filter_map(|index| lifetimes[index].map(|lifetime| (index, lifetime)))
+            .collect::<Vec<_>>();
+        transients.sort_by_key(|&(index, (first, _))| (first, index));
+        let mut aliases = vec![None; textures.len()];
+        let mut slots: Vec<PhysicalTextureSlot> = vec![];
+        let mut slot_last_uses: Vec<usize> = vec![];
+        for (index, (first, last)) in transients {
+            let slot = PhysicalTextureSlot {
+                format: textures[index].format,
+                size: textures[index].size,
+                sample_count: textures[index].sample_count,
+            };
+            let free_slot =
+                (0..slots.len()).find(|&s| slots[s] == slot && slot_last_uses[s] < first);
+            let s = match free_slot {
+                Some(s) => s,
+                None => {
+                    slots.push(slot);
+                    slot_last_uses.push(0);
+                    slots.len() - 1
+                }
+            };
+            slot_last_uses[s] = last;
+            aliases[index] = Some(s);
+        }
+
+        Ok(Self {
+            nodes,
+            texture_labels,
+            order,
+            culled,
+            aliases,
+            slots,
+        })
+    }
+
+    fn pass_names(&self) -> Vec<&str> {
+        self.order
+            .iter()
+            .map(|&i| self.nodes[i].name.as_str())
+            .collect()
+    }
+}
+impl fmt::Display for CompiledRenderGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let labels = |textures: &[RenderGraphTexture]| {
+            if textures.is_empty() {
+                "-".to_string()
+            } else {
+                textures
+                    .iter()
+                    .map(|texture| self.texture_labels[texture.0].as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }
+        };
+        writeln!(f, "Render graph:")?;
+        for (position, &i) in self.order.iter().enumerate() {
+            let node = &self.nodes[i];
+            writeln!(
+                f,
+                "  {}. {} (reads: {}, writes: {})",
+                position,
+                node.name,
+                labels(&node.reads),
+                labels(&node.writes)
+            )?;
+        }
+        for &i in &self.culled {
+            writeln!(f, "  culled: {}", self.nodes[i].name)?;
+        }
+        for (index, alias) in self.aliases.iter().enumerate() {
+            if let Some(slot) = alias {
+                writeln!(
+                    f,
+                    "  {} -> transient texture {} ({:?})",
+                    self.texture_labels[index + 1],
+                    slot,
+                    self.slots[*slot].format
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Orders the passes of a frame by the textures they read and write,
+// allocates (and aliases) transient textures and records them into one command encoder.
+#[derive(Debug)]
+pub struct RenderGraph {
+    textures: Vec<RenderGraphTextureDescriptor>,
+    physical_textures: Vec<PhysicalTexture>,
+    compiled: Option<CompiledRenderGraph>,
+    profiler: Option<Profiler>,
+    gpu_timer: Option<GpuTimer>,
+}
+impl RenderGraph {
+    pub fn new() -> Self {
+        Self {
+            textures: vec![],
+            physical_textures: vec![],
+            compiled: None,
+            profiler: None,
+            gpu_timer: None,
+        }
+    }
+
+    // Time the passes on the CPU, and on the GPU when timestamp queries are supported.
+    pub fn with_profiler(self, profiler: Profiler) -> Self {
+        Self {
+            profiler: Some(profiler),
+            ..self
+        }
+    }
+
+    pub fn create_texture(
+        &mut self,
+        descriptor: RenderGraphTextureDescriptor,
+    ) -> RenderGraphTexture {
+        self.textures.push(descriptor);
+        RenderGraphTexture::transient(self.textures.len() - 1)
+    }
+
+    // Pass order, culled passes and transient texture aliasing of the last executed frame.
+    pub fn dump(&self) -> String {
+        match &self.compiled {
+            Some(compiled) => compiled.to_string(),
+            None => "Render graph: not executed yet\n".to_string(),
+        }
+    }
+
+    fn allocate(
+        &mut self,
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        slots: &[PhysicalTextureSlot],
+    ) {
+        self.physical_textures.truncate(slots.len());
+        for (index, slot) in slots.iter().enumerate() {
+            let (width, height) = slot.size.extent(config);
+            let reusable = self
+                .physical_textures
+                .get(index)
+                .map(|texture| {
+                    texture.format == slot.format
+                        && texture.width == width
+                        && texture.height == height
+                        && texture.sample_count == slot.sample_count
+                })
+                .unwrap_or(false);
+            if reusable {
+                continue;
+            }
+            let texture = device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("render_graph_transient_texture"),
+                size: wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: slot.sample_count,
+                dimension: wgpu::TextureDimension::D2,
+                format: slot.format,
+                // Multisampled textures are only resolved, never sampled.
+                usage: if slot.sample_count > 1 {
+                    wgpu::TextureUsages::RENDER_ATTACHMENT
+                } else {
+                    wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING
+                },
+            });
+            let physical_texture = PhysicalTexture {
+                format: slot.format,
+                width,
+                height,
+                sample_count: slot.sample_count,
+                view: texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            };
+            if index < self.physical_textures.len() {
+                self.physical_textures[index] = physical_texture;
+            } else {
+                self.physical_textures.push(physical_texture);
+            }
+        }
+    }
+
+    pub fn execute<T>(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        config: &wgpu::SurfaceConfiguration,
+        surface_view: &wgpu::TextureView,
+        passes: &mut [&mut dyn RenderGraphPass<T>],
+        data: &mut T,
+    ) -> Result<(), RenderGraphError> {
+        let nodes = passes
+            .iter()
+            .map(|pass| {
+                let mut builder = RenderGraphPassBuilder::default();
+                pass.setup(&mut builder);
+                RenderGraphNode {
+                    name: pass.name().to_string(),
+                    reads: builder.reads,
+                    writes: builder.writes,
+                }
+            })
+            .collect();
+        let compiled = CompiledRenderGraph::compile(nodes, &self.textures)?;
+        let changed = self
+            .compiled
+            .as_ref()
+            .map(|last| last.pass_names() != compiled.pass_names())
+            .unwrap_or(true);
+
+        self.allocate(device, config, &compiled.slots);
+
+        let views = std::iter::once(Some(surface_view))
+            .chain(
+                compiled
+                    .aliases
+                    .iter()
+                    .map(|alias| alias.map(|slot| &self.physical_textures[slot].view)),
+            )
+            .collect();
+        let context = RenderGraphContext {
+            device,
+            queue,
+            views,
+        };
+
+        if let Some(profiler) = &self.profiler {
+            if self.gpu_timer.is_none() {
+                self.gpu_timer = GpuTimer::new(device, queue);
+            }
+            if let Some(gpu_timer) = &mut self.gpu_timer {
+                gpu_timer.collect(device, profiler);
+                gpu_timer.begin(profiler);
+            }
+        }
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Render Graph Encoder"),
+        });
+        for &i in &compiled.order {
+            let name = passes[i].name().to_string();
+            let _scope = self.profiler.as_ref().map(|profiler| profiler.scope(&name));
+            let gpu_scope = self
+                .gpu_timer
+                .as_mut()
+                .and_then(|gpu_timer| gpu_timer.begin_scope(&mut encoder));
+            passes[i].execute(&mut encoder, &context, data);
+            if let Some(gpu_timer) = &mut self.gpu_timer {
+                gpu_timer.end_scope(&mut encoder, gpu_scope, &name);
+            }
+        }
+        if let Some(gpu_timer) = &mut self.gpu_timer {
+            gpu_timer.resolve(device, &mut encoder);
+        }
+        queue.submit(std::iter::once(encoder.finish()));
+        if let Some(gpu_timer) = &mut self.gpu_timer {
+            gpu_timer.map();
+        }
+
+        self.compiled = Some(compiled);
+        if changed {
+            log::debug!("{}", self.dump());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(
+        name: &str,
+        reads: &[RenderGraphTexture],
+        writes: &[RenderGraphTexture],
+    ) -> RenderGraphNode {
+        RenderGraphNode {
+            name: name.into(),
+            reads: reads.to_vec(),
+            writes: writes.to_vec(),
+        }
+    }
+
+    fn textures(count: usize) -> Vec<RenderGraphTextureDescriptor> {
+        (0..count)
+            .map(|i| {
+                RenderGraphTextureDescriptor::new(
+                    format!("texture{}", i),
+                    wgpu::TextureFormat::Rgba16Float,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn it_should_order_readers_after_writers_and_cull_unused_passes() {
+        let surface = RenderGraphTexture::SURFACE;
+        let hdr = RenderGraphTexture::transient(0);
+        let unused = RenderGraphTexture::transient(1);
+        let compiled = CompiledRenderGraph::compile(
+            vec![
+                node("tonemap", &[hdr], &[surface]),
+                node("unused", &[], &[unused]),
+                node("scene", &[], &[hdr]),
+                node("ui", &[surface], &[surface]),
+            ],
+            &textures(2),
+        )
+        .unwrap();
+        assert_eq!(compiled.pass_names(), vec!["scene", "tonemap", "ui"]);
+        assert_eq!(compiled.culled, vec![1]);
+        assert_eq!(compiled.aliases, vec![Some(0), None]);
+    }
+
+    #[test]
+    fn it_should_alias_textures_with_disjoint_lifetimes() {
+        let surface = RenderGraphTexture::SURFACE;
+        let [a, b, c] = [0, 1, 2].map(RenderGraphTexture::transient);
+        let compiled = CompiledRenderGraph::compile(
+            vec![
+                node("a", &[], &[a]),
+                node("b", &[a], &[b]),
+                node("c", &[b], &[c]),
+                node("present", &[c], &[surface]),
+            ],
+            &textures(3),
+        )
+        .unwrap();
+        assert_eq!(compiled.aliases, vec![Some(0), Some(1), Some(0)]);
+        assert_eq!(compiled.slots.len(), 2);
+    }
+
+    #[test]
+    fn it_should_report_cycles() {
+        let surface = RenderGraphTexture::SURFACE;
+        let [a, b] = [0, 1].map(RenderGraphTexture::transient);
+        let result = CompiledRenderGraph::compile(
+            vec![node("a", &[b], &[a, surface]), node("b", &[a], &[b])],
+            &textures(2),
+        );
+        assert!(matches!(result, Err(RenderGraphError::CycleError(_))));
+    }
+}
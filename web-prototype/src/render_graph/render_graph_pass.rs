@@ -0,0 +1,56 @@
+use crate::render_graph::RenderGraphTexture;
+
+// Resources a pass declares in `RenderGraphPass::setup`.
+// A color attachment that is cleared is only written,
+// one that is loaded (e.g. an overlay drawn over the scene) is read and written.
+#[derive(Debug, Default)]
+pub struct RenderGraphPassBuilder {
+    pub(super) reads: Vec<RenderGraphTexture>,
+    pub(super) writes: Vec<RenderGraphTexture>,
+}
+impl RenderGraphPassBuilder {
+    pub fn read(&mut self, texture: RenderGraphTexture) -> &mut Self {
+        if !self.reads.contains(&texture) {
+            self.reads.push(texture);
+        }
+        self
+    }
+
+    pub fn write(&mut self, texture: RenderGraphTexture) -> &mut Self {
+        if !self.writes.contains(&texture) {
+            self.writes.push(texture);
+        }
+        self
+    }
+}
+
+// GPU objects of the frame being recorded.
+pub struct RenderGraphContext<'a> {
+    pub device: &'a wgpu::Device,
+    pub queue: &'a wgpu::Queue,
+    pub(super) views: Vec<Option<&'a wgpu::TextureView>>,
+}
+impl<'a> RenderGraphContext<'a> {
+    pub fn view(&self, texture: RenderGraphTexture) -> &'a wgpu::TextureView {
+        self.views
+            .get(texture.0)
+            .copied()
+            .flatten()
+            .unwrap_or_else(|| panic!("{:?} was not declared by the pass.", texture))
+    }
+}
+
+// A pass of the render graph.
+// `T` is the frame data of the state owning the graph, e.g. the main scene.
+pub trait RenderGraphPass<T> {
+    fn name(&self) -> &str;
+
+    fn setup(&self, builder: &mut RenderGraphPassBuilder);
+
+    fn execute(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        context: &RenderGraphContext,
+        data: &mut T,
+    );
+}
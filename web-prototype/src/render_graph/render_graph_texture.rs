@@ -0,0 +1,70 @@
+// Handle of a texture used by render graph passes.
+// The surface texture of the current frame is always `RenderGraphTexture::SURFACE`,
+// transient textures are created by `RenderGraph::create_texture`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RenderGraphTexture(pub(super) usize);
+impl RenderGraphTexture {
+    pub const SURFACE: Self = Self(0);
+
+    pub(super) fn transient(index: usize) -> Self {
+        Self(index + 1)
+    }
+
+    pub(super) fn transient_index(&self) -> Option<usize> {
+        self.0.checked_sub(1)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RenderGraphTextureSize {
+    // Same size as the surface, reallocated when the surface is resized.
+    Surface,
+    // Surface size divided by `divisor`, at least 1x1, e.g. for downsampled mip chains.
+    Scaled { divisor: u32 },
+}
+impl RenderGraphTextureSize {
+    pub(super) fn extent(&self, config: &wgpu::SurfaceConfiguration) -> (u32, u32) {
+        match *self {
+            Self::Surface => (config.width, config.height),
+            Self::Scaled { divisor } => (
+                (config.width / divisor).max(1),
+                (config.height / divisor).max(1),
+            ),
+        }
+    }
+}
+
+// Transient textures live for a single frame only.
+// Their contents are undefined before the first pass writing them,
+// and textures with the same format and size may share the same memory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderGraphTextureDescriptor {
+    pub label: String,
+    pub format: wgpu::TextureFormat,
+    pub size: RenderGraphTextureSize,
+    pub sample_count: u32,
+}
+impl RenderGraphTextureDescriptor {
+    pub fn new(label: impl Into<String>, format: wgpu::TextureFormat) -> Self {
+        Self {
+            label: label.into(),
+            format,
+            size: RenderGraphTextureSize::Surface,
+            sample_count: 1,
+        }
+    }
+
+    pub fn with_sample_count(self, sample_count: u32) -> Self {
+        Self {
+            sample_count,
+            ..self
+        }
+    }
+
+    pub fn with_divisor(self, divisor: u32) -> Self {
+        Self {
+            size: RenderGraphTextureSize::Scaled { divisor },
+            ..self
+        }
+    }
+}
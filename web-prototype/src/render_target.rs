@@ -0,0 +1,5 @@
+mod offscreen_target;
+mod render_target;
+
+pub use offscreen_target::{OffscreenFrame, OffscreenTarget};
+pub use render_target::RenderTarget;
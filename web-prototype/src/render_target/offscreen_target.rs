@@ -0,0 +1,159 @@
+use futures::FutureExt;
+use golden_image::TextureReadback;
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+
+type MapFuture = Pin<Box<dyn Future<Output = Result<(), wgpu::BufferAsyncError>> + Send>>;
+
+// A frame read back from an offscreen target, tightly packed RGBA8.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OffscreenFrame {
+    pub index: u64,
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+impl OffscreenFrame {
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), image::ImageError> {
+        image::save_buffer(
+            path,
+            &self.rgba,
+            self.width,
+            self.height,
+            image::ColorType::Rgba8,
+        )
+    }
+}
+
+struct Readback {
+    readback: TextureReadback,
+    // Frame index and map future while the buffer is in flight.
+    pending: Option<(u64, MapFuture)>,
+}
+impl std::fmt::Debug for Readback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Readback")
+            .field("readback", &self.readback)
+            .field("pending", &self.pending.as_ref().map(|(index, _)| index))
+            .finish()
+    }
+}
+
+// Render target backed by a texture instead of a window surface.
+// Every presented frame is copied into a readback buffer and mapped asynchronously,
+// so rendering does not stall on the GPU until the frames are collected.
+#[derive(Debug)]
+pub struct OffscreenTarget {
+    texture: wgpu::Texture,
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+    frame_index: u64,
+    readbacks: Vec<Readback>,
+}
+impl OffscreenTarget {
+    pub fn new(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> Self {
+        Self {
+            texture: Self::create_texture(device, config),
+            format: config.format,
+            width: config.width,
+            height: config.height,
+            frame_index: 0,
+            readbacks: vec![],
+        }
+    }
+
+    fn create_texture(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("offscreen_target_texture"),
+            size: wgpu::Extent3d {
+                width: config.width,
+                height: config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: config.format,
+            // Sampled when the frame capture blits the target to the surface.
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::COPY_SRC
+                | wgpu::TextureUsages::TEXTURE_BINDING,
+        })
+    }
+
+    pub fn configure(&mut self, device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) {
+        if (self.format, self.width, self.height) != (config.format, config.width, config.height) {
+            self.texture = Self::create_texture(device, config);
+            self.format = config.format;
+            self.width = config.width;
+            self.height = config.height;
+        }
+    }
+
+    pub fn create_view(&self) -> wgpu::TextureView {
+        self.texture
+            .create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    // Copy the rendered frame into a free readback buffer and start mapping it.
+    pub fn present(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let (width, height) = (self.width, self.height);
+        let free = self.readbacks.iter().position(|readback| {
+            readback.pending.is_none()
+                && readback.readback.width() == width
+                && readback.readback.height() == height
+        });
+        let index = match free {
+            Some(index) => index,
+            None => {
+                self.readbacks.push(Readback {
+                    readback: TextureReadback::new(device, width, height),
+                    pending: None,
+                });
+                self.readbacks.len() - 1
+            }
+        };
+        let readback = &mut self.readbacks[index];
+
+        readback.readback.copy(device, queue, &self.texture);
+        readback.pending = Some((self.frame_index, Box::pin(readback.readback.map())));
+        self.frame_index += 1;
+    }
+
+    // Collect the frames whose readback has finished, in presentation order.
+    // With `wait` the device is polled until every pending frame is available.
+    pub fn frames(&mut self, device: &wgpu::Device, wait: bool) -> Vec<OffscreenFrame> {
+        device.poll(if wait {
+            wgpu::Maintain::Wait
+        } else {
+            wgpu::Maintain::Poll
+        });
+
+        let mut frames = vec![];
+        for readback in &mut self.readbacks {
+            let ready = match &mut readback.pending {
+                Some((index, map_future)) => match map_future.now_or_never() {
+                    Some(result) => {
+                        result.unwrap_or_else(|_| panic!("Failed to map readback buffer."));
+                        Some(*index)
+                    }
+                    None => None,
+                },
+                None => None,
+            };
+            if let Some(index) = ready {
+                readback.pending = None;
+                frames.push(OffscreenFrame {
+                    index,
+                    width: readback.readback.width(),
+                    height: readback.readback.height(),
+                    rgba: readback.readback.read(),
+                });
+            }
+        }
+        frames.sort_by_key(|frame| frame.index);
+        frames
+    }
+}
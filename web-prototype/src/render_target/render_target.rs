@@ -0,0 +1,63 @@
+use gpu_context::GpuContext;
+
+use crate::render_target::{OffscreenFrame, OffscreenTarget};
+
+// Texture the states render a frame into.
+#[derive(Debug)]
+pub struct RenderTargetFrame {
+    surface_texture: Option<wgpu::SurfaceTexture>,
+    pub view: wgpu::TextureView,
+}
+
+// Where the states present their frames: the surface of the GPU context,
+// or an offscreen texture that is read back in headless mode.
+#[derive(Debug)]
+pub enum RenderTarget {
+    Surface,
+    Offscreen(OffscreenTarget),
+}
+impl RenderTarget {
+    // Apply changes of the GPU context config, the surface itself is configured by the context.
+    pub fn configure(&mut self, gpu: &GpuContext) {
+        if let Self::Offscreen(target) = self {
+            target.configure(&gpu.device, &gpu.config);
+        }
+    }
+
+    pub fn acquire(&self, gpu: &GpuContext) -> Result<RenderTargetFrame, wgpu::SurfaceError> {
+        match self {
+            Self::Surface => {
+                let output = gpu.acquire()?;
+                let view = output
+                    .texture
+                    .create_view(&wgpu::TextureViewDescriptor::default());
+                Ok(RenderTargetFrame {
+                    surface_texture: Some(output),
+                    view,
+                })
+            }
+            Self::Offscreen(target) => Ok(RenderTargetFrame {
+                surface_texture: None,
+                view: target.create_view(),
+            }),
+        }
+    }
+
+    pub fn present(&mut self, frame: RenderTargetFrame, gpu: &GpuContext) {
+        match self {
+            Self::Surface => {
+                if let Some(surface_texture) = frame.surface_texture {
+                    surface_texture.present();
+                }
+            }
+            Self::Offscreen(target) => target.present(&gpu.device, &gpu.queue),
+        }
+    }
+
+    pub fn frames(&mut self, device: &wgpu::Device, wait: bool) -> Vec<OffscreenFrame> {
+        match self {
+            Self::Surface => vec![],
+            Self::Offscreen(target) => target.frames(device, wait),
+        }
+    }
+}
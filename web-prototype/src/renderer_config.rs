@@ -0,0 +1,92 @@
+use gpu_context::GpuContextDescriptor;
+
+// Renderer settings requested by the application.
+// Unsupported values fall back to supported ones with a warning instead of failing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RendererConfig {
+    // MSAA sample count of the scene passes.
+    pub sample_count: u32,
+    // Falls back to `Fifo` when the surface does not support it.
+    pub present_mode: wgpu::PresentMode,
+    // Surface format, `None` uses the preferred format of the surface.
+    pub format: Option<wgpu::TextureFormat>,
+    // Features and limits are requested once when the device is created,
+    // changing them at runtime has no effect until the next start.
+    pub features: wgpu::Features,
+    pub limits: wgpu::Limits,
+}
+impl Default for RendererConfig {
+    fn default() -> Self {
+        Self {
+            sample_count: 4,
+            present_mode: wgpu::PresentMode::Fifo,
+            format: None,
+            // Optional, the profiler times the passes on the GPU when the adapter supports it.
+            features: wgpu::Features::TIMESTAMP_QUERY,
+            limits: wgpu::Limits::default(),
+        }
+    }
+}
+impl RendererConfig {
+    // wgpu can only resolve 4x multisampled attachments.
+    pub const SAMPLE_COUNTS: [u32; 2] = [1, 4];
+    pub const PRESENT_MODES: [wgpu::PresentMode; 3] = [
+        wgpu::PresentMode::Fifo,
+        wgpu::PresentMode::Mailbox,
+        wgpu::PresentMode::Immediate,
+    ];
+
+    // Clamp the sample count to the highest supported one not above the requested one.
+    pub fn validate(self) -> Self {
+        let sample_count = Self::SAMPLE_COUNTS
+            .iter()
+            .copied()
+            .filter(|&count| count <= self.sample_count)
+            .max()
+            .unwrap_or(1);
+        if sample_count != self.sample_count {
+            log::warn!(
+                "MSAA sample count {} is not supported, using {}.",
+                self.sample_count,
+                sample_count
+            );
+        }
+        Self {
+            sample_count,
+            ..self
+        }
+    }
+
+    // Adapter, device and surface settings of the GPU context.
+    pub fn descriptor(&self) -> GpuContextDescriptor {
+        GpuContextDescriptor {
+            features: self.features,
+            limits: self.limits.clone(),
+            present_mode: self.present_mode,
+            format: self.format,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_fall_back_to_supported_sample_count() {
+        let sample_count = |sample_count| {
+            RendererConfig {
+                sample_count,
+                ..Default::default()
+            }
+            .validate()
+            .sample_count
+        };
+        assert_eq!(sample_count(0), 1);
+        assert_eq!(sample_count(1), 1);
+        assert_eq!(sample_count(2), 1);
+        assert_eq!(sample_count(4), 4);
+        assert_eq!(sample_count(8), 4);
+    }
+}
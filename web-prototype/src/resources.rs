@@ -1,18 +1,29 @@
+mod material;
 mod mesh;
+mod network_simulator;
+mod resource_handle;
+mod resource_info;
 mod resources;
+mod resources_cache;
 mod resources_catalog;
 mod resources_loader;
 mod texture;
 
-pub use mesh::Mesh;
+pub use material::Material;
+pub use mesh::{Mesh, MeshVertex};
+pub use network_simulator::{NetworkSimulator, NetworkSimulatorConfig};
+pub use resource_handle::ResourceHandle;
+pub use resource_info::{format_bytes, ResourceInfo, ResourceKind};
 pub use resources::Resources;
-pub use resources_catalog::ResourcesCatalog;
+pub use resources_cache::{ResourcesCache, ResourcesCacheConfig};
 use resources_catalog::ResourcesCatalogItem;
+pub use resources_catalog::{ResourcePriority, ResourcesCatalog};
 pub use resources_loader::ResourcesLoader;
-pub use texture::Texture;
+pub use texture::{Texture, TextureChannel};
 
 pub fn get_catalog() -> ResourcesCatalog {
     ResourcesCatalog::new(&[
+        ResourcesCatalogItem::scene(hash("room_scene"), "assets/room.scene.ron"),
         ResourcesCatalogItem::mesh(hash("room"), "assets/room.glb"),
         ResourcesCatalogItem::texture(
             hash("room_tex_base_color"),
@@ -21,13 +32,28 @@ pub fn get_catalog() -> ResourcesCatalog {
         ResourcesCatalogItem::texture(
             hash("room_tex_metallic"),
             "assets/room_Material_Metallic.png",
-        ),
-        ResourcesCatalogItem::texture(hash("room_tex_normal"), "assets/room_Material_Normal.png"),
+        )
+        .with_priority(ResourcePriority::Normal)
+        .with_placeholder([0, 0, 0, 255]),
+        ResourcesCatalogItem::texture(hash("room_tex_normal"), "assets/room_Material_Normal.png")
+            .with_priority(ResourcePriority::High)
+            .with_placeholder([128, 128, 255, 255]),
         ResourcesCatalogItem::texture(
             hash("room_tex_roughness"),
             "assets/room_Material_Roughness.png",
-        ),
+        )
+        .with_priority(ResourcePriority::Normal)
+        .with_placeholder([255, 255, 255, 255]),
+        ResourcesCatalogItem::texture(hash("lut_warm"), "assets/lut_warm.png")
+            .with_priority(ResourcePriority::Low),
+        ResourcesCatalogItem::texture(hash("lut_cool"), "assets/lut_cool.png")
+            .with_priority(ResourcePriority::Low),
     ])
+    .with_materials(&[Material::new(hash("room_material"))
+        .with_base_color_texture(hash("room_tex_base_color"))
+        .with_metallic_texture(hash("room_tex_metallic"))
+        .with_roughness_texture(hash("room_tex_roughness"))
+        .with_normal_texture(hash("room_tex_normal"))])
 }
 
 pub const fn hash(name: &str) -> u64 {
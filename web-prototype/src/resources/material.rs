@@ -0,0 +1,182 @@
+use std::path::Path;
+
+use crate::resources::hash;
+
+// Metallic-roughness material matching the glTF 2.0 core material model.
+// Textures are referenced by their resource hash. Metallic and roughness can come from
+// separate grayscale maps or from one packed glTF map, so each keeps its own channel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Material {
+    pub hash: u64,
+    pub base_color_factor: vek::Rgba<f32>,
+    pub metallic_factor: f32,
+    pub roughness_factor: f32,
+    pub normal_scale: f32,
+    pub base_color_texture: Option<u64>,
+    pub metallic_texture: Option<u64>,
+    pub metallic_channel: u32,
+    pub roughness_texture: Option<u64>,
+    pub roughness_channel: u32,
+    pub normal_texture: Option<u64>,
+}
+impl Material {
+    pub const DEFAULT_HASH: u64 = hash("default_material");
+
+    pub fn new(hash: u64) -> Self {
+        Self {
+            hash,
+            base_color_factor: vek::Rgba::new(1.0, 1.0, 1.0, 1.0),
+            metallic_factor: 1.0,
+            roughness_factor: 1.0,
+            normal_scale: 1.0,
+            base_color_texture: None,
+            metallic_texture: None,
+            metallic_channel: 0,
+            roughness_texture: None,
+            roughness_channel: 0,
+            normal_texture: None,
+        }
+    }
+
+    pub fn with_base_color_texture(self, texture: u64) -> Self {
+        Self {
+            base_color_texture: Some(texture),
+            ..self
+        }
+    }
+
+    pub fn with_metallic_texture(self, texture: u64) -> Self {
+        Self {
+            metallic_texture: Some(texture),
+            metallic_channel: 0,
+            ..self
+        }
+    }
+
+    pub fn with_roughness_texture(self, texture: u64) -> Self {
+        Self {
+            roughness_texture: Some(texture),
+            roughness_channel: 0,
+            ..self
+        }
+    }
+
+    pub fn with_normal_texture(self, texture: u64) -> Self {
+        Self {
+            normal_texture: Some(texture),
+            ..self
+        }
+    }
+
+    // Hash of a material embedded in a glTF file.
+    pub fn gltf_hash(path: &Path, index: usize) -> u64 {
+        hash(&format!("{}#material{}", path.display(), index))
+    }
+
+    // Hash of a texture embedded in a glTF file.
+    pub fn gltf_texture_hash(path: &Path, index: usize) -> u64 {
+        hash(&format!("{}#texture{}", path.display(), index))
+    }
+
+    pub fn from_gltf(path: &Path, material: &gltf::Material) -> Self {
+        let hash = match material.index() {
+            Some(index) => Self::gltf_hash(path, index),
+            None => Self::DEFAULT_HASH,
+        };
+        let pbr = material.pbr_metallic_roughness();
+        let [r, g, b, a] = pbr.base_color_factor();
+        let texture_hash = |texture: gltf::Texture| Self::gltf_texture_hash(path, texture.index());
+        let metallic_roughness_texture = pbr
+            .metallic_roughness_texture()
+            .map(|info| texture_hash(info.texture()));
+
+        Self {
+            hash,
+            base_color_factor: vek::Rgba::new(r, g, b, a),
+            metallic_factor: pbr.metallic_factor(),
+            roughness_factor: pbr.roughness_factor(),
+            normal_scale: material
+                .normal_texture()
+                .map(|normal| normal.scale())
+                .unwrap_or(1.0),
+            base_color_texture: pbr
+                .base_color_texture()
+                .map(|info| texture_hash(info.texture())),
+            // glTF packs metalness in the blue channel and roughness in the green channel.
+            metallic_texture: metallic_roughness_texture,
+            metallic_channel: 2,
+            roughness_texture: metallic_roughness_texture,
+            roughness_channel: 1,
+            normal_texture: material
+                .normal_texture()
+                .map(|normal| texture_hash(normal.texture())),
+        }
+    }
+
+    // Non-metallic, mid-roughness material used when a mesh references a missing material.
+    pub fn fallback() -> Self {
+        Self {
+            metallic_factor: 0.0,
+            roughness_factor: 0.5,
+            ..Self::new(Self::DEFAULT_HASH)
+        }
+    }
+
+    pub fn textures(&self) -> impl Iterator<Item = u64> {
+        [
+            self.base_color_texture,
+            self.metallic_texture,
+            self.roughness_texture,
+            self.normal_texture,
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_map_gltf_material_onto_metallic_roughness() {
+        let json = r#"{
+            "asset": { "version": "2.0" },
+            "images": [{ "uri": "a.png" }, { "uri": "b.png" }],
+            "textures": [{ "source": 0 }, { "source": 1 }],
+            "materials": [{
+                "pbrMetallicRoughness": {
+                    "baseColorFactor": [0.5, 0.5, 0.5, 1.0],
+                    "baseColorTexture": { "index": 0 },
+                    "metallicFactor": 0.25,
+                    "metallicRoughnessTexture": { "index": 1 }
+                },
+                "normalTexture": { "index": 1, "scale": 0.5 }
+            }]
+        }"#;
+        let gltf = gltf::Gltf::from_slice(json.as_bytes()).unwrap();
+        let path = Path::new("assets/a.gltf");
+        let material = Material::from_gltf(path, &gltf.materials().next().unwrap());
+
+        assert_eq!(material.hash, Material::gltf_hash(path, 0));
+        assert_eq!(
+            material.base_color_factor,
+            vek::Rgba::new(0.5, 0.5, 0.5, 1.0)
+        );
+        assert_eq!(material.metallic_factor, 0.25);
+        assert_eq!(material.roughness_factor, 1.0);
+        assert_eq!(material.normal_scale, 0.5);
+        assert_eq!(
+            material.base_color_texture,
+            Some(Material::gltf_texture_hash(path, 0))
+        );
+        assert_eq!(
+            material.metallic_texture,
+            Some(Material::gltf_texture_hash(path, 1))
+        );
+        assert_eq!(
+            (material.metallic_channel, material.roughness_channel),
+            (2, 1)
+        );
+    }
+}
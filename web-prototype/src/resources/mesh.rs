@@ -1,4 +1,282 @@
+use std::path::Path;
+
+use crate::resources::Material;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct MeshVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub uv: [f32; 2],
+    pub tangent: [f32; 4],
+}
+
+#[derive(Debug)]
+pub struct MeshPrimitive {
+    pub vertices: Vec<MeshVertex>,
+    pub indices: Vec<u32>,
+    pub material: u64,
+}
+
+impl MeshPrimitive {
+    // Axis aligned bounds of the vertices, `None` without vertices.
+    pub fn bounds(&self) -> Option<vek::Aabb<f32>> {
+        let mut positions = self
+            .vertices
+            .iter()
+            .map(|vertex| vek::Vec3::from(vertex.position));
+        let first = positions.next()?;
+        Some(
+            positions.fold(vek::Aabb::new_empty(first), |bounds, position| {
+                bounds.expanded_to_contain_point(position)
+            }),
+        )
+    }
+}
+
 #[derive(Debug)]
 pub struct Mesh {
     pub hash: u64,
+    // Primitives of every mesh node in the default scene, in world space.
+    pub primitives: Vec<MeshPrimitive>,
+}
+impl Mesh {
+    pub fn vertex_count(&self) -> usize {
+        self.primitives
+            .iter()
+            .map(|primitive| primitive.vertices.len())
+            .sum()
+    }
+
+    pub fn index_count(&self) -> usize {
+        self.primitives
+            .iter()
+            .map(|primitive| primitive.indices.len())
+            .sum()
+    }
+
+    // Size of the vertex and index data.
+    pub fn memory_size(&self) -> usize {
+        self.vertex_count() * std::mem::size_of::<MeshVertex>()
+            + self.index_count() * std::mem::size_of::<u32>()
+    }
+
+    pub fn bounds(&self) -> Option<vek::Aabb<f32>> {
+        self.primitives
+            .iter()
+            .filter_map(MeshPrimitive::bounds)
+            .reduce(vek::Aabb::union)
+    }
+
+    pub(super) fn from_gltf(
+        hash: u64,
+        path: &Path,
+        bytes: &[u8],
+    ) -> Result<(Self, Vec<Material>), gltf::Error> {
+        let gltf = gltf::Gltf::from_slice(bytes)?;
+        let materials = gltf
+            .materials()
+            .map(|material| Material::from_gltf(path, &material))
+            .collect();
+
+        let mut primitives = vec![];
+        let scene = gltf.default_scene().or_else(|| gltf.scenes().next());
+        for node in scene.iter().flat_map(|scene| scene.nodes()) {
+            Self::load_node(path, &gltf, &node, vek::Mat4::identity(), &mut primitives);
+        }
+
+        Ok((Self { hash, primitives }, materials))
+    }
+
+    fn load_node(
+        path: &Path,
+        gltf: &gltf::Gltf,
+        node: &gltf::Node,
+        parent_transform: vek::Mat4<f32>,
+        primitives: &mut Vec<MeshPrimitive>,
+    ) {
+        let transform = parent_transform * vek::Mat4::from_col_arrays(node.transform().matrix());
+        let normal_transform = transform.inverted().transposed();
+
+        if let Some(mesh) = node.mesh() {
+            for primitive in mesh.primitives() {
+                if primitive.mode() != gltf::mesh::Mode::Triangles {
+                    log::warn!("{}: skip non triangle list primitive.", path.display());
+                    continue;
+                }
+
+                let reader = primitive.reader(|buffer| match buffer.source() {
+                    gltf::buffer::Source::Bin => gltf.blob.as_deref(),
+                    gltf::buffer::Source::Uri(_) => None,
+                });
+                let positions = match reader.read_positions() {
+                    Some(positions) => positions.collect::<Vec<_>>(),
+                    None => {
+                        // Draco compressed primitives do not have a buffer view to read.
+                        log::warn!(
+                            "{}: skip primitive without readable positions \
+                            (compressed or external buffers are not supported).",
+                            path.display()
+                        );
+                        continue;
+                    }
+                };
+                let normals = reader
+                    .read_normals()
+                    .map(|normals| normals.collect::<Vec<_>>())
+                    .unwrap_or_else(|| vec![[0.0, 0.0, 1.0]; positions.len()]);
+                let uvs = reader
+                    .read_tex_coords(0)
+                    .map(|uvs| uvs.into_f32().collect::<Vec<_>>())
+                    .unwrap_or_else(|| vec![[0.0, 0.0]; positions.len()]);
+                let indices = reader
+                    .read_indices()
+                    .map(|indices| indices.into_u32().collect::<Vec<_>>())
+                    .unwrap_or_else(|| (0..positions.len() as u32).collect());
+
+                let mut vertices = positions
+                    .iter()
+                    .zip(normals.iter())
+                    .zip(uvs.iter())
+                    .map(|((position, normal), uv)| MeshVertex {
+                        position: transform.mul_point(vek::Vec3::from(*position)).into_array(),
+                        normal: normal_transform
+                            .mul_direction(vek::Vec3::from(*normal))
+                            .normalized()
+                            .into_array(),
+                        uv: *uv,
+                        tangent: [1.0, 0.0, 0.0, 1.0],
+                    })
+                    .collect::<Vec<_>>();
+                match reader.read_tangents() {
+                    Some(tangents) => {
+                        for (vertex, tangent) in vertices.iter_mut().zip(tangents) {
+                            let xyz = transform
+                                .mul_direction(vek::Vec3::new(tangent[0], tangent[1], tangent[2]))
+                                .normalized();
+                            vertex.tangent = [xyz.x, xyz.y, xyz.z, tangent[3]];
+                        }
+                    }
+                    None => compute_tangents(&mut vertices, &indices),
+                }
+
+                let material = match primitive.material().index() {
+                    Some(index) => Material::gltf_hash(path, index),
+                    None => Material::DEFAULT_HASH,
+                };
+                primitives.push(MeshPrimitive {
+                    vertices,
+                    indices,
+                    material,
+                });
+            }
+        }
+
+        for child in node.children() {
+            Self::load_node(path, gltf, &child, transform, primitives);
+        }
+    }
+}
+
+// Per vertex tangents accumulated from the uv derivatives of the adjacent triangles.
+fn compute_tangents(vertices: &mut [MeshVertex], indices: &[u32]) {
+    let mut tangents = vec![vek::Vec3::<f32>::zero(); vertices.len()];
+    let mut bitangents = vec![vek::Vec3::<f32>::zero(); vertices.len()];
+    for triangle in indices.chunks_exact(3) {
+        let [i0, i1, i2] = [
+            triangle[0] as usize,
+            triangle[1] as usize,
+            triangle[2] as usize,
+        ];
+        let p0 = vek::Vec3::<f32>::from(vertices[i0].position);
+        let e1 = vek::Vec3::<f32>::from(vertices[i1].position) - p0;
+        let e2 = vek::Vec3::<f32>::from(vertices[i2].position) - p0;
+        let uv0 = vek::Vec2::<f32>::from(vertices[i0].uv);
+        let duv1 = vek::Vec2::<f32>::from(vertices[i1].uv) - uv0;
+        let duv2 = vek::Vec2::<f32>::from(vertices[i2].uv) - uv0;
+        let det: f32 = duv1.x * duv2.y - duv2.x * duv1.y;
+        if det.abs() < f32::EPSILON {
+            continue;
+        }
+        let tangent = (e1 * duv2.y - e2 * duv1.y) / det;
+        let bitangent = (e2 * duv1.x - e1 * duv2.x) / det;
+        for i in [i0, i1, i2] {
+            tangents[i] += tangent;
+            bitangents[i] += bitangent;
+        }
+    }
+
+    for ((vertex, tangent), bitangent) in vertices.iter_mut().zip(tangents).zip(bitangents) {
+        let normal = vek::Vec3::from(vertex.normal);
+        // Gram-Schmidt orthogonalize against the normal.
+        let tangent = tangent - normal * normal.dot(tangent);
+        if tangent.magnitude_squared() < f32::EPSILON {
+            continue;
+        }
+        let tangent = tangent.normalized();
+        let handedness = if normal.cross(tangent).dot(bitangent) < 0.0 {
+            -1.0
+        } else {
+            1.0
+        };
+        vertex.tangent = [tangent.x, tangent.y, tangent.z, handedness];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vertex(position: [f32; 3], uv: [f32; 2]) -> MeshVertex {
+        MeshVertex {
+            position,
+            normal: [0.0, 0.0, 1.0],
+            uv,
+            tangent: [0.0; 4],
+        }
+    }
+
+    #[test]
+    fn it_should_compute_tangent_along_u_direction() {
+        let mut vertices = vec![
+            vertex([0.0, 0.0, 0.0], [0.0, 0.0]),
+            vertex([1.0, 0.0, 0.0], [1.0, 0.0]),
+            vertex([0.0, 1.0, 0.0], [0.0, 1.0]),
+        ];
+        compute_tangents(&mut vertices, &[0, 1, 2]);
+        for vertex in vertices {
+            assert_eq!(vertex.tangent, [1.0, 0.0, 0.0, 1.0]);
+        }
+    }
+
+    #[test]
+    fn it_should_compute_bounds_of_every_primitive() {
+        let primitive = |positions: &[[f32; 3]]| MeshPrimitive {
+            vertices: positions
+                .iter()
+                .map(|position| vertex(*position, [0.0, 0.0]))
+                .collect(),
+            indices: vec![0, 1, 2],
+            material: Material::DEFAULT_HASH,
+        };
+        let mesh = Mesh {
+            hash: 0,
+            primitives: vec![
+                primitive(&[[0.0, 0.0, 0.0], [1.0, 2.0, 0.0], [0.5, -1.0, 0.0]]),
+                primitive(&[[-3.0, 0.0, 1.0], [0.0, 0.0, 4.0], [0.0, 0.0, 2.0]]),
+            ],
+        };
+        let bounds = mesh.bounds().unwrap();
+        assert_eq!(bounds.min, vek::Vec3::new(-3.0, -1.0, 0.0));
+        assert_eq!(bounds.max, vek::Vec3::new(1.0, 2.0, 4.0));
+        assert_eq!((mesh.vertex_count(), mesh.index_count()), (6, 6));
+        assert_eq!(
+            Mesh {
+                hash: 0,
+                primitives: vec![]
+            }
+            .bounds(),
+            None
+        );
+    }
 }
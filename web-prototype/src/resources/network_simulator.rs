@@ -0,0 +1,138 @@
+use instant::Duration;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use thiserror::Error;
+
+use crate::runtime::Runtime;
+
+#[derive(Debug, Error)]
+pub enum NetworkSimulatorError {
+    #[error("Simulated load failure of resource {hash:016x} (attempt {attempt})")]
+    SimulatedFailure { hash: u64, attempt: u32 },
+}
+
+#[derive(Debug, Clone)]
+pub struct NetworkSimulatorConfig {
+    pub seed: u64,
+    pub latency: Duration,
+    pub jitter: Duration,
+    // Bytes per second. `None` means unlimited bandwidth.
+    pub bandwidth: Option<u64>,
+    pub failure_rate: f64,
+    pub max_retries: u32,
+}
+impl Default for NetworkSimulatorConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            latency: Duration::from_millis(100),
+            jitter: Duration::from_millis(400),
+            bandwidth: Some(4 * 1024 * 1024),
+            failure_rate: 0.0,
+            max_retries: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkSimulatorSample {
+    pub delay: Duration,
+    pub failed: bool,
+}
+
+// Opt-in latency, bandwidth, jitter and failure injection for resource loading.
+// Every sample is derived from the seed, the resource hash and the attempt number,
+// so the result does not depend on the order in which load tasks are polled.
+#[derive(Debug, Clone)]
+pub struct NetworkSimulator {
+    config: NetworkSimulatorConfig,
+}
+impl NetworkSimulator {
+    pub fn new(config: NetworkSimulatorConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn max_retries(&self) -> u32 {
+        self.config.max_retries
+    }
+
+    pub fn sample(&self, hash: u64, attempt: u32, size: usize) -> NetworkSimulatorSample {
+        let mut rng = StdRng::seed_from_u64(
+            self.config.seed ^ hash ^ (attempt as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15),
+        );
+
+        let jitter = self.config.jitter.mul_f64(rng.gen_range(0.0..1.0));
+        let transfer = match self.config.bandwidth {
+            Some(bandwidth) if bandwidth > 0 => {
+                Duration::from_secs_f64(size as f64 / bandwidth as f64)
+            }
+            _ => Duration::ZERO,
+        };
+        let failed = rng.gen_bool(self.config.failure_rate.clamp(0.0, 1.0));
+
+        NetworkSimulatorSample {
+            delay: self.config.latency + jitter + transfer,
+            failed,
+        }
+    }
+
+    pub async fn simulate(
+        &self,
+        runtime: &Runtime,
+        hash: u64,
+        attempt: u32,
+        size: usize,
+    ) -> Result<(), NetworkSimulatorError> {
+        let sample = self.sample(hash, attempt, size);
+        runtime.delay(sample.delay).await;
+        if sample.failed {
+            Err(NetworkSimulatorError::SimulatedFailure { hash, attempt })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::hash;
+
+    fn simulator(failure_rate: f64) -> NetworkSimulator {
+        NetworkSimulator::new(NetworkSimulatorConfig {
+            seed: 42,
+            failure_rate,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn it_should_return_same_sample_for_same_seed() {
+        let a = simulator(0.5);
+        let b = simulator(0.5);
+        for attempt in 0..8 {
+            assert_eq!(
+                a.sample(hash("room"), attempt, 1024),
+                b.sample(hash("room"), attempt, 1024)
+            );
+        }
+    }
+
+    #[test]
+    fn it_should_add_transfer_time_by_bandwidth() {
+        let simulator = NetworkSimulator::new(NetworkSimulatorConfig {
+            latency: Duration::from_millis(10),
+            jitter: Duration::ZERO,
+            bandwidth: Some(1000),
+            ..Default::default()
+        });
+        let sample = simulator.sample(hash("room"), 0, 500);
+        assert_eq!(sample.delay, Duration::from_millis(510));
+    }
+
+    #[test]
+    fn it_should_fail_by_failure_rate() {
+        assert!((0..8).all(|attempt| !simulator(0.0).sample(hash("room"), attempt, 0).failed));
+        assert!((0..8).all(|attempt| simulator(1.0).sample(hash("room"), attempt, 0).failed));
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use crate::resources::resources_loader::ResourceLoaderError;
+
+// Set of loaded and failed resource hashes shared between the loader task and the handles.
+#[derive(Debug, Default)]
+pub(super) struct LoadedResources {
+    loaded: HashSet<u64>,
+    failures: HashMap<u64, ResourceLoaderError>,
+    // One waker per pending handle, keyed by resource hash and handle id.
+    wakers: HashMap<u64, HashMap<usize, Waker>>,
+    next_handle_id: usize,
+}
+impl LoadedResources {
+    pub(super) fn contains(&self, hash: u64) -> bool {
+        self.loaded.contains(&hash)
+    }
+
+    pub(super) fn failure(&self, hash: u64) -> Option<&ResourceLoaderError> {
+        self.failures.get(&hash)
+    }
+
+    pub(super) fn insert(&mut self, hash: u64) {
+        self.loaded.insert(hash);
+        self.wake(hash);
+    }
+
+    pub(super) fn fail(&mut self, hash: u64, e: ResourceLoaderError) {
+        self.failures.insert(hash, e);
+        self.wake(hash);
+    }
+
+    fn wake(&mut self, hash: u64) {
+        for (_, waker) in self.wakers.remove(&hash).unwrap_or_default() {
+            waker.wake();
+        }
+    }
+
+    fn next_handle_id(&mut self) -> usize {
+        self.next_handle_id += 1;
+        self.next_handle_id
+    }
+}
+
+// Future which resolves once the resource of the hash is loaded, or with the error it failed with.
+// The loaded resource is moved into `Resources` on the next `ResourcesLoader::stream_into`.
+#[derive(Debug)]
+pub struct ResourceHandle {
+    hash: u64,
+    id: usize,
+    loaded: Arc<Mutex<LoadedResources>>,
+}
+impl ResourceHandle {
+    pub(super) fn new(hash: u64, loaded: Arc<Mutex<LoadedResources>>) -> Self {
+        let id = loaded.lock().unwrap().next_handle_id();
+        Self { hash, id, loaded }
+    }
+
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        self.loaded.lock().unwrap().contains(self.hash)
+    }
+}
+// Every clone gets its own id so it keeps its own waker.
+impl Clone for ResourceHandle {
+    fn clone(&self) -> Self {
+        Self::new(self.hash, Arc::clone(&self.loaded))
+    }
+}
+impl Drop for ResourceHandle {
+    fn drop(&mut self) {
+        let mut loaded = self.loaded.lock().unwrap();
+        if let Some(wakers) = loaded.wakers.get_mut(&self.hash) {
+            wakers.remove(&self.id);
+        }
+    }
+}
+impl Future for ResourceHandle {
+    type Output = Result<u64, ResourceLoaderError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut loaded = self.loaded.lock().unwrap();
+        if loaded.contains(self.hash) {
+            return Poll::Ready(Ok(self.hash));
+        }
+        if let Some(e) = loaded.failure(self.hash) {
+            return Poll::Ready(Err(e.clone()));
+        }
+        let waker = loaded
+            .wakers
+            .entry(self.hash)
+            .or_default()
+            .entry(self.id)
+            .or_insert_with(|| cx.waker().clone());
+        if !waker.will_wake(cx.waker()) {
+            *waker = cx.waker().clone();
+        }
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::hash;
+    use futures::FutureExt;
+
+    #[test]
+    fn it_should_resolve_after_insert() {
+        let loaded = Arc::new(Mutex::new(LoadedResources::default()));
+        let mut handle = ResourceHandle::new(hash("room"), Arc::clone(&loaded));
+
+        assert!((&mut handle).now_or_never().is_none());
+        loaded.lock().unwrap().insert(hash("room"));
+        assert_eq!(handle.now_or_never().unwrap().unwrap(), hash("room"));
+    }
+
+    #[test]
+    fn it_should_resolve_with_the_error_after_fail() {
+        let loaded = Arc::new(Mutex::new(LoadedResources::default()));
+        let mut handle = ResourceHandle::new(hash("room"), Arc::clone(&loaded));
+
+        assert!((&mut handle).now_or_never().is_none());
+        loaded.lock().unwrap().fail(
+            hash("room"),
+            ResourceLoaderError::LoadError("assets/room.glb".to_string(), "404".to_string()),
+        );
+        assert!(matches!(
+            handle.now_or_never(),
+            Some(Err(ResourceLoaderError::LoadError(_, _)))
+        ));
+    }
+
+    #[test]
+    fn it_should_keep_one_waker_per_handle() {
+        let loaded = Arc::new(Mutex::new(LoadedResources::default()));
+        let mut handle = ResourceHandle::new(hash("room"), Arc::clone(&loaded));
+        let mut other = handle.clone();
+
+        for _ in 0..3 {
+            assert!((&mut handle).now_or_never().is_none());
+            assert!((&mut other).now_or_never().is_none());
+        }
+        assert_eq!(loaded.lock().unwrap().wakers[&hash("room")].len(), 2);
+        drop(other);
+        assert_eq!(loaded.lock().unwrap().wakers[&hash("room")].len(), 1);
+    }
+}
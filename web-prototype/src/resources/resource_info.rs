@@ -0,0 +1,96 @@
+use instant::Duration;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceKind {
+    Mesh {
+        vertex_count: usize,
+        index_count: usize,
+    },
+    Material,
+    // `placeholder` while the texture is still streaming.
+    Texture {
+        width: u32,
+        height: u32,
+        placeholder: bool,
+    },
+    Scene {
+        entity_count: usize,
+    },
+}
+impl ResourceKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Mesh { .. } => "Mesh",
+            Self::Material => "Material",
+            Self::Texture { .. } => "Texture",
+            Self::Scene { .. } => "Scene",
+        }
+    }
+
+    pub fn size_label(&self) -> String {
+        match self {
+            Self::Mesh {
+                vertex_count,
+                index_count,
+            } => format!("{} vertices, {} indices", vertex_count, index_count),
+            Self::Material => "-".to_string(),
+            Self::Texture {
+                width,
+                height,
+                placeholder,
+            } => {
+                let size = format!("{}x{}", width, height);
+                if *placeholder {
+                    size + " (placeholder)"
+                } else {
+                    size
+                }
+            }
+            Self::Scene { entity_count } => format!("{} entities", entity_count),
+        }
+    }
+}
+
+// Summary of an entry in `Resources` for the inspector.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceInfo {
+    pub hash: u64,
+    pub kind: ResourceKind,
+    // Catalog path, materials and embedded resources do not have one.
+    pub path: Option<PathBuf>,
+    pub memory_size: usize,
+    pub load_time: Option<Duration>,
+}
+impl ResourceInfo {
+    pub fn name(&self) -> String {
+        match &self.path {
+            Some(path) => path.display().to_string(),
+            None => format!("{:016x}", self.hash),
+        }
+    }
+}
+
+pub fn format_bytes(bytes: usize) -> String {
+    const KIB: f64 = 1024.0;
+    let bytes = bytes as f64;
+    if bytes >= KIB * KIB {
+        format!("{:.1} MiB", bytes / (KIB * KIB))
+    } else if bytes >= KIB {
+        format!("{:.1} KiB", bytes / KIB)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(4 * 1024 * 1024), "4.0 MiB");
+    }
+}
@@ -1,15 +1,71 @@
+use instant::Duration;
 use std::collections::HashMap;
 
-use crate::resources::{Mesh, Texture};
+use crate::resources::{Material, Mesh, ResourceInfo, ResourceKind, ResourcesCatalog, Texture};
+use crate::scene::{EntityDescription, SceneDescription};
 
 #[derive(Debug)]
 pub struct Resources {
     meshes: HashMap<u64, Mesh>,
+    materials: HashMap<u64, Material>,
+    fallback_material: Material,
     textures: HashMap<u64, Texture>,
+    placeholder_textures: HashMap<u64, Texture>,
+    scenes: HashMap<u64, SceneDescription>,
+    load_times: HashMap<u64, Duration>,
 }
 impl Resources {
-    pub(super) fn new(meshes: HashMap<u64, Mesh>, textures: HashMap<u64, Texture>) -> Self {
-        Self { meshes, textures }
+    pub(super) fn new(
+        meshes: HashMap<u64, Mesh>,
+        materials: HashMap<u64, Material>,
+        textures: HashMap<u64, Texture>,
+        placeholder_textures: HashMap<u64, Texture>,
+        scenes: HashMap<u64, SceneDescription>,
+    ) -> Self {
+        Self {
+            meshes,
+            materials,
+            fallback_material: Material::fallback(),
+            textures,
+            placeholder_textures,
+            scenes,
+            load_times: HashMap::new(),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn from_textures(textures: Vec<Texture>) -> Self {
+        Self::new(
+            HashMap::new(),
+            HashMap::new(),
+            textures
+                .into_iter()
+                .map(|texture| (texture.hash, texture))
+                .collect(),
+            HashMap::new(),
+            HashMap::new(),
+        )
+    }
+
+    pub(super) fn insert_mesh(&mut self, mesh: Mesh) {
+        self.meshes.insert(mesh.hash, mesh);
+    }
+
+    pub(super) fn insert_material(&mut self, material: Material) {
+        self.materials.insert(material.hash, material);
+    }
+
+    pub(super) fn insert_texture(&mut self, texture: Texture) {
+        self.placeholder_textures.remove(&texture.hash);
+        self.textures.insert(texture.hash, texture);
+    }
+
+    pub(super) fn insert_scene(&mut self, hash: u64, scene: SceneDescription) {
+        self.scenes.insert(hash, scene);
+    }
+
+    pub(super) fn insert_load_time(&mut self, hash: u64, load_time: Duration) {
+        self.load_times.insert(hash, load_time);
     }
 
     pub fn mesh(&self, hash: u64) -> &Mesh {
@@ -18,9 +74,128 @@ impl Resources {
             .unwrap_or_else(|| panic!("no such hash resource."))
     }
 
+    pub fn try_mesh(&self, hash: u64) -> Option<&Mesh> {
+        self.meshes.get(&hash)
+    }
+
+    // Returns the fallback material for unknown hashes.
+    pub fn material(&self, hash: u64) -> &Material {
+        self.materials.get(&hash).unwrap_or(&self.fallback_material)
+    }
+
+    pub fn try_texture(&self, hash: u64) -> Option<&Texture> {
+        self.textures
+            .get(&hash)
+            .or_else(|| self.placeholder_textures.get(&hash))
+    }
+
+    // Returns the placeholder texture while the texture is still streaming.
     pub fn texture(&self, hash: u64) -> &Texture {
         self.textures
             .get(&hash)
+            .or_else(|| self.placeholder_textures.get(&hash))
             .unwrap_or_else(|| panic!("no such hash resource."))
     }
+
+    pub fn is_texture_loaded(&self, hash: u64) -> bool {
+        self.textures.contains_key(&hash)
+    }
+
+    pub fn try_scene(&self, hash: u64) -> Option<&SceneDescription> {
+        self.scenes.get(&hash)
+    }
+
+    // Every mesh, material, texture and scene with its catalog path, sorted by kind and name.
+    pub fn infos(&self, catalog: &ResourcesCatalog) -> Vec<ResourceInfo> {
+        let info = |hash: u64, kind: ResourceKind, memory_size: usize| ResourceInfo {
+            hash,
+            kind,
+            path: catalog.path(hash).map(|path| path.to_path_buf()),
+            memory_size,
+            load_time: self.load_times.get(&hash).copied(),
+        };
+        let meshes = self.meshes.values().map(|mesh| {
+            let kind = ResourceKind::Mesh {
+                vertex_count: mesh.vertex_count(),
+                index_count: mesh.index_count(),
+            };
+            info(mesh.hash, kind, mesh.memory_size())
+        });
+        let materials = self.materials.values().map(|material| {
+            info(
+                material.hash,
+                ResourceKind::Material,
+                std::mem::size_of::<Material>(),
+            )
+        });
+        let textures = self
+            .textures
+            .values()
+            .map(|texture| (texture, false))
+            .chain(
+                self.placeholder_textures
+                    .values()
+                    .map(|texture| (texture, true)),
+            )
+            .map(|(texture, placeholder)| {
+                let kind = ResourceKind::Texture {
+                    width: texture.width,
+                    height: texture.height,
+                    placeholder,
+                };
+                info(texture.hash, kind, texture.memory_size())
+            });
+
+        let scenes = self.scenes.iter().map(|(hash, scene)| {
+            let entity_count = scene.entity_count();
+            let kind = ResourceKind::Scene { entity_count };
+            info(
+                *hash,
+                kind,
+                entity_count * std::mem::size_of::<EntityDescription>(),
+            )
+        });
+
+        let mut infos = meshes
+            .chain(materials)
+            .chain(textures)
+            .chain(scenes)
+            .collect::<Vec<_>>();
+        infos.sort_by_key(|info| (info.kind.label(), info.name()));
+        infos
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::{get_catalog, hash};
+
+    #[test]
+    fn it_should_list_textures_with_catalog_paths() {
+        let mut resources = Resources::from_textures(vec![Texture {
+            hash: hash("room_tex_base_color"),
+            rgba: vec![255; 16],
+            width: 2,
+            height: 2,
+        }]);
+        resources.insert_load_time(hash("room_tex_base_color"), Duration::from_millis(20));
+        resources.insert_material(Material::new(hash("room_material")));
+
+        let infos = resources.infos(&get_catalog());
+        assert_eq!(infos.len(), 2);
+        assert_eq!(infos[0].kind, ResourceKind::Material);
+        assert_eq!(infos[0].path, None);
+        assert_eq!(
+            infos[1].kind,
+            ResourceKind::Texture {
+                width: 2,
+                height: 2,
+                placeholder: false
+            }
+        );
+        assert_eq!(infos[1].name(), "assets/room_Material_BaseColor.png");
+        assert_eq!(infos[1].memory_size, 16);
+        assert_eq!(infos[1].load_time, Some(Duration::from_millis(20)));
+    }
 }
@@ -0,0 +1,493 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use thiserror::Error;
+
+use crate::resources::ResourcesCatalog;
+
+const INDEX_KEY: &str = "index";
+const INDEX_HEADER: &str = "resources-cache 2";
+
+#[derive(Debug, Error)]
+pub enum ResourcesCacheError {
+    #[error("Failed to open resources cache: {0}")]
+    OpenError(String),
+    #[error("Failed to read resources cache entry: {0}")]
+    ReadError(String),
+    #[error("Failed to write resources cache entry: {0}")]
+    WriteError(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct ResourcesCacheConfig {
+    pub name: String,
+    pub version: u32,
+    pub size_budget: u64,
+}
+impl Default for ResourcesCacheConfig {
+    fn default() -> Self {
+        Self {
+            name: "web-prototype-resources".into(),
+            version: 1,
+            size_budget: 256 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ResourcesCacheEntry {
+    version: u32,
+    // Content hash of the asset the entry was made from, as in the catalog.
+    source: u64,
+    // Content hash of the stored bytes.
+    content_hash: u64,
+    size: u64,
+    last_used: u64,
+}
+
+#[derive(Debug, Default)]
+struct ResourcesCacheIndex {
+    entries: HashMap<u64, ResourcesCacheEntry>,
+    clock: u64,
+}
+impl ResourcesCacheIndex {
+    fn parse(text: &str) -> Option<Self> {
+        let mut lines = text.lines();
+        if lines.next()? != INDEX_HEADER {
+            return None;
+        }
+        let mut index = Self::default();
+        for line in lines {
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            if fields.len() != 6 {
+                return None;
+            }
+            let hash = u64::from_str_radix(fields[0], 16).ok()?;
+            let entry = ResourcesCacheEntry {
+                version: fields[1].parse().ok()?,
+                source: u64::from_str_radix(fields[2], 16).ok()?,
+                content_hash: u64::from_str_radix(fields[3], 16).ok()?,
+                size: fields[4].parse().ok()?,
+                last_used: fields[5].parse().ok()?,
+            };
+            index.clock = index.clock.max(entry.last_used);
+            index.entries.insert(hash, entry);
+        }
+        Some(index)
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = String::from(INDEX_HEADER);
+        for (hash, entry) in &self.entries {
+            text.push_str(&format!(
+                "\n{:016x} {} {:016x} {:016x} {} {}",
+                hash, entry.version, entry.source, entry.content_hash, entry.size, entry.last_used
+            ));
+        }
+        text
+    }
+
+    fn total_size(&self) -> u64 {
+        self.entries.values().map(|entry| entry.size).sum()
+    }
+}
+
+#[derive(Debug)]
+struct ResourcesCacheBackend {
+    #[cfg(not(target_arch = "wasm32"))]
+    directory: std::path::PathBuf,
+    #[cfg(target_arch = "wasm32")]
+    name: String,
+}
+impl ResourcesCacheBackend {
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn open(name: &str) -> Result<Self, ResourcesCacheError> {
+        use std::env;
+        use std::path::PathBuf;
+
+        let directory = if let Ok(cache_dir) = env::var("WEB_PROTOTYPE_CACHE_DIR") {
+            PathBuf::from(cache_dir)
+        } else if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
+            let mut cargo_dir = PathBuf::from(manifest_dir);
+            cargo_dir.push("target");
+            cargo_dir.push(name);
+            cargo_dir
+        } else {
+            env::temp_dir().join(name)
+        };
+        std::fs::create_dir_all(&directory)
+            .map_err(|e| ResourcesCacheError::OpenError(e.to_string()))?;
+        Ok(Self { directory })
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn read(&self, key: &str) -> Result<Option<Vec<u8>>, ResourcesCacheError> {
+        match std::fs::read(self.directory.join(key)) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(ResourcesCacheError::ReadError(e.to_string())),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn write(&self, key: &str, bytes: &[u8]) -> Result<(), ResourcesCacheError> {
+        std::fs::write(self.directory.join(key), bytes)
+            .map_err(|e| ResourcesCacheError::WriteError(e.to_string()))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn remove(&self, key: &str) -> Result<(), ResourcesCacheError> {
+        match std::fs::remove_file(self.directory.join(key)) {
+            Ok(_) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(ResourcesCacheError::WriteError(e.to_string())),
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    async fn open(name: &str) -> Result<Self, ResourcesCacheError> {
+        let backend = Self { name: name.into() };
+        backend
+            .cache()
+            .await
+            .map_err(ResourcesCacheError::OpenError)?;
+        Ok(backend)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    async fn cache(&self) -> Result<web_sys::Cache, String> {
+        use wasm_bindgen::JsCast;
+        use wasm_bindgen_futures::JsFuture;
+
+        let window = web_sys::window().ok_or_else(|| "Failed to load html window.".to_string())?;
+        let caches = window
+            .caches()
+            .map_err(|_| "Cache Storage is not available.".to_string())?;
+        JsFuture::from(caches.open(&self.name))
+            .await
+            .map_err(|_| "Failed to open Cache Storage.".to_string())?
+            .dyn_into()
+            .map_err(|_| "Failed to cast Cache".to_string())
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn url(key: &str) -> String {
+        format!("/__resources_cache/{}", key)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    async fn read(&self, key: &str) -> Result<Option<Vec<u8>>, ResourcesCacheError> {
+        use js_sys::Uint8Array;
+        use wasm_bindgen::JsCast;
+        use wasm_bindgen_futures::JsFuture;
+        use web_sys::Response;
+
+        let cache = self.cache().await.map_err(ResourcesCacheError::ReadError)?;
+        let value = JsFuture::from(cache.match_with_str(&Self::url(key)))
+            .await
+            .map_err(|_| ResourcesCacheError::ReadError("Failed to match entry".into()))?;
+        if value.is_undefined() {
+            return Ok(None);
+        }
+        let resp: Response = value
+            .dyn_into()
+            .map_err(|_| ResourcesCacheError::ReadError("Failed to cast Response".into()))?;
+        let buffer = JsFuture::from(
+            resp.array_buffer()
+                .map_err(|_| ResourcesCacheError::ReadError("Failed to get buffer".into()))?,
+        )
+        .await
+        .map_err(|_| ResourcesCacheError::ReadError("Failed to read buffer".into()))?;
+        Ok(Some(Uint8Array::new(&buffer).to_vec()))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    async fn write(&self, key: &str, bytes: &[u8]) -> Result<(), ResourcesCacheError> {
+        use wasm_bindgen_futures::JsFuture;
+        use web_sys::Response;
+
+        let cache = self
+            .cache()
+            .await
+            .map_err(ResourcesCacheError::WriteError)?;
+        let mut body = Vec::from(bytes);
+        let resp = Response::new_with_opt_u8_array(Some(&mut body))
+            .map_err(|_| ResourcesCacheError::WriteError("Failed to create Response".into()))?;
+        JsFuture::from(cache.put_with_str(&Self::url(key), &resp))
+            .await
+            .map_err(|_| ResourcesCacheError::WriteError("Failed to put entry".into()))?;
+        Ok(())
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    async fn remove(&self, key: &str) -> Result<(), ResourcesCacheError> {
+        use wasm_bindgen_futures::JsFuture;
+
+        let cache = self
+            .cache()
+            .await
+            .map_err(ResourcesCacheError::WriteError)?;
+        JsFuture::from(cache.delete_with_str(&Self::url(key)))
+            .await
+            .map_err(|_| ResourcesCacheError::WriteError("Failed to delete entry".into()))?;
+        Ok(())
+    }
+}
+
+// Persistent store of loaded resource bytes.
+// Entries are keyed by catalog hash and validated against the cache version,
+// the content hash of the asset in the catalog and the content hash of the stored bytes.
+#[derive(Debug)]
+pub struct ResourcesCache {
+    config: ResourcesCacheConfig,
+    backend: ResourcesCacheBackend,
+    index: Mutex<ResourcesCacheIndex>,
+}
+impl ResourcesCache {
+    pub async fn open(config: ResourcesCacheConfig) -> Result<Self, ResourcesCacheError> {
+        let backend = ResourcesCacheBackend::open(&config.name).await?;
+        let index = backend
+            .read(INDEX_KEY)
+            .await?
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .and_then(|text| ResourcesCacheIndex::parse(&text))
+            .unwrap_or_default();
+        Ok(Self {
+            config,
+            backend,
+            index: Mutex::new(index),
+        })
+    }
+
+    fn entry_key(hash: u64) -> String {
+        format!("{:016x}", hash)
+    }
+
+    pub fn content_hash(bytes: &[u8]) -> u64 {
+        const_fnv1a_hash::fnv1a_hash_64(bytes, None)
+    }
+
+    // Bytes stored for the asset with content hash `source`, `None` when the asset changed since.
+    pub async fn get(&self, hash: u64, source: u64) -> Option<Vec<u8>> {
+        let entry = self.index.lock().unwrap().entries.get(&hash).cloned()?;
+        if entry.version != self.config.version || entry.source != source {
+            return None;
+        }
+        let bytes = self
+            .backend
+            .read(&Self::entry_key(hash))
+            .await
+            .unwrap_or_else(|e| {
+                log::warn!("{}", e);
+                None
+            })?;
+        if Self::content_hash(&bytes) != entry.content_hash {
+            log::warn!("Resources cache entry {:016x} is corrupted.", hash);
+            self.index.lock().unwrap().entries.remove(&hash);
+            return None;
+        }
+
+        let mut index = self.index.lock().unwrap();
+        index.clock += 1;
+        let clock = index.clock;
+        if let Some(entry) = index.entries.get_mut(&hash) {
+            entry.last_used = clock;
+        }
+        Some(bytes)
+    }
+
+    pub async fn put(
+        &self,
+        hash: u64,
+        source: u64,
+        bytes: &[u8],
+    ) -> Result<(), ResourcesCacheError> {
+        self.backend.write(&Self::entry_key(hash), bytes).await?;
+
+        let mut index = self.index.lock().unwrap();
+        index.clock += 1;
+        let entry = ResourcesCacheEntry {
+            version: self.config.version,
+            source,
+            content_hash: Self::content_hash(bytes),
+            size: bytes.len() as u64,
+            last_used: index.clock,
+        };
+        index.entries.insert(hash, entry);
+        Ok(())
+    }
+
+    // Drop every entry that no longer matches an item of the catalog.
+    pub async fn retain_catalog(
+        &self,
+        catalog: &ResourcesCatalog,
+    ) -> Result<(), ResourcesCacheError> {
+        let sources = catalog
+            .items
+            .iter()
+            .filter_map(|item| Some((item.hash(), item.content_hash()?)))
+            .collect::<HashMap<_, _>>();
+        let stale = {
+            let mut index = self.index.lock().unwrap();
+            let stale = index
+                .entries
+                .iter()
+                .filter(|(hash, entry)| {
+                    entry.version != self.config.version || sources.get(hash) != Some(&entry.source)
+                })
+                .map(|(hash, _)| *hash)
+                .collect::<Vec<_>>();
+            for hash in &stale {
+                index.entries.remove(hash);
+            }
+            stale
+        };
+        for hash in stale {
+            self.backend.remove(&Self::entry_key(hash)).await?;
+        }
+        Ok(())
+    }
+
+    // Evict least recently used entries until the cache fits in the size budget.
+    pub async fn evict(&self) -> Result<(), ResourcesCacheError> {
+        let evicted = {
+            let mut index = self.index.lock().unwrap();
+            let mut entries = index
+                .entries
+                .iter()
+                .map(|(hash, entry)| (*hash, entry.last_used, entry.size))
+                .collect::<Vec<_>>();
+            entries.sort_by_key(|(_, last_used, _)| *last_used);
+
+            let mut total_size = index.total_size();
+            let mut evicted = vec![];
+            for (hash, _, size) in entries {
+                if total_size <= self.config.size_budget {
+                    break;
+                }
+                total_size -= size;
+                index.entries.remove(&hash);
+                evicted.push(hash);
+            }
+            evicted
+        };
+        for hash in evicted {
+            self.backend.remove(&Self::entry_key(hash)).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn flush(&self) -> Result<(), ResourcesCacheError> {
+        let text = self.index.lock().unwrap().to_text();
+        self.backend.write(INDEX_KEY, text.as_bytes()).await
+    }
+
+    pub fn total_size(&self) -> u64 {
+        self.index.lock().unwrap().total_size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::hash;
+    use crate::resources::resources_catalog::ResourcesCatalogItem;
+
+    async fn open_cache(name: &str, version: u32, size_budget: u64) -> ResourcesCache {
+        ResourcesCache::open(ResourcesCacheConfig {
+            name: name.into(),
+            version,
+            size_budget,
+        })
+        .await
+        .unwrap()
+    }
+
+    fn clear_cache(name: &str) {
+        let cache = futures::executor::block_on(open_cache(name, 0, 0));
+        let _ = std::fs::remove_dir_all(&cache.backend.directory);
+    }
+
+    #[test]
+    fn it_should_return_put_bytes_after_reopen() {
+        clear_cache("test-cache-reopen");
+        futures::executor::block_on(async {
+            let cache = open_cache("test-cache-reopen", 1, 1024).await;
+            cache
+                .put(hash("a"), hash("a.png"), &[1, 2, 3])
+                .await
+                .unwrap();
+            cache.flush().await.unwrap();
+
+            let cache = open_cache("test-cache-reopen", 1, 1024).await;
+            let bytes = cache.get(hash("a"), hash("a.png")).await;
+            assert_eq!(bytes, Some(vec![1, 2, 3]));
+        });
+    }
+
+    #[test]
+    fn it_should_miss_on_version_or_content_change() {
+        clear_cache("test-cache-invalidate");
+        futures::executor::block_on(async {
+            let cache = open_cache("test-cache-invalidate", 1, 1024).await;
+            cache
+                .put(hash("a"), hash("a.png"), &[1, 2, 3])
+                .await
+                .unwrap();
+            cache.flush().await.unwrap();
+            assert_eq!(cache.get(hash("a"), hash("a_changed.png")).await, None);
+
+            let cache = open_cache("test-cache-invalidate", 2, 1024).await;
+            assert_eq!(cache.get(hash("a"), hash("a.png")).await, None);
+        });
+    }
+
+    #[test]
+    fn it_should_drop_entries_not_in_catalog() {
+        clear_cache("test-cache-catalog");
+        futures::executor::block_on(async {
+            let item = ResourcesCatalogItem::texture(
+                hash("room_tex_metallic"),
+                "assets/room_Material_Metallic.png",
+            );
+            let source = ResourcesCache::content_hash(include_bytes!(
+                "../../assets/room_Material_Metallic.png"
+            ));
+            assert_eq!(item.content_hash(), Some(source));
+
+            let cache = open_cache("test-cache-catalog", 1, 1024).await;
+            cache
+                .put(hash("room_tex_metallic"), source, &[1])
+                .await
+                .unwrap();
+            cache.put(hash("b"), hash("b.png"), &[2]).await.unwrap();
+
+            cache
+                .retain_catalog(&ResourcesCatalog::new(&[item]))
+                .await
+                .unwrap();
+            assert_eq!(
+                cache.get(hash("room_tex_metallic"), source).await,
+                Some(vec![1])
+            );
+            assert_eq!(cache.get(hash("b"), hash("b.png")).await, None);
+        });
+    }
+
+    #[test]
+    fn it_should_evict_least_recently_used_entries_over_budget() {
+        clear_cache("test-cache-evict");
+        futures::executor::block_on(async {
+            let cache = open_cache("test-cache-evict", 1, 8).await;
+            cache.put(hash("a"), hash("a.png"), &[0; 4]).await.unwrap();
+            cache.put(hash("b"), hash("b.png"), &[0; 4]).await.unwrap();
+            cache.get(hash("a"), hash("a.png")).await;
+            cache.put(hash("c"), hash("c.png"), &[0; 4]).await.unwrap();
+            cache.evict().await.unwrap();
+
+            assert_eq!(cache.total_size(), 8);
+            assert!(cache.get(hash("a"), hash("a.png")).await.is_some());
+            assert!(cache.get(hash("b"), hash("b.png")).await.is_none());
+            assert!(cache.get(hash("c"), hash("c.png")).await.is_some());
+        });
+    }
+}
@@ -1,15 +1,59 @@
 use std::path::{Path, PathBuf};
 
-#[derive(Clone)]
+use crate::resources::Material;
+
+include!(concat!(env!("OUT_DIR"), "/asset_content_hashes.rs"));
+
+// Content hash of an asset file at build time, `None` for paths outside `assets`.
+fn asset_content_hash(path: &Path) -> Option<u64> {
+    ASSET_CONTENT_HASHES
+        .iter()
+        .find(|(asset, _)| Path::new(asset) == path)
+        .map(|(_, content_hash)| *content_hash)
+}
+
+// Load order of catalog items.
+// `Required` items have to be loaded before the main state starts,
+// the others are streamed in afterwards from the highest priority.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ResourcePriority {
+    Required,
+    High,
+    Normal,
+    Low,
+}
+
+// Every item carries the expected content hash of its file,
+// cached copies are only used when it matches.
+#[derive(Debug, Clone)]
 pub(super) enum ResourcesCatalogItem {
-    Mesh { hash: u64, path: PathBuf },
-    Texture { hash: u64, path: PathBuf },
+    Mesh {
+        hash: u64,
+        path: PathBuf,
+        priority: ResourcePriority,
+        content_hash: Option<u64>,
+    },
+    Texture {
+        hash: u64,
+        path: PathBuf,
+        priority: ResourcePriority,
+        placeholder: [u8; 4],
+        content_hash: Option<u64>,
+    },
+    Scene {
+        hash: u64,
+        path: PathBuf,
+        priority: ResourcePriority,
+        content_hash: Option<u64>,
+    },
 }
 impl ResourcesCatalogItem {
     pub fn mesh(hash: u64, path: impl AsRef<Path>) -> Self {
         Self::Mesh {
             hash,
             path: PathBuf::from(path.as_ref()),
+            priority: ResourcePriority::Required,
+            content_hash: asset_content_hash(path.as_ref()),
         }
     }
 
@@ -17,18 +61,111 @@ impl ResourcesCatalogItem {
         Self::Texture {
             hash,
             path: PathBuf::from(path.as_ref()),
+            priority: ResourcePriority::Required,
+            placeholder: [255, 255, 255, 255],
+            content_hash: asset_content_hash(path.as_ref()),
+        }
+    }
+
+    pub fn scene(hash: u64, path: impl AsRef<Path>) -> Self {
+        Self::Scene {
+            hash,
+            path: PathBuf::from(path.as_ref()),
+            priority: ResourcePriority::Required,
+            content_hash: asset_content_hash(path.as_ref()),
+        }
+    }
+
+    pub fn with_priority(mut self, new_priority: ResourcePriority) -> Self {
+        match &mut self {
+            Self::Mesh { priority, .. }
+            | Self::Texture { priority, .. }
+            | Self::Scene { priority, .. } => *priority = new_priority,
+        }
+        self
+    }
+
+    // Color of the 1x1 texture used until the texture is streamed in.
+    pub fn with_placeholder(mut self, color: [u8; 4]) -> Self {
+        if let Self::Texture { placeholder, .. } = &mut self {
+            *placeholder = color;
+        }
+        self
+    }
+
+    pub fn hash(&self) -> u64 {
+        match self {
+            Self::Mesh { hash, .. } | Self::Texture { hash, .. } | Self::Scene { hash, .. } => {
+                *hash
+            }
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Mesh { path, .. } | Self::Texture { path, .. } | Self::Scene { path, .. } => path,
+        }
+    }
+
+    pub fn content_hash(&self) -> Option<u64> {
+        match self {
+            Self::Mesh { content_hash, .. }
+            | Self::Texture { content_hash, .. }
+            | Self::Scene { content_hash, .. } => *content_hash,
+        }
+    }
+
+    pub fn priority(&self) -> ResourcePriority {
+        match self {
+            Self::Mesh { priority, .. }
+            | Self::Texture { priority, .. }
+            | Self::Scene { priority, .. } => *priority,
         }
     }
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ResourcesCatalog {
     pub(super) items: Vec<ResourcesCatalogItem>,
+    pub(super) materials: Vec<Material>,
 }
 impl ResourcesCatalog {
     pub(super) fn new(items: &[ResourcesCatalogItem]) -> Self {
         Self {
             items: Vec::from(items),
+            materials: vec![],
+        }
+    }
+
+    pub(super) fn with_materials(self, materials: &[Material]) -> Self {
+        Self {
+            materials: Vec::from(materials),
+            ..self
         }
     }
+
+    pub fn path(&self, hash: u64) -> Option<&Path> {
+        self.items
+            .iter()
+            .find(|item| item.hash() == hash)
+            .map(|item| item.path())
+    }
+
+    pub fn has_mesh(&self, hash: u64) -> bool {
+        self.items
+            .iter()
+            .any(|item| matches!(item, ResourcesCatalogItem::Mesh { .. }) && item.hash() == hash)
+    }
+
+    pub fn has_material(&self, hash: u64) -> bool {
+        self.materials.iter().any(|material| material.hash == hash)
+    }
+
+    pub(super) fn required_hashes(&self) -> Vec<u64> {
+        self.items
+            .iter()
+            .filter(|item| item.priority() == ResourcePriority::Required)
+            .map(|item| item.hash())
+            .collect()
+    }
 }
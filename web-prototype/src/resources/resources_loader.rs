@@ -1,25 +1,54 @@
-use image::GenericImageView;
-use instant::Duration;
-use rand::Rng;
+use instant::{Duration, Instant};
 use std::future::Future;
 use std::path::Path;
 use std::pin::Pin;
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::mpsc::Sender;
 use std::sync::{Arc, Mutex};
-use std::{collections::HashMap, sync::mpsc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::mpsc,
+};
 use thiserror::Error;
 
+use crate::resources::resource_handle::LoadedResources;
 use crate::resources::resources_catalog::ResourcesCatalogItem;
-use crate::resources::{Mesh, Resources, ResourcesCatalog, Texture};
+use crate::resources::{
+    Material, Mesh, NetworkSimulator, NetworkSimulatorConfig, ResourceHandle, ResourcePriority,
+    Resources, ResourcesCache, ResourcesCacheConfig, ResourcesCatalog, Texture,
+};
 use crate::runtime::Runtime;
+use crate::scene::SceneDescription;
 
-#[derive(Debug, Error)]
+type LoadResult = Result<(), ResourceLoaderError>;
+
+#[derive(Debug, Clone, Error)]
 pub enum ResourceLoaderError {
     #[error("Load resources not completed")]
     LoadNotCompletedError,
     #[error("Already take resources")]
     AlreadyTakeResourcesError,
+    #[error("Resources not taken yet")]
+    ResourcesNotTakenError,
+    #[error("Failed to load {0}: {1}")]
+    LoadError(String, String),
+}
+impl ResourceLoaderError {
+    fn load_error(path: &Path, e: impl std::fmt::Display) -> Self {
+        Self::LoadError(path.display().to_string(), e.to_string())
+    }
+}
+
+// Shared state the load task fills in while the loader hands it out.
+struct LoadOutputs {
+    loaded_flag: Arc<AtomicBool>,
+    loaded_count: Arc<AtomicU32>,
+    loaded: Arc<Mutex<LoadedResources>>,
+    loaded_meshes: Arc<Mutex<HashMap<u64, Mesh>>>,
+    loaded_materials: Arc<Mutex<HashMap<u64, Material>>>,
+    loaded_textures: Arc<Mutex<HashMap<u64, Texture>>>,
+    loaded_scenes: Arc<Mutex<HashMap<u64, SceneDescription>>>,
+    load_times: Arc<Mutex<HashMap<u64, Duration>>>,
 }
 
 #[derive(Debug)]
@@ -28,83 +57,211 @@ pub struct ResourcesLoader {
     loaded_flag: Arc<AtomicBool>,
     loaded_count: Arc<AtomicU32>,
     all_count: usize,
-    loaded_meshes: Arc<Mutex<Option<HashMap<u64, Mesh>>>>,
-    loaded_textures: Arc<Mutex<Option<HashMap<u64, Texture>>>>,
+    catalog: Option<ResourcesCatalog>,
+    // Loaded and failed resources, failed ones count as loaded for `loaded_count`.
+    loaded: Arc<Mutex<LoadedResources>>,
+    loaded_meshes: Arc<Mutex<HashMap<u64, Mesh>>>,
+    loaded_materials: Arc<Mutex<HashMap<u64, Material>>>,
+    loaded_textures: Arc<Mutex<HashMap<u64, Texture>>>,
+    loaded_scenes: Arc<Mutex<HashMap<u64, SceneDescription>>>,
+    // Time each resource took from the start of its fetch until it was decoded.
+    load_times: Arc<Mutex<HashMap<u64, Duration>>>,
+    cache_config: Option<ResourcesCacheConfig>,
+    network_simulator: Option<NetworkSimulator>,
 }
 impl ResourcesLoader {
     pub fn new() -> Self {
         let all_count = 0;
         let loaded_flag = Arc::new(AtomicBool::new(false));
         let loaded_count = Arc::new(AtomicU32::new(0));
-        let loaded_meshes = Arc::new(Mutex::new(Some(HashMap::new())));
-        let loaded_textures = Arc::new(Mutex::new(Some(HashMap::new())));
+        let loaded = Arc::new(Mutex::new(LoadedResources::default()));
+        let loaded_meshes = Arc::new(Mutex::new(HashMap::new()));
+        let loaded_materials = Arc::new(Mutex::new(HashMap::new()));
+        let loaded_textures = Arc::new(Mutex::new(HashMap::new()));
+        let loaded_scenes = Arc::new(Mutex::new(HashMap::new()));
+        let load_times = Arc::new(Mutex::new(HashMap::new()));
 
         Self {
             take_flag: false,
             loaded_flag,
             loaded_count,
             all_count,
+            catalog: None,
+            loaded,
             loaded_meshes,
+            loaded_materials,
             loaded_textures,
+            loaded_scenes,
+            load_times,
+            cache_config: None,
+            network_simulator: None,
+        }
+    }
+
+    pub fn with_cache(cache_config: ResourcesCacheConfig) -> Self {
+        Self {
+            cache_config: Some(cache_config),
+            ..Self::new()
+        }
+    }
+
+    pub fn with_network_simulator(self, config: NetworkSimulatorConfig) -> Self {
+        Self {
+            network_simulator: Some(NetworkSimulator::new(config)),
+            ..self
         }
     }
 
     pub fn start_load(&mut self, runtime: Runtime, catalog: ResourcesCatalog) {
         self.all_count = catalog.items.len();
+        self.catalog = Some(catalog.clone());
 
         runtime.spawn(Self::load(
             runtime.clone(),
-            catalog.clone(),
-            Arc::clone(&self.loaded_flag),
-            Arc::clone(&self.loaded_count),
+            catalog,
             self.all_count,
-            Arc::clone(&self.loaded_meshes),
-            Arc::clone(&self.loaded_textures),
+            LoadOutputs {
+                loaded_flag: Arc::clone(&self.loaded_flag),
+                loaded_count: Arc::clone(&self.loaded_count),
+                loaded: Arc::clone(&self.loaded),
+                loaded_meshes: Arc::clone(&self.loaded_meshes),
+                loaded_materials: Arc::clone(&self.loaded_materials),
+                loaded_textures: Arc::clone(&self.loaded_textures),
+                loaded_scenes: Arc::clone(&self.loaded_scenes),
+                load_times: Arc::clone(&self.load_times),
+            },
+            self.cache_config.clone(),
+            self.network_simulator.clone().map(Arc::new),
         ));
     }
 
     async fn load(
         runtime: Runtime,
         catalog: ResourcesCatalog,
-        loaded_flag: Arc<AtomicBool>,
-        loaded_count: Arc<AtomicU32>,
         all_count: usize,
-        loaded_meshes: Arc<Mutex<Option<HashMap<u64, Mesh>>>>,
-        loaded_textures: Arc<Mutex<Option<HashMap<u64, Texture>>>>,
+        outputs: LoadOutputs,
+        cache_config: Option<ResourcesCacheConfig>,
+        network_simulator: Option<Arc<NetworkSimulator>>,
     ) {
-        let (mesh_sender, mesh_receiver) = mpsc::channel::<Mesh>();
-        let (texture_sender, texture_receiver) = mpsc::channel::<Texture>();
+        let LoadOutputs {
+            loaded_flag,
+            loaded_count,
+            loaded,
+            loaded_meshes,
+            loaded_materials,
+            loaded_textures,
+            loaded_scenes,
+            load_times,
+        } = outputs;
+
+        let cache = match cache_config {
+            Some(cache_config) => match ResourcesCache::open(cache_config).await {
+                Ok(cache) => {
+                    if let Err(e) = cache.retain_catalog(&catalog).await {
+                        log::warn!("{}", e);
+                    }
+                    Some(Arc::new(cache))
+                }
+                Err(e) => {
+                    log::warn!("{}", e);
+                    None
+                }
+            },
+            None => None,
+        };
+
+        let (mesh_sender, mesh_receiver) = mpsc::channel::<(Mesh, Vec<Material>, Duration)>();
+        let (texture_sender, texture_receiver) = mpsc::channel::<(Texture, Duration)>();
+        let (failure_sender, failure_receiver) = mpsc::channel::<(u64, ResourceLoaderError)>();
+        let (scene_sender, scene_receiver) = mpsc::channel::<(u64, SceneDescription, Duration)>();
 
         #[cfg(not(target_arch = "wasm32"))]
         let tasks = {
             catalog
                 .items
                 .iter()
-                .map(|item| match item.clone() {
-                    ResourcesCatalogItem::Mesh { hash, path } => Box::pin({
-                        let runtime = runtime.clone();
-                        let mesh_sender = mesh_sender.clone();
-                        async move {
-                            let duration =
-                                Duration::from_secs_f64(rand::thread_rng().gen_range(0.0..1.0));
-                            runtime.delay(duration).await;
-                            Self::load_mesh(mesh_sender, hash, path).await;
-                        }
-                    })
-                        as Pin<Box<dyn Future<Output = ()> + Send>>,
-                    ResourcesCatalogItem::Texture { hash, path } => Box::pin({
-                        let runtime = runtime.clone();
-                        let texture_sender = texture_sender.clone();
-                        async move {
-                            let duration =
-                                Duration::from_secs_f64(rand::thread_rng().gen_range(0.0..1.0));
-                            runtime.delay(duration).await;
-                            Self::load_texture(texture_sender, hash, path).await;
-                        }
-                    })
-                        as Pin<Box<dyn Future<Output = ()> + Send>>,
+                .map(|item| {
+                    let task = match item.clone() {
+                        ResourcesCatalogItem::Mesh {
+                            hash,
+                            path,
+                            content_hash,
+                            ..
+                        } => Box::pin({
+                            let runtime = runtime.clone();
+                            let mesh_sender = mesh_sender.clone();
+                            let cache = cache.clone();
+                            let network_simulator = network_simulator.clone();
+                            async move {
+                                Self::load_mesh(
+                                    runtime,
+                                    network_simulator,
+                                    mesh_sender,
+                                    cache,
+                                    hash,
+                                    content_hash,
+                                    path,
+                                )
+                                .await
+                            }
+                        })
+                            as Pin<Box<dyn Future<Output = LoadResult> + Send>>,
+                        ResourcesCatalogItem::Texture {
+                            hash,
+                            path,
+                            content_hash,
+                            ..
+                        } => Box::pin({
+                            let runtime = runtime.clone();
+                            let texture_sender = texture_sender.clone();
+                            let cache = cache.clone();
+                            let network_simulator = network_simulator.clone();
+                            async move {
+                                Self::load_texture(
+                                    runtime,
+                                    network_simulator,
+                                    texture_sender,
+                                    cache,
+                                    hash,
+                                    content_hash,
+                                    path,
+                                )
+                                .await
+                            }
+                        })
+                            as Pin<Box<dyn Future<Output = LoadResult> + Send>>,
+                        ResourcesCatalogItem::Scene {
+                            hash,
+                            path,
+                            content_hash,
+                            ..
+                        } => Box::pin({
+                            let runtime = runtime.clone();
+                            let scene_sender = scene_sender.clone();
+                            let network_simulator = network_simulator.clone();
+                            let cache = cache.clone();
+                            async move {
+                                Self::load_scene(
+                                    runtime,
+                                    network_simulator,
+                                    scene_sender,
+                                    cache,
+                                    hash,
+                                    content_hash,
+                                    path,
+                                )
+                                .await
+                            }
+                        })
+                            as Pin<Box<dyn Future<Output = LoadResult> + Send>>,
+                    };
+                    (item.priority(), item.hash(), task)
                 })
-                .collect::<Vec<Pin<Box<dyn Future<Output = ()> + Send>>>>()
+                .collect::<Vec<(
+                    ResourcePriority,
+                    u64,
+                    Pin<Box<dyn Future<Output = LoadResult> + Send>>,
+                )>>()
         };
 
         #[cfg(target_arch = "wasm32")]
@@ -112,98 +269,326 @@ impl ResourcesLoader {
             catalog
                 .items
                 .iter()
-                .map(|item| match item.clone() {
-                    ResourcesCatalogItem::Mesh { hash, path } => Box::pin({
-                        let runtime = runtime.clone();
-                        let mesh_sender = mesh_sender.clone();
-                        async move {
-                            let duration =
-                                Duration::from_secs_f64(rand::thread_rng().gen_range(0.0..1.0));
-                            runtime.delay(duration).await;
-                            Self::load_mesh(mesh_sender, hash, path).await;
-                        }
-                    })
-                        as Pin<Box<dyn Future<Output = ()>>>,
-                    ResourcesCatalogItem::Texture { hash, path } => Box::pin({
-                        let runtime = runtime.clone();
-                        let texture_sender = texture_sender.clone();
-                        async move {
-                            let duration =
-                                Duration::from_secs_f64(rand::thread_rng().gen_range(0.0..1.0));
-                            runtime.delay(duration).await;
-                            Self::load_texture(texture_sender, hash, path).await;
-                        }
-                    })
-                        as Pin<Box<dyn Future<Output = ()>>>,
+                .map(|item| {
+                    let task = match item.clone() {
+                        ResourcesCatalogItem::Mesh {
+                            hash,
+                            path,
+                            content_hash,
+                            ..
+                        } => Box::pin({
+                            let runtime = runtime.clone();
+                            let mesh_sender = mesh_sender.clone();
+                            let cache = cache.clone();
+                            let network_simulator = network_simulator.clone();
+                            async move {
+                                Self::load_mesh(
+                                    runtime,
+                                    network_simulator,
+                                    mesh_sender,
+                                    cache,
+                                    hash,
+                                    content_hash,
+                                    path,
+                                )
+                                .await
+                            }
+                        })
+                            as Pin<Box<dyn Future<Output = LoadResult>>>,
+                        ResourcesCatalogItem::Texture {
+                            hash,
+                            path,
+                            content_hash,
+                            ..
+                        } => Box::pin({
+                            let runtime = runtime.clone();
+                            let texture_sender = texture_sender.clone();
+                            let cache = cache.clone();
+                            let network_simulator = network_simulator.clone();
+                            async move {
+                                Self::load_texture(
+                                    runtime,
+                                    network_simulator,
+                                    texture_sender,
+                                    cache,
+                                    hash,
+                                    content_hash,
+                                    path,
+                                )
+                                .await
+                            }
+                        })
+                            as Pin<Box<dyn Future<Output = LoadResult>>>,
+                        ResourcesCatalogItem::Scene {
+                            hash,
+                            path,
+                            content_hash,
+                            ..
+                        } => Box::pin({
+                            let runtime = runtime.clone();
+                            let scene_sender = scene_sender.clone();
+                            let network_simulator = network_simulator.clone();
+                            let cache = cache.clone();
+                            async move {
+                                Self::load_scene(
+                                    runtime,
+                                    network_simulator,
+                                    scene_sender,
+                                    cache,
+                                    hash,
+                                    content_hash,
+                                    path,
+                                )
+                                .await
+                            }
+                        })
+                            as Pin<Box<dyn Future<Output = LoadResult>>>,
+                    };
+                    (item.priority(), item.hash(), task)
                 })
-                .collect::<Vec<Pin<Box<dyn Future<Output = ()>>>>>()
+                .collect::<Vec<(
+                    ResourcePriority,
+                    u64,
+                    Pin<Box<dyn Future<Output = LoadResult>>>,
+                )>>()
         };
 
-        runtime.spawn(async {
-            futures::future::join_all(tasks).await;
+        // Load each priority group after the previous one has been loaded.
+        // A failed resource is reported instead of stopping the other loads.
+        let mut groups = BTreeMap::new();
+        for (priority, hash, task) in tasks {
+            let failure_sender = failure_sender.clone();
+            let task = async move {
+                if let Err(e) = task.await {
+                    failure_sender
+                        .send((hash, e))
+                        .unwrap_or_else(|_| panic!("Failed to send load failure."));
+                }
+            };
+            groups.entry(priority).or_insert_with(Vec::new).push(task);
+        }
+        runtime.spawn(async move {
+            for (_, tasks) in groups {
+                futures::future::join_all(tasks).await;
+            }
         });
 
         while loaded_count.load(Ordering::SeqCst) < all_count as u32 {
-            for mesh in mesh_receiver.try_iter() {
-                loaded_meshes
-                    .lock()
-                    .unwrap()
-                    .as_mut()
-                    .and_then(|hm| hm.insert(mesh.hash, mesh));
+            for (mesh, materials, load_time) in mesh_receiver.try_iter() {
+                let hash = mesh.hash;
+                load_times.lock().unwrap().insert(hash, load_time);
+                loaded_materials.lock().unwrap().extend(
+                    materials
+                        .into_iter()
+                        .map(|material| (material.hash, material)),
+                );
+                loaded_meshes.lock().unwrap().insert(hash, mesh);
+                loaded.lock().unwrap().insert(hash);
+                loaded_count.fetch_add(1, Ordering::SeqCst);
+            }
+            for (hash, scene, load_time) in scene_receiver.try_iter() {
+                load_times.lock().unwrap().insert(hash, load_time);
+                loaded_scenes.lock().unwrap().insert(hash, scene);
+                loaded.lock().unwrap().insert(hash);
+                loaded_count.fetch_add(1, Ordering::SeqCst);
+            }
+            for (texture, load_time) in texture_receiver.try_iter() {
+                let hash = texture.hash;
+                load_times.lock().unwrap().insert(hash, load_time);
+                loaded_textures.lock().unwrap().insert(hash, texture);
+                loaded.lock().unwrap().insert(hash);
                 loaded_count.fetch_add(1, Ordering::SeqCst);
             }
-            for texture in texture_receiver.try_iter() {
-                loaded_textures
-                    .lock()
-                    .unwrap()
-                    .as_mut()
-                    .and_then(|hm| hm.insert(texture.hash, texture));
+            for (hash, e) in failure_receiver.try_iter() {
+                log::error!("{}", e);
+                loaded.lock().unwrap().fail(hash, e);
                 loaded_count.fetch_add(1, Ordering::SeqCst);
             }
 
             runtime.delay(Duration::ZERO).await;
         }
 
-        runtime.delay(Duration::from_secs_f32(0.3)).await;
+        if let Some(cache) = cache {
+            if let Err(e) = cache.evict().await {
+                log::warn!("{}", e);
+            }
+            if let Err(e) = cache.flush().await {
+                log::warn!("{}", e);
+            }
+            log::info!("Resources cache size: {} bytes", cache.total_size());
+        }
 
         loaded_flag.store(true, Ordering::SeqCst);
     }
 
     // #[cfg(not(target_arch = "wasm32"))]
-    async fn load_mesh(mesh_sender: Sender<Mesh>, hash: u64, path: impl AsRef<Path>) {
+    async fn load_mesh(
+        runtime: Runtime,
+        network_simulator: Option<Arc<NetworkSimulator>>,
+        mesh_sender: Sender<(Mesh, Vec<Material>, Duration)>,
+        cache: Option<Arc<ResourcesCache>>,
+        hash: u64,
+        content_hash: Option<u64>,
+        path: impl AsRef<Path>,
+    ) -> LoadResult {
+        let start = Instant::now();
+        let bytes = Self::fetch_cached(
+            &runtime,
+            &network_simulator,
+            &cache,
+            hash,
+            content_hash,
+            path.as_ref(),
+        )
+        .await?;
+        let (mesh, materials) = Mesh::from_gltf(hash, path.as_ref(), &bytes)
+            .map_err(|e| ResourceLoaderError::load_error(path.as_ref(), e))?;
         mesh_sender
-            .send(Mesh { hash })
-            .unwrap_or_else(|_| panic!("Failed to send loaded mesh."))
+            .send((mesh, materials, start.elapsed()))
+            .unwrap_or_else(|_| panic!("Failed to send loaded mesh."));
+        Ok(())
+    }
+
+    async fn load_scene(
+        runtime: Runtime,
+        network_simulator: Option<Arc<NetworkSimulator>>,
+        scene_sender: Sender<(u64, SceneDescription, Duration)>,
+        cache: Option<Arc<ResourcesCache>>,
+        hash: u64,
+        content_hash: Option<u64>,
+        path: impl AsRef<Path>,
+    ) -> LoadResult {
+        let start = Instant::now();
+        let bytes = Self::fetch_cached(
+            &runtime,
+            &network_simulator,
+            &cache,
+            hash,
+            content_hash,
+            path.as_ref(),
+        )
+        .await?;
+        let source = String::from_utf8(bytes)
+            .map_err(|e| ResourceLoaderError::load_error(path.as_ref(), e))?;
+        let scene = SceneDescription::from_ron(&source)
+            .map_err(|e| ResourceLoaderError::load_error(path.as_ref(), e))?;
+        scene_sender
+            .send((hash, scene, start.elapsed()))
+            .unwrap_or_else(|_| panic!("Failed to send loaded scene."));
+        Ok(())
+    }
+
+    // File of the asset with content hash `content_hash`, fetched and cached unless the cache has it.
+    // Meshes are cached as their fetched file, decoding them again is cheap next to the fetch.
+    async fn fetch_cached(
+        runtime: &Runtime,
+        network_simulator: &Option<Arc<NetworkSimulator>>,
+        cache: &Option<Arc<ResourcesCache>>,
+        hash: u64,
+        content_hash: Option<u64>,
+        path: &Path,
+    ) -> Result<Vec<u8>, ResourceLoaderError> {
+        if let (Some(cache), Some(content_hash)) = (cache, content_hash) {
+            if let Some(bytes) = cache.get(hash, content_hash).await {
+                return Ok(bytes);
+            }
+        }
+
+        let bytes = Self::fetch(runtime, network_simulator, hash, path).await?;
+        Self::store_cached(cache, hash, content_hash, &bytes, || bytes.clone(), path).await;
+        Ok(bytes)
+    }
+
+    // Cached texture of the asset with content hash `content_hash`, assets without one are not cached.
+    async fn load_cached_texture(
+        cache: &Option<Arc<ResourcesCache>>,
+        hash: u64,
+        content_hash: Option<u64>,
+    ) -> Option<Texture> {
+        let bytes = cache.as_ref()?.get(hash, content_hash?).await?;
+        Texture::from_bytes(hash, &bytes)
+    }
+
+    // Store what `cached` returns for the asset fetched as `bytes` unless the asset differs
+    // from the catalog, the cache would keep an outdated copy of it.
+    async fn store_cached(
+        cache: &Option<Arc<ResourcesCache>>,
+        hash: u64,
+        content_hash: Option<u64>,
+        bytes: &[u8],
+        cached: impl FnOnce() -> Vec<u8>,
+        path: &Path,
+    ) {
+        if let Some(cache) = cache {
+            let source = ResourcesCache::content_hash(bytes);
+            if content_hash != Some(source) {
+                log::warn!(
+                    "{} differs from the catalog, not caching it.",
+                    path.display()
+                );
+                return;
+            }
+            if let Err(e) = cache.put(hash, source, &cached()).await {
+                log::warn!("{}", e);
+            }
+        }
+    }
+
+    // Run the network simulation for one attempt and retry on simulated failures.
+    async fn simulate(
+        runtime: &Runtime,
+        network_simulator: &NetworkSimulator,
+        hash: u64,
+        size: usize,
+        path: &Path,
+    ) -> LoadResult {
+        let mut attempt = 0;
+        loop {
+            match network_simulator
+                .simulate(runtime, hash, attempt, size)
+                .await
+            {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt < network_simulator.max_retries() => {
+                    log::warn!("{}, retrying {}", e, path.display());
+                    attempt += 1;
+                }
+                Err(e) => return Err(ResourceLoaderError::load_error(path, e)),
+            }
+        }
+    }
+
+    async fn fetch(
+        runtime: &Runtime,
+        network_simulator: &Option<Arc<NetworkSimulator>>,
+        hash: u64,
+        path: &Path,
+    ) -> Result<Vec<u8>, ResourceLoaderError> {
+        let bytes = Self::fetch_bytes(path).await?;
+        if let Some(network_simulator) = network_simulator {
+            Self::simulate(runtime, network_simulator, hash, bytes.len(), path).await?;
+        }
+        Ok(bytes)
     }
 
     #[cfg(not(target_arch = "wasm32"))]
-    async fn load_texture(texture_sender: Sender<Texture>, hash: u64, path: impl AsRef<Path>) {
+    async fn fetch_bytes(path: &Path) -> Result<Vec<u8>, ResourceLoaderError> {
         use std::env;
         use std::path::PathBuf;
 
-        let path = if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
+        let file_path = if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
             let mut cargo_dir = PathBuf::from(manifest_dir);
             cargo_dir.push(path);
             cargo_dir.as_path().to_owned()
         } else {
-            path.as_ref().to_owned()
+            path.to_owned()
         };
 
-        let img = image::open(path).unwrap_or_else(|_| panic!("Failed to load image."));
-        let dimensions = img.dimensions();
-        let rgba: &[u8] = &img.to_rgba8();
-        texture_sender
-            .send(Texture {
-                hash,
-                rgba: Vec::from(rgba),
-                height: dimensions.0,
-                width: dimensions.1,
-            })
-            .unwrap_or_else(|_| panic!("Failed to send loaded mesh."))
+        std::fs::read(file_path).map_err(|e| ResourceLoaderError::load_error(path, e))
     }
 
     #[cfg(target_arch = "wasm32")]
-    async fn load_texture(texture_sender: Sender<Texture>, hash: u64, path: impl AsRef<Path>) {
+    async fn fetch_bytes(path: &Path) -> Result<Vec<u8>, ResourceLoaderError> {
         use futures::StreamExt;
         use js_sys::Uint8Array;
         use wasm_bindgen::{prelude::*, JsCast};
@@ -214,72 +599,163 @@ impl ResourcesLoader {
         let mut opts = RequestInit::new();
         opts.method("GET");
         opts.mode(RequestMode::Cors);
-        let request = Request::new_with_str_and_init(path.as_ref().to_str().unwrap(), &opts)
+        let error = |e: JsValue| ResourceLoaderError::load_error(path, format!("{:?}", e));
+        let request = Request::new_with_str_and_init(path.to_str().unwrap(), &opts)
             .unwrap_or_else(|_| panic!("Failed to create request."));
 
         let window = web_sys::window().unwrap_or_else(|| panic!("Failed to load html window."));
         let resp_value = JsFuture::from(window.fetch_with_request(&request))
             .await
-            .unwrap_or_else(|_| panic!("Failed to fetch request."));
+            .map_err(error)?;
 
         let resp: Response = resp_value
             .dyn_into()
             .unwrap_or_else(|_| panic!("Failed to cast Response"));
-        let blob: Blob =
-            JsFuture::from(resp.blob().unwrap_or_else(|_| panic!("Failed to get Blob")))
-                .await
-                .unwrap_or_else(|_| panic!("Failed to unwrap JsSuture"))
-                .dyn_into()
-                .unwrap_or_else(|_| panic!("Failed to cast Blob"));
+        if !resp.ok() {
+            return Err(ResourceLoaderError::load_error(
+                path,
+                format!("HTTP status {}", resp.status()),
+            ));
+        }
+        let blob: Blob = JsFuture::from(resp.blob().map_err(error)?)
+            .await
+            .map_err(error)?
+            .dyn_into()
+            .unwrap_or_else(|_| panic!("Failed to cast Blob"));
         let readable_stream = blob.stream().dyn_into().unwrap_throw();
         let mut stream = ReadableStream::from_raw(readable_stream).into_stream();
         let mut bytes = vec![];
-        while let Some(Ok(chunk)) = stream.next().await {
-            let uint8_array = Uint8Array::new(&chunk);
+        while let Some(chunk) = stream.next().await {
+            let uint8_array = Uint8Array::new(&chunk.map_err(error)?);
             bytes.append(&mut uint8_array.to_vec());
         }
+        Ok(bytes)
+    }
 
-        let img = image::load_from_memory(&bytes).unwrap();
-        let dimensions = img.dimensions();
-        let rgba: &[u8] = &img.to_rgba8();
+    async fn load_texture(
+        runtime: Runtime,
+        network_simulator: Option<Arc<NetworkSimulator>>,
+        texture_sender: Sender<(Texture, Duration)>,
+        cache: Option<Arc<ResourcesCache>>,
+        hash: u64,
+        content_hash: Option<u64>,
+        path: impl AsRef<Path>,
+    ) -> LoadResult {
+        let start = Instant::now();
+        if let Some(texture) = Self::load_cached_texture(&cache, hash, content_hash).await {
+            texture_sender
+                .send((texture, start.elapsed()))
+                .unwrap_or_else(|_| panic!("Failed to send loaded texture."));
+            return Ok(());
+        }
+
+        let bytes = Self::fetch(&runtime, &network_simulator, hash, path.as_ref()).await?;
+        let texture = Texture::from_image(hash, &bytes)
+            .map_err(|e| ResourceLoaderError::load_error(path.as_ref(), e))?;
+        let load_time = start.elapsed();
+        Self::store_cached(
+            &cache,
+            hash,
+            content_hash,
+            &bytes,
+            || texture.to_bytes(),
+            path.as_ref(),
+        )
+        .await;
         texture_sender
-            .send(Texture {
-                hash,
-                rgba: Vec::from(rgba),
-                height: dimensions.0,
-                width: dimensions.1,
-            })
-            .unwrap_or_else(|_| panic!("Failed to send loaded mesh."))
+            .send((texture, load_time))
+            .unwrap_or_else(|_| panic!("Failed to send loaded texture."));
+        Ok(())
     }
 
-    pub fn all_count(&self) -> usize {
-        self.all_count
+    pub fn is_loaded(&self) -> bool {
+        self.loaded_flag.load(Ordering::SeqCst)
     }
 
-    pub fn loaded_count(&self) -> usize {
-        self.loaded_count.load(Ordering::SeqCst) as usize
+    fn required_hashes(&self) -> Vec<u64> {
+        self.catalog
+            .as_ref()
+            .map(|catalog| catalog.required_hashes())
+            .unwrap_or_default()
     }
 
-    pub fn is_loaded(&self) -> bool {
-        self.loaded_flag.load(Ordering::SeqCst)
+    pub fn is_required_loaded(&self) -> bool {
+        let loaded = self.loaded.lock().unwrap();
+        self.catalog.is_some()
+            && self
+                .required_hashes()
+                .into_iter()
+                .all(|hash| loaded.contains(hash))
     }
 
-    pub fn progress(&self) -> f32 {
-        self.loaded_count() as f32 / self.all_count() as f32
+    // Error of a required resource which failed to load, the main state can not start without it.
+    pub fn required_failure(&self) -> Option<String> {
+        let loaded = self.loaded.lock().unwrap();
+        self.required_hashes()
+            .into_iter()
+            .find_map(|hash| loaded.failure(hash).map(|e| e.to_string()))
+    }
+
+    pub fn required_progress(&self) -> f32 {
+        let required_hashes = self.required_hashes();
+        if required_hashes.is_empty() {
+            return if self.catalog.is_some() { 1.0 } else { 0.0 };
+        }
+        let loaded = self.loaded.lock().unwrap();
+        let loaded_count = required_hashes
+            .iter()
+            .filter(|hash| loaded.contains(**hash))
+            .count();
+        loaded_count as f32 / required_hashes.len() as f32
+    }
+
+    pub fn catalog(&self) -> Option<&ResourcesCatalog> {
+        self.catalog.as_ref()
+    }
+
+    pub fn handle(&self, hash: u64) -> ResourceHandle {
+        ResourceHandle::new(hash, Arc::clone(&self.loaded))
     }
 
     pub fn take_flag(&self) -> bool {
         self.take_flag
     }
 
+    // Take the resources once the required set is loaded.
+    // Streaming textures are replaced by their placeholder until `stream_into` moves them in.
     pub fn take_resources(&mut self) -> Result<Resources, ResourceLoaderError> {
-        if self.loaded_flag.load(Ordering::SeqCst) {
+        if self.is_required_loaded() {
             if !self.take_flag() {
                 self.take_flag = true;
-                Ok(Resources::new(
-                    self.loaded_meshes.lock().unwrap().take().unwrap(),
-                    self.loaded_textures.lock().unwrap().take().unwrap(),
-                ))
+                let meshes = std::mem::take(&mut *self.loaded_meshes.lock().unwrap());
+                let mut materials = self
+                    .catalog
+                    .iter()
+                    .flat_map(|catalog| catalog.materials.iter())
+                    .map(|material| (material.hash, material.clone()))
+                    .collect::<HashMap<_, _>>();
+                materials.extend(std::mem::take(&mut *self.loaded_materials.lock().unwrap()));
+                let textures = std::mem::take(&mut *self.loaded_textures.lock().unwrap());
+                let scenes = std::mem::take(&mut *self.loaded_scenes.lock().unwrap());
+                let placeholder_textures = self
+                    .catalog
+                    .iter()
+                    .flat_map(|catalog| catalog.items.iter())
+                    .filter_map(|item| match item {
+                        ResourcesCatalogItem::Texture {
+                            hash, placeholder, ..
+                        } if !textures.contains_key(hash) => {
+                            Some((*hash, Texture::placeholder(*hash, *placeholder)))
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                let mut resources =
+                    Resources::new(meshes, materials, textures, placeholder_textures, scenes);
+                for (hash, load_time) in self.load_times.lock().unwrap().drain() {
+                    resources.insert_load_time(hash, load_time);
+                }
+                Ok(resources)
             } else {
                 Err(ResourceLoaderError::AlreadyTakeResourcesError)
             }
@@ -287,4 +763,27 @@ impl ResourcesLoader {
             Err(ResourceLoaderError::LoadNotCompletedError)
         }
     }
+
+    // Move the resources streamed in since the last call into `resources`.
+    pub fn stream_into(&self, resources: &mut Resources) -> Result<(), ResourceLoaderError> {
+        if !self.take_flag() {
+            return Err(ResourceLoaderError::ResourcesNotTakenError);
+        }
+        for (_, mesh) in self.loaded_meshes.lock().unwrap().drain() {
+            resources.insert_mesh(mesh);
+        }
+        for (_, material) in self.loaded_materials.lock().unwrap().drain() {
+            resources.insert_material(material);
+        }
+        for (_, texture) in self.loaded_textures.lock().unwrap().drain() {
+            resources.insert_texture(texture);
+        }
+        for (hash, scene) in self.loaded_scenes.lock().unwrap().drain() {
+            resources.insert_scene(hash, scene);
+        }
+        for (hash, load_time) in self.load_times.lock().unwrap().drain() {
+            resources.insert_load_time(hash, load_time);
+        }
+        Ok(())
+    }
 }
@@ -1,3 +1,26 @@
+// Channels a texture preview shows, a single channel is shown as grayscale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureChannel {
+    Rgba,
+    Red,
+    Green,
+    Blue,
+    Alpha,
+}
+impl TextureChannel {
+    pub const ALL: [Self; 5] = [Self::Rgba, Self::Red, Self::Green, Self::Blue, Self::Alpha];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Rgba => "RGBA",
+            Self::Red => "R",
+            Self::Green => "G",
+            Self::Blue => "B",
+            Self::Alpha => "A",
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Texture {
     pub hash: u64,
@@ -5,3 +28,131 @@ pub struct Texture {
     pub width: u32,
     pub height: u32,
 }
+impl Texture {
+    // Decoded PNG or JPEG image, converted to RGBA.
+    pub fn from_image(hash: u64, bytes: &[u8]) -> image::ImageResult<Self> {
+        let image = image::load_from_memory(bytes)?.to_rgba8();
+        Ok(Self {
+            hash,
+            width: image.width(),
+            height: image.height(),
+            rgba: image.into_raw(),
+        })
+    }
+
+    pub(super) fn placeholder(hash: u64, color: [u8; 4]) -> Self {
+        Self {
+            hash,
+            rgba: Vec::from(color),
+            width: 1,
+            height: 1,
+        }
+    }
+
+    pub fn mip_level_count(&self) -> u32 {
+        32 - self.width.max(self.height).max(1).leading_zeros()
+    }
+
+    pub fn memory_size(&self) -> usize {
+        self.rgba.len()
+    }
+
+    // Box filtered mip level of the texture showing only `channel`, e.g. for the inspector.
+    // The level is clamped to the smallest one.
+    pub fn preview(&self, hash: u64, channel: TextureChannel, mip_level: u32) -> Self {
+        let mut rgba = self.rgba.clone();
+        let (mut width, mut height) = (self.width, self.height);
+        for _ in 0..mip_level.min(self.mip_level_count() - 1) {
+            let (mip_width, mip_height) = ((width / 2).max(1), (height / 2).max(1));
+            let mut mip = Vec::with_capacity(4 * mip_width as usize * mip_height as usize);
+            for y in 0..mip_height {
+                for x in 0..mip_width {
+                    for c in 0..4 {
+                        let texel = |x: u32, y: u32| {
+                            let (x, y) = (x.min(width - 1), y.min(height - 1));
+                            rgba[4 * (y * width + x) as usize + c] as u32
+                        };
+                        let sum = texel(2 * x, 2 * y)
+                            + texel(2 * x + 1, 2 * y)
+                            + texel(2 * x, 2 * y + 1)
+                            + texel(2 * x + 1, 2 * y + 1);
+                        mip.push(((sum + 2) / 4) as u8);
+                    }
+                }
+            }
+            rgba = mip;
+            width = mip_width;
+            height = mip_height;
+        }
+
+        let channel_index = match channel {
+            TextureChannel::Rgba => None,
+            TextureChannel::Red => Some(0),
+            TextureChannel::Green => Some(1),
+            TextureChannel::Blue => Some(2),
+            TextureChannel::Alpha => Some(3),
+        };
+        if let Some(channel_index) = channel_index {
+            for texel in rgba.chunks_exact_mut(4) {
+                let value = texel[channel_index];
+                texel.copy_from_slice(&[value, value, value, 255]);
+            }
+        }
+
+        Self {
+            hash,
+            rgba,
+            width,
+            height,
+        }
+    }
+
+    pub(super) fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(8 + self.rgba.len());
+        bytes.extend_from_slice(&self.width.to_le_bytes());
+        bytes.extend_from_slice(&self.height.to_le_bytes());
+        bytes.extend_from_slice(&self.rgba);
+        bytes
+    }
+
+    pub(super) fn from_bytes(hash: u64, bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < 8 {
+            return None;
+        }
+        let width = u32::from_le_bytes(bytes[0..4].try_into().ok()?);
+        let height = u32::from_le_bytes(bytes[4..8].try_into().ok()?);
+        let rgba = Vec::from(&bytes[8..]);
+        if rgba.len() != 4 * width as usize * height as usize {
+            return None;
+        }
+        Some(Self {
+            hash,
+            rgba,
+            width,
+            height,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_preview_mip_level_of_channel() {
+        let texture = Texture {
+            hash: 0,
+            rgba: [[10, 20, 30, 255], [30, 40, 50, 255], [50, 60, 70, 255]].concat(),
+            width: 3,
+            height: 1,
+        };
+        assert_eq!(texture.mip_level_count(), 2);
+
+        let preview = texture.preview(1, TextureChannel::Green, 1);
+        assert_eq!((preview.hash, preview.width, preview.height), (1, 1, 1));
+        assert_eq!(preview.rgba, vec![30, 30, 30, 255]);
+
+        let preview = texture.preview(1, TextureChannel::Rgba, 5);
+        assert_eq!(preview.rgba, vec![20, 30, 40, 255]);
+    }
+}
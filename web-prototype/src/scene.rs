@@ -0,0 +1,8 @@
+mod scene_description;
+mod scene_graph;
+
+pub use scene_description::{
+    save_scene, CameraDescription, EntityDescription, LightDescription, SceneDescription,
+    SceneError, TransformDescription,
+};
+pub use scene_graph::{SceneGraph, SceneNode};
@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SceneError {
+    #[error("Failed to parse scene: {0}")]
+    ParseError(ron::Error),
+    #[error("Failed to serialize scene: {0}")]
+    SerializeError(ron::Error),
+    #[error("Unknown mesh {0} in the resources catalog")]
+    UnknownMeshError(String),
+    #[error("Unknown material {0} in the resources catalog")]
+    UnknownMaterialError(String),
+}
+
+// Scene file stored as RON next to the assets.
+// Meshes and materials are referenced by their catalog names, e.g. `mesh: Some("room")`.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SceneDescription {
+    pub camera: Option<CameraDescription>,
+    pub lights: Vec<LightDescription>,
+    pub entities: Vec<EntityDescription>,
+}
+impl SceneDescription {
+    pub fn from_ron(source: &str) -> Result<Self, SceneError> {
+        ron::from_str(source).map_err(SceneError::ParseError)
+    }
+
+    pub fn to_ron(&self) -> Result<String, SceneError> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::new())
+            .map_err(SceneError::SerializeError)
+    }
+
+    // Number of entities including the children.
+    pub fn entity_count(&self) -> usize {
+        fn count(entities: &[EntityDescription]) -> usize {
+            entities
+                .iter()
+                .map(|entity| 1 + count(&entity.children))
+                .sum()
+        }
+        count(&self.entities)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CameraDescription {
+    pub position: [f32; 3],
+    pub target: [f32; 3],
+    pub fovy_degrees: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum LightDescription {
+    Directional {
+        direction: [f32; 3],
+        color: [f32; 3],
+        intensity: f32,
+    },
+    // Ambient light of a procedural sky gradient.
+    Sky {
+        sky: [f32; 3],
+        horizon: [f32; 3],
+        ground: [f32; 3],
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntityDescription {
+    pub name: String,
+    #[serde(default)]
+    pub transform: TransformDescription,
+    #[serde(default)]
+    pub mesh: Option<String>,
+    // Replaces the materials of every primitive of the mesh.
+    #[serde(default)]
+    pub material: Option<String>,
+    #[serde(default)]
+    pub children: Vec<EntityDescription>,
+}
+
+// Transform relative to the parent entity.
+// The rotation is in degrees, applied around X, then Y, then Z.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransformDescription {
+    pub translation: [f32; 3],
+    pub rotation: [f32; 3],
+    pub scale: [f32; 3],
+}
+impl Default for TransformDescription {
+    fn default() -> Self {
+        Self {
+            translation: [0.0; 3],
+            rotation: [0.0; 3],
+            scale: [1.0; 3],
+        }
+    }
+}
+
+// Write the scene to `file_name` in the working directory.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_scene(file_name: &str, source: &str) {
+    match std::fs::write(file_name, source) {
+        Ok(()) => log::info!("Saved {}", file_name),
+        Err(e) => log::warn!("Failed to save {}: {}", file_name, e),
+    }
+}
+
+// Download the scene as `file_name`.
+#[cfg(target_arch = "wasm32")]
+pub fn save_scene(file_name: &str, source: &str) {
+    use wasm_bindgen::JsCast;
+
+    let parts = js_sys::Array::of1(&wasm_bindgen::JsValue::from_str(source));
+    let blob = web_sys::Blob::new_with_str_sequence(&parts)
+        .unwrap_or_else(|_| panic!("Failed to create scene blob"));
+    let url = web_sys::Url::create_object_url_with_blob(&blob)
+        .unwrap_or_else(|_| panic!("Failed to create scene url"));
+    let anchor = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.create_element("a").ok())
+        .and_then(|element| element.dyn_into::<web_sys::HtmlAnchorElement>().ok())
+        .unwrap_or_else(|| panic!("Failed to create download link"));
+    anchor.set_href(&url);
+    anchor.set_download(file_name);
+    anchor.click();
+    if web_sys::Url::revoke_object_url(&url).is_err() {
+        log::warn!("Failed to revoke scene url");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_parse_with_defaults() {
+        let scene = SceneDescription::from_ron(
+            r#"(
+                entities: [
+                    (name: "room", mesh: Some("room"), children: [(name: "child")]),
+                ],
+            )"#,
+        )
+        .unwrap();
+        assert_eq!(scene.camera, None);
+        assert_eq!(scene.entity_count(), 2);
+        assert_eq!(scene.entities[0].mesh.as_deref(), Some("room"));
+        assert_eq!(
+            scene.entities[0].children[0].transform,
+            TransformDescription::default()
+        );
+
+        let source = scene.to_ron().unwrap();
+        assert_eq!(SceneDescription::from_ron(&source).unwrap(), scene);
+    }
+}
@@ -0,0 +1,258 @@
+use crate::camera::Camera;
+use crate::light::{AmbientLight, DirectionalLight};
+use crate::resources::{hash, ResourcesCatalog};
+use crate::scene::*;
+use vek::{Mat4, Rgb, Vec3};
+
+// Catalog resource referenced by name from a scene file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneHandle {
+    pub name: String,
+    pub hash: u64,
+}
+impl SceneHandle {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            hash: hash(name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneNode {
+    pub name: String,
+    pub parent: Option<usize>,
+    pub transform: TransformDescription,
+    pub mesh: Option<SceneHandle>,
+    pub material: Option<SceneHandle>,
+}
+impl SceneNode {
+    pub fn local_transform(&self) -> Mat4<f32> {
+        let TransformDescription {
+            translation,
+            rotation,
+            scale,
+        } = self.transform;
+        Mat4::<f32>::translation_3d(translation)
+            * Mat4::rotation_z(rotation[2].to_radians())
+            * Mat4::rotation_y(rotation[1].to_radians())
+            * Mat4::rotation_x(rotation[0].to_radians())
+            * Mat4::scaling_3d(scale)
+    }
+}
+
+// Mesh of a node with its world transform.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneDraw {
+    pub mesh: u64,
+    pub material: Option<u64>,
+    pub transform: Mat4<f32>,
+}
+
+// Entities of a scene file with the handles resolved against the catalog.
+// Nodes are flattened depth first, so parents always come before their children.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SceneGraph {
+    pub nodes: Vec<SceneNode>,
+    pub camera: Option<CameraDescription>,
+    pub lights: Vec<LightDescription>,
+}
+impl SceneGraph {
+    pub fn load(
+        description: &SceneDescription,
+        catalog: &ResourcesCatalog,
+    ) -> Result<Self, SceneError> {
+        let mut graph = Self {
+            nodes: vec![],
+            camera: description.camera.clone(),
+            lights: description.lights.clone(),
+        };
+        for entity in &description.entities {
+            graph.load_entity(entity, None, catalog)?;
+        }
+        Ok(graph)
+    }
+
+    fn load_entity(
+        &mut self,
+        entity: &EntityDescription,
+        parent: Option<usize>,
+        catalog: &ResourcesCatalog,
+    ) -> Result<(), SceneError> {
+        let mesh = entity.mesh.as_deref().map(SceneHandle::new);
+        if let Some(mesh) = &mesh {
+            if !catalog.has_mesh(mesh.hash) {
+                return Err(SceneError::UnknownMeshError(mesh.name.clone()));
+            }
+        }
+        let material = entity.material.as_deref().map(SceneHandle::new);
+        if let Some(material) = &material {
+            if !catalog.has_material(material.hash) {
+                return Err(SceneError::UnknownMaterialError(material.name.clone()));
+            }
+        }
+
+        let index = self.nodes.len();
+        self.nodes.push(SceneNode {
+            name: entity.name.clone(),
+            parent,
+            transform: entity.transform,
+            mesh,
+            material,
+        });
+        for child in &entity.children {
+            self.load_entity(child, Some(index), catalog)?;
+        }
+        Ok(())
+    }
+
+    // Scene file of the current nodes, e.g. after they were edited.
+    pub fn save(&self) -> SceneDescription {
+        SceneDescription {
+            camera: self.camera.clone(),
+            lights: self.lights.clone(),
+            entities: self.entities(None),
+        }
+    }
+
+    fn entities(&self, parent: Option<usize>) -> Vec<EntityDescription> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.parent == parent)
+            .map(|(index, node)| EntityDescription {
+                name: node.name.clone(),
+                transform: node.transform,
+                mesh: node.mesh.as_ref().map(|mesh| mesh.name.clone()),
+                material: node.material.as_ref().map(|material| material.name.clone()),
+                children: self.entities(Some(index)),
+            })
+            .collect()
+    }
+
+    pub fn apply_camera(&self, camera: &mut Camera) {
+        if let Some(description) = &self.camera {
+            camera.position = Vec3::from(description.position);
+            camera.target = Vec3::from(description.target);
+            camera.fovy = description.fovy_degrees.to_radians();
+        }
+    }
+
+    // Keep the camera for `save`.
+    pub fn set_camera(&mut self, camera: &Camera) {
+        self.camera = Some(CameraDescription {
+            position: camera.position.into_array(),
+            target: camera.target.into_array(),
+            fovy_degrees: camera.fovy.to_degrees(),
+        });
+    }
+
+    // The mesh pass lights the scene with the first directional light.
+    pub fn directional_light(&self) -> Option<DirectionalLight> {
+        self.lights.iter().find_map(|light| match light {
+            LightDescription::Directional {
+                direction,
+                color,
+                intensity,
+            } => Some(DirectionalLight {
+                direction: Vec3::from(*direction).normalized(),
+                color: rgb(*color),
+                intensity: *intensity,
+            }),
+            _ => None,
+        })
+    }
+
+    pub fn ambient_light(&self) -> Option<AmbientLight> {
+        self.lights.iter().find_map(|light| match light {
+            LightDescription::Sky {
+                sky,
+                horizon,
+                ground,
+            } => Some(AmbientLight::sky(rgb(*sky), rgb(*horizon), rgb(*ground))),
+            _ => None,
+        })
+    }
+
+    pub fn world_transforms(&self) -> Vec<Mat4<f32>> {
+        let mut transforms: Vec<Mat4<f32>> = Vec::with_capacity(self.nodes.len());
+        for node in &self.nodes {
+            let transform = match node.parent {
+                Some(parent) => transforms[parent] * node.local_transform(),
+                None => node.local_transform(),
+            };
+            transforms.push(transform);
+        }
+        transforms
+    }
+
+    pub fn draws(&self) -> Vec<SceneDraw> {
+        self.nodes
+            .iter()
+            .zip(self.world_transforms())
+            .filter_map(|(node, transform)| {
+                Some(SceneDraw {
+                    mesh: node.mesh.as_ref()?.hash,
+                    material: node.material.as_ref().map(|material| material.hash),
+                    transform,
+                })
+            })
+            .collect()
+    }
+}
+
+fn rgb([r, g, b]: [f32; 3]) -> Rgb<f32> {
+    Rgb::new(r, g, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::get_catalog;
+
+    const SCENE: &str = r#"(
+        entities: [
+            (
+                name: "root",
+                transform: (translation: (1.0, 0.0, 0.0), scale: (2.0, 2.0, 2.0)),
+                children: [
+                    (
+                        name: "room",
+                        transform: (translation: (0.0, 1.0, 0.0)),
+                        mesh: Some("room"),
+                        material: Some("room_material"),
+                    ),
+                ],
+            ),
+            (name: "empty"),
+        ],
+    )"#;
+
+    #[test]
+    fn it_should_compose_transforms_of_parents() {
+        let description = SceneDescription::from_ron(SCENE).unwrap();
+        let graph = SceneGraph::load(&description, &get_catalog()).unwrap();
+        assert_eq!(graph.nodes.len(), 3);
+        assert_eq!(graph.nodes[1].parent, Some(0));
+
+        let draws = graph.draws();
+        assert_eq!(draws.len(), 1);
+        assert_eq!(draws[0].mesh, hash("room"));
+        assert_eq!(draws[0].material, Some(hash("room_material")));
+        let origin = draws[0].transform.mul_point(Vec3::zero());
+        assert!((origin - Vec3::new(1.0, 2.0, 0.0)).magnitude() < 1e-6);
+
+        assert_eq!(graph.save(), description);
+    }
+
+    #[test]
+    fn it_should_reject_unknown_meshes() {
+        let description =
+            SceneDescription::from_ron(r#"(entities: [(name: "a", mesh: Some("b"))])"#).unwrap();
+        assert!(matches!(
+            SceneGraph::load(&description, &get_catalog()),
+            Err(SceneError::UnknownMeshError(name)) if name == "b"
+        ));
+    }
+}
@@ -0,0 +1,5 @@
+mod settings_storage;
+mod user_settings;
+
+pub use settings_storage::{load_settings, save_settings};
+pub use user_settings::UserSettings;
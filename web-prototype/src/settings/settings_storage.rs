@@ -0,0 +1,47 @@
+// Settings file in the working directory.
+#[cfg(not(target_arch = "wasm32"))]
+const SETTINGS_FILE: &str = "settings.ron";
+
+// localStorage key of the settings.
+#[cfg(target_arch = "wasm32")]
+const SETTINGS_KEY: &str = "web-prototype-settings";
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_settings() -> Option<String> {
+    match std::fs::read_to_string(SETTINGS_FILE) {
+        Ok(source) => Some(source),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => {
+            log::warn!("Failed to read {}: {}", SETTINGS_FILE, e);
+            None
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_settings(source: &str) {
+    if let Err(e) = std::fs::write(SETTINGS_FILE, source) {
+        log::warn!("Failed to save {}: {}", SETTINGS_FILE, e);
+    }
+}
+
+// localStorage is missing e.g. when the page is sandboxed, the settings are not kept then.
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn load_settings() -> Option<String> {
+    local_storage()?.get_item(SETTINGS_KEY).ok().flatten()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn save_settings(source: &str) {
+    let saved = local_storage()
+        .map(|storage| storage.set_item(SETTINGS_KEY, source).is_ok())
+        .unwrap_or(false);
+    if !saved {
+        log::warn!("Failed to save the settings to localStorage");
+    }
+}
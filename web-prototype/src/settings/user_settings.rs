@@ -0,0 +1,317 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::camera::CameraControllerKind;
+use crate::capture::CaptureSettings;
+use crate::pass::{PostProcessSettings, TonemapSettings};
+use crate::renderer_config::RendererConfig;
+use crate::settings::{load_settings, save_settings};
+use crate::state::*;
+
+#[derive(Debug, Error)]
+pub enum SettingsError {
+    #[error("Failed to parse settings: {0}")]
+    ParseError(ron::Error),
+    #[error("Failed to serialize settings: {0}")]
+    SerializeError(ron::Error),
+    #[error("Unsupported settings version {0}")]
+    UnsupportedVersionError(u32),
+}
+
+// Preferences of the user restored on startup, a config file on native and localStorage on wasm.
+// Fields added later fall back to their defaults, other changes bump `VERSION` and add a migration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserSettings {
+    pub version: u32,
+    #[serde(default)]
+    pub view: ViewSettings,
+    #[serde(default)]
+    pub tonemap: TonemapSettings,
+    #[serde(default)]
+    pub post_process: PostProcessSettings,
+    #[serde(default)]
+    pub renderer: RendererSettings,
+    #[serde(default)]
+    pub windows: WindowSettings,
+    #[serde(default)]
+    pub capture: CaptureSettings,
+}
+impl Default for UserSettings {
+    fn default() -> Self {
+        Self {
+            version: Self::VERSION,
+            view: ViewSettings::default(),
+            tonemap: TonemapSettings::default(),
+            post_process: PostProcessSettings::default(),
+            renderer: RendererSettings::default(),
+            windows: WindowSettings::default(),
+            capture: CaptureSettings::default(),
+        }
+    }
+}
+impl UserSettings {
+    pub const VERSION: u32 = 1;
+    // Migration `i` upgrades settings of version `i + 1` to version `i + 2`.
+    const MIGRATIONS: &'static [fn(&mut Self)] = &[];
+
+    pub fn from_ron(source: &str) -> Result<Self, SettingsError> {
+        let mut settings: Self = ron::from_str(source).map_err(SettingsError::ParseError)?;
+        if settings.version == 0 || settings.version > Self::VERSION {
+            return Err(SettingsError::UnsupportedVersionError(settings.version));
+        }
+        for migration in &Self::MIGRATIONS[settings.version as usize - 1..] {
+            migration(&mut settings);
+        }
+        settings.version = Self::VERSION;
+        Ok(settings)
+    }
+
+    pub fn to_ron(&self) -> Result<String, SettingsError> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::new())
+            .map_err(SettingsError::SerializeError)
+    }
+
+    // Saved settings, or the defaults when there are none or they can not be read.
+    pub fn load() -> Self {
+        match load_settings() {
+            Some(source) => Self::from_ron(&source).unwrap_or_else(|e| {
+                log::warn!("{}, using the default settings", e);
+                Self::default()
+            }),
+            None => Self::default(),
+        }
+    }
+
+    pub fn save(&self) {
+        match self.to_ron() {
+            Ok(source) => save_settings(&source),
+            Err(e) => log::warn!("{}", e),
+        }
+    }
+
+    pub fn apply(&self, scene: &mut MainStateScene) {
+        scene.view_state = self.view.view_state();
+        scene.tonemap = self.tonemap;
+        scene.post_process = self.post_process;
+        scene.show_profiler = self.windows.show_profiler;
+        scene.show_scene = self.windows.show_scene;
+        scene.inspector.open = self.windows.show_resource_inspector;
+        scene.capture.frames = self.capture.frames;
+        scene.capture.fps = self.capture.fps;
+    }
+
+    // Settings with the current preferences of the scene.
+    pub fn with_scene(&self, scene: &MainStateScene) -> Self {
+        Self {
+            version: Self::VERSION,
+            view: self.view.with_view_state(&scene.view_state),
+            tonemap: scene.tonemap,
+            post_process: scene.post_process,
+            renderer: RendererSettings::new(&scene.renderer),
+            windows: WindowSettings {
+                show_profiler: scene.show_profiler,
+                show_scene: scene.show_scene,
+                show_resource_inspector: scene.inspector.open,
+            },
+            capture: scene.capture,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ViewKind {
+    Triangle,
+    Texture,
+    Mesh,
+}
+
+// The selected view and the options of every view, kept while another view is selected.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ViewSettings {
+    pub view: ViewKind,
+    pub clear_color: [f32; 4],
+    pub controller: CameraControllerKind,
+}
+impl Default for ViewSettings {
+    fn default() -> Self {
+        Self {
+            view: ViewKind::Triangle,
+            clear_color: [0.0, 0.0, 0.0, 1.0],
+            controller: CameraControllerKind::Orbit,
+        }
+        .with_view_state(&MainStateViewState::new())
+    }
+}
+impl ViewSettings {
+    pub fn view_state(&self) -> MainStateViewState {
+        match self.view {
+            ViewKind::Triangle => MainStateViewState::Triangle {
+                label: "Triangle".into(),
+                clear_color: vek::Rgba::from(self.clear_color),
+            },
+            ViewKind::Texture => MainStateViewState::texture(),
+            ViewKind::Mesh => MainStateViewState::Mesh {
+                label: "Mesh".into(),
+                controller: self.controller,
+            },
+        }
+    }
+
+    pub fn with_view_state(&self, view_state: &MainStateViewState) -> Self {
+        match view_state {
+            MainStateViewState::Triangle { clear_color, .. } => Self {
+                view: ViewKind::Triangle,
+                clear_color: clear_color.into_array(),
+                ..*self
+            },
+            MainStateViewState::Texture { .. } => Self {
+                view: ViewKind::Texture,
+                ..*self
+            },
+            MainStateViewState::Mesh { controller, .. } => Self {
+                view: ViewKind::Mesh,
+                controller: *controller,
+                ..*self
+            },
+        }
+    }
+}
+
+// Renderer options of the renderer window, applied when the device is created.
+// wgpu types are stored by their names, unknown names fall back to the defaults.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RendererSettings {
+    pub sample_count: u32,
+    pub present_mode: String,
+    // `None` uses the preferred format of the surface.
+    pub format: Option<String>,
+}
+impl Default for RendererSettings {
+    fn default() -> Self {
+        Self::new(&RendererConfig::default())
+    }
+}
+impl RendererSettings {
+    // Formats the renderer window offers on the supported backends.
+    const FORMATS: [wgpu::TextureFormat; 6] = [
+        wgpu::TextureFormat::Bgra8Unorm,
+        wgpu::TextureFormat::Bgra8UnormSrgb,
+        wgpu::TextureFormat::Rgba8Unorm,
+        wgpu::TextureFormat::Rgba8UnormSrgb,
+        wgpu::TextureFormat::Rgba16Float,
+        wgpu::TextureFormat::Rgb10a2Unorm,
+    ];
+
+    pub fn new(config: &RendererConfig) -> Self {
+        Self {
+            sample_count: config.sample_count,
+            present_mode: format!("{:?}", config.present_mode),
+            format: config.format.map(|format| format!("{:?}", format)),
+        }
+    }
+
+    pub fn renderer_config(&self) -> RendererConfig {
+        let default = RendererConfig::default();
+        let present_mode = RendererConfig::PRESENT_MODES
+            .into_iter()
+            .find(|present_mode| format!("{:?}", present_mode) == self.present_mode)
+            .unwrap_or_else(|| {
+                log::warn!("Unknown present mode {}", self.present_mode);
+                default.present_mode
+            });
+        let format = self.format.as_ref().and_then(|name| {
+            let format = Self::FORMATS
+                .into_iter()
+                .find(|format| format!("{:?}", format) == *name);
+            if format.is_none() {
+                log::warn!("Unknown surface format {}", name);
+            }
+            format
+        });
+        RendererConfig {
+            sample_count: self.sample_count,
+            present_mode,
+            format,
+            ..default
+        }
+        .validate()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowSettings {
+    pub show_profiler: bool,
+    pub show_scene: bool,
+    pub show_resource_inspector: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pass::TonemapOperator;
+
+    #[test]
+    fn it_should_fill_missing_fields_with_defaults() {
+        let settings = UserSettings::from_ron(
+            r#"(
+                version: 1,
+                view: (view: Mesh, controller: Fly),
+                tonemap: (operator: AgX),
+                renderer: (present_mode: "Mailbox", format: Some("Rgba8Unorm")),
+            )"#,
+        )
+        .unwrap();
+        assert_eq!(
+            settings.view.view_state(),
+            MainStateViewState::Mesh {
+                label: "Mesh".into(),
+                controller: CameraControllerKind::Fly,
+            }
+        );
+        assert_eq!(settings.tonemap.operator, TonemapOperator::AgX);
+        assert_eq!(
+            settings.tonemap.exposure,
+            TonemapSettings::default().exposure
+        );
+        assert_eq!(settings.post_process, PostProcessSettings::default());
+
+        let config = settings.renderer.renderer_config();
+        assert_eq!(config.present_mode, wgpu::PresentMode::Mailbox);
+        assert_eq!(config.format, Some(wgpu::TextureFormat::Rgba8Unorm));
+        assert_eq!(config.sample_count, RendererConfig::default().sample_count);
+
+        let source = settings.to_ron().unwrap();
+        assert_eq!(UserSettings::from_ron(&source).unwrap(), settings);
+    }
+
+    #[test]
+    fn it_should_reject_unsupported_versions() {
+        assert_eq!(
+            UserSettings::MIGRATIONS.len() as u32,
+            UserSettings::VERSION - 1
+        );
+        let source = format!("(version: {})", UserSettings::VERSION + 1);
+        assert!(matches!(
+            UserSettings::from_ron(&source),
+            Err(SettingsError::UnsupportedVersionError(version)) if version == UserSettings::VERSION + 1
+        ));
+        assert!(matches!(
+            UserSettings::from_ron("(view: (view: Texture))"),
+            Err(SettingsError::ParseError(_))
+        ));
+    }
+
+    #[test]
+    fn it_should_keep_options_of_other_views() {
+        let view = ViewSettings::default().with_view_state(&MainStateViewState::Triangle {
+            label: "Triangle".into(),
+            clear_color: vek::Rgba::new(1.0, 0.5, 0.0, 1.0),
+        });
+        let view = view.with_view_state(&MainStateViewState::mesh());
+        assert_eq!(view.view, ViewKind::Mesh);
+        assert_eq!(view.clear_color, [1.0, 0.5, 0.0, 1.0]);
+    }
+}
@@ -1,10 +1,16 @@
 mod loading_state;
 mod main_state;
+mod pause_state;
 mod state;
+mod state_context;
+mod state_transition;
 
 use loading_state::LoadingState;
 use main_state::MainState;
+use pause_state::PauseState;
 use state::StateTrait;
+use state_context::StateContext;
+use state_transition::{Crossfade, StateOp, StateTransition};
 
-pub use main_state::MainStateViewState;
+pub use main_state::{MainStateScene, MainStateViewState};
 pub use state::State;
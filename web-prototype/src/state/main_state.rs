@@ -1,6 +1,20 @@
+use gpu_context::negotiate_format;
+use instant::{Duration, Instant};
+use shader_library::ShaderLibrary;
+
+use crate::camera::*;
+use crate::capture::{CaptureRequest, CaptureSettings};
+use crate::input::actions;
+use crate::light::*;
 use crate::pass::*;
+use crate::profiler::Profiler;
+use crate::render_graph::*;
+use crate::renderer_config::RendererConfig;
 use crate::resources::*;
+use crate::scene::SceneGraph;
+use crate::settings::UserSettings;
 use crate::state::*;
+use crate::ui::{main_ui, ResourceInspectorState, TexturePreview};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum MainStateViewState {
@@ -11,6 +25,10 @@ pub enum MainStateViewState {
     Texture {
         label: String,
     },
+    Mesh {
+        label: String,
+        controller: CameraControllerKind,
+    },
 }
 impl MainStateViewState {
     pub fn new() -> Self {
@@ -34,125 +52,409 @@ impl MainStateViewState {
             label: "Texture".into(),
         }
     }
+
+    pub fn mesh() -> Self {
+        Self::Mesh {
+            label: "Mesh".into(),
+            controller: CameraControllerKind::Orbit,
+        }
+    }
 }
 
+// Frame data shared by the passes of the main state render graph.
 #[derive(Debug)]
-pub(super) struct MainState {
-    surface: wgpu::Surface,
-    device: wgpu::Device,
-    queue: wgpu::Queue,
-    config: wgpu::SurfaceConfiguration,
-    size: winit::dpi::PhysicalSize<u32>,
+pub struct MainStateScene {
+    pub view_state: MainStateViewState,
+    pub resources: Resources,
+    // Catalog the resources were loaded from, the inspector shows their paths.
+    pub catalog: ResourcesCatalog,
+    pub scene_graph: SceneGraph,
+    pub camera: Camera,
+    pub light: DirectionalLight,
+    pub ambient: AmbientLight,
+    pub tonemap: TonemapSettings,
+    pub post_process: PostProcessSettings,
+    // Requested renderer config, applied before the next frame when it changes.
+    pub renderer: RendererConfig,
+    // Format the surface is configured with, the renderer window offers its sRGB counterpart.
+    pub surface_format: wgpu::TextureFormat,
+    pub profiler: Profiler,
+    pub show_profiler: bool,
+    pub show_scene: bool,
+    pub inspector: ResourceInspectorState,
+    pub capture: CaptureSettings,
+}
 
-    resources: Resources,
+// Render graph and passes of the main state,
+// recreated whenever the renderer config changes.
+#[derive(Debug)]
+struct MainStatePasses {
+    render_graph: RenderGraph,
+    triangle: TrianglePass,
+    texture: TexturePass,
+    mesh: MeshPass,
+    bloom: BloomPass,
+    tonemap: TonemapPass,
+    post_process: PostProcessPass,
+}
+impl MainStatePasses {
+    fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        config: &wgpu::SurfaceConfiguration,
+        renderer_config: &RendererConfig,
+        shaders: &ShaderLibrary,
+        profiler: &Profiler,
+    ) -> Self {
+        let mut render_graph = RenderGraph::new().with_profiler(profiler.clone());
+        let scene_target = SceneTarget::new(&mut render_graph, renderer_config.sample_count);
+        let ldr = render_graph.create_texture(RenderGraphTextureDescriptor::new(
+            "ldr",
+            PostProcessPass::LDR_FORMAT,
+        ));
 
-    view_state: MainStateViewState,
+        let triangle = TrianglePass::new(device, shaders, scene_target);
+        let texture = TexturePass::new(device, shaders, scene_target);
+        let mesh = MeshPass::new(device, queue, shaders, scene_target);
+        let bloom = BloomPass::new(device, shaders, &mut render_graph, scene_target.hdr);
+        let tonemap = TonemapPass::new(
+            device,
+            shaders,
+            PostProcessPass::LDR_FORMAT,
+            scene_target.hdr,
+            ldr,
+        );
+        let post_process = PostProcessPass::new(device, queue, shaders, config, ldr);
 
-    triangle_pass: TrianglePass,
-    texture_pass: TexturePass,
+        Self {
+            render_graph,
+            triangle,
+            texture,
+            mesh,
+            bloom,
+            tonemap,
+            post_process,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct MainState {
+    scene: MainStateScene,
 
-    egui_pass: EguiPass,
+    camera_controller: CameraController,
+    // Camera of the last two fixed updates, the scene renders the one interpolated between them.
+    camera: Camera,
+    previous_camera: Camera,
+
+    passes: MainStatePasses,
+    egui_pass: EguiPass<MainStateScene>,
+    // Streamed texture the texture view shows.
+    base_color_texture: ResourceHandle,
+    // Inspector preview last set on the egui pass.
+    texture_preview: Option<TexturePreview>,
+    // Settings last saved, and when they started to differ from the scene.
+    settings: Option<UserSettings>,
+    settings_changed: Option<Instant>,
 }
 impl MainState {
-    pub(super) fn new(loading_state: Box<LoadingState>, resources: Resources) -> Self {
-        let LoadingState {
-            surface,
-            device,
-            queue,
-            config,
-            size,
-            ..
-        } = *loading_state;
+    // Dragging a slider changes the settings every frame, they are saved once it rests.
+    const SETTINGS_SAVE_DELAY: Duration = Duration::from_millis(500);
 
-        let view_state = MainStateViewState::new();
+    pub(super) fn new(context: &StateContext, resources: Resources) -> Self {
+        let catalog = context
+            .resources_loader
+            .catalog()
+            .cloned()
+            .unwrap_or_else(|| {
+                // Without a catalog the inspector is empty and the scene falls back below.
+                log::error!("Failed to get resources catalog.");
+                ResourcesCatalog::default()
+            });
+        // A broken scene file leaves the scene empty instead of stopping the app.
+        let scene_graph = match resources.try_scene(hash("room_scene")) {
+            Some(description) => SceneGraph::load(description, &catalog).unwrap_or_else(|e| {
+                log::warn!("{}", e);
+                SceneGraph::default()
+            }),
+            None => SceneGraph::default(),
+        };
+        let mut camera = Camera::new(context.size);
+        scene_graph.apply_camera(&mut camera);
 
-        let triangle_pass = TrianglePass::new(&device, &config);
-        let texture_pass = TexturePass::new(&device);
+        let mut scene = MainStateScene {
+            view_state: MainStateViewState::new(),
+            resources,
+            catalog,
+            light: scene_graph.directional_light().unwrap_or_default(),
+            ambient: scene_graph.ambient_light().unwrap_or_default(),
+            scene_graph,
+            camera,
+            tonemap: TonemapSettings::default(),
+            post_process: PostProcessSettings::default(),
+            renderer: context.renderer_config.clone(),
+            surface_format: context.gpu.config.format,
+            profiler: context.profiler.clone(),
+            show_profiler: false,
+            show_scene: false,
+            inspector: ResourceInspectorState::new(),
+            capture: CaptureSettings::default(),
+        };
+        // The renderer settings were applied when the device was created.
+        if let Some(settings) = &context.settings {
+            settings.apply(&mut scene);
+        }
 
-        let egui_pass = EguiPass::new(&device, &config, size);
+        let camera_controller = CameraController::new(CameraControllerKind::Orbit);
+        let camera = scene.camera.clone();
+        let previous_camera = scene.camera.clone();
+
+        let passes = MainStatePasses::new(
+            &context.gpu.device,
+            &context.gpu.queue,
+            &context.gpu.config,
+            &context.renderer_config,
+            &context.shaders,
+            &context.profiler,
+        );
+        let egui_pass = EguiPass::new(
+            &context.gpu.device,
+            &context.shaders,
+            &context.gpu.config,
+            context.size,
+            context.scale_factor,
+            main_ui,
+        );
 
         Self {
-            surface,
-            device,
-            queue,
-            config,
-            size,
+            scene,
 
-            resources,
+            camera_controller,
+            camera,
+            previous_camera,
 
-            view_state,
+            passes,
+            egui_pass,
+            base_color_texture: context.resources_loader.handle(hash("room_tex_base_color")),
+            texture_preview: None,
+            settings: context.settings.clone(),
+            settings_changed: None,
+        }
+    }
 
-            triangle_pass,
-            texture_pass,
+    fn save_settings(&mut self) {
+        let saved = match &self.settings {
+            Some(saved) => saved,
+            None => return,
+        };
+        let settings = saved.with_scene(&self.scene);
+        if settings == *saved {
+            self.settings_changed = None;
+            return;
+        }
+        let changed = *self.settings_changed.get_or_insert_with(Instant::now);
+        if changed.elapsed() >= Self::SETTINGS_SAVE_DELAY {
+            settings.save();
+            self.settings = Some(settings);
+            self.settings_changed = None;
+        }
+    }
 
-            egui_pass,
+    // Reconfigure the target and recreate every pass with the requested renderer config.
+    // Features and limits belong to the device and only apply at startup.
+    fn apply_renderer_config(&mut self, context: &mut StateContext) {
+        if self.scene.renderer.features != context.renderer_config.features
+            || self.scene.renderer.limits != context.renderer_config.limits
+        {
+            log::warn!(
+                "Device features and limits only apply at startup, keeping the current ones."
+            );
+        }
+        let renderer_config = RendererConfig {
+            features: context.renderer_config.features,
+            limits: context.renderer_config.limits.clone(),
+            ..self.scene.renderer.clone()
         }
+        .validate();
+
+        let format = negotiate_format(renderer_config.format, context.gpu.preferred_format());
+        if format != context.gpu.config.format {
+            self.egui_pass.recreate(
+                &context.gpu.device,
+                &context.gpu.queue,
+                &context.shaders,
+                format,
+            );
+        }
+        context.gpu.config.format = format;
+        self.scene.surface_format = format;
+        context.gpu.config.present_mode = renderer_config.present_mode;
+        context.gpu.configure();
+        context.target.configure(&context.gpu);
+
+        self.passes = MainStatePasses::new(
+            &context.gpu.device,
+            &context.gpu.queue,
+            &context.gpu.config,
+            &renderer_config,
+            &context.shaders,
+            &context.profiler,
+        );
+        self.scene.renderer = renderer_config.clone();
+        context.renderer_config = renderer_config;
     }
 }
 impl StateTrait for MainState {
-    fn update(mut self: Box<Self>) -> Box<dyn StateTrait + Send> {
+    fn fixed_update(&mut self, context: &mut StateContext, delta_time: f32) {
+        if let MainStateViewState::Mesh { .. } = self.scene.view_state {
+            self.previous_camera = self.camera.clone();
+            self.camera_controller
+                .fixed_update(&mut self.camera, &context.input, delta_time);
+        }
+    }
+
+    fn update(
+        &mut self,
+        context: &mut StateContext,
+        _delta_time: f32,
+        alpha: f32,
+    ) -> Option<StateTransition> {
+        if self.scene.renderer != context.renderer_config {
+            self.apply_renderer_config(context);
+        }
         self.egui_pass.update();
-        self
+        let (wants_pointer, wants_keyboard) = self.egui_pass.wants_input();
+        context.input.set_ui_capture(wants_pointer, wants_keyboard);
+        // A resolved handle is moved into the resources by the next `stream_into`.
+        let base_color_loaded = self.base_color_texture.is_loaded();
+        if let Err(e) = context
+            .resources_loader
+            .stream_into(&mut self.scene.resources)
+        {
+            log::warn!("{}", e);
+        }
+        // The texture view shows the base color texture once it streamed in.
+        if base_color_loaded && !self.egui_pass.has_texture(self.base_color_texture.hash()) {
+            self.egui_pass.set_texture(
+                &context.gpu.device,
+                &context.gpu.queue,
+                self.scene.resources.texture(self.base_color_texture.hash()),
+            );
+        }
+        let texture_preview = self.scene.inspector.texture_preview(&self.scene.resources);
+        if texture_preview != self.texture_preview {
+            if let Some(preview) = texture_preview {
+                let texture = self.scene.resources.texture(preview.texture).preview(
+                    ResourceInspectorState::PREVIEW_TEXTURE,
+                    preview.channel,
+                    preview.mip_level,
+                );
+                self.egui_pass
+                    .set_texture(&context.gpu.device, &context.gpu.queue, &texture);
+            }
+            self.texture_preview = texture_preview;
+        }
+
+        if let MainStateViewState::Mesh { controller, .. } = &self.scene.view_state {
+            if self.camera_controller.kind() != *controller {
+                self.camera_controller = CameraController::new(*controller);
+            }
+            // Pointer motion is applied at once, it moves both ends of the interpolation.
+            self.camera_controller
+                .update(&mut self.previous_camera, &context.input);
+            self.camera_controller
+                .update(&mut self.camera, &context.input);
+        }
+        self.scene.camera = Camera::interpolate(&self.previous_camera, &self.camera, alpha);
+
+        if context.input.just_pressed(actions::TOGGLE_PROFILER) {
+            self.scene.show_profiler = !self.scene.show_profiler;
+        }
+        if context.input.just_pressed(actions::CAPTURE_SCREENSHOT) {
+            self.scene.capture.request = Some(CaptureRequest::Screenshot);
+        }
+        if let Some(request) = self.scene.capture.request.take() {
+            context.capture.request(request);
+        }
+        self.scene.capture.remaining_frames = context.capture.remaining_frames();
+        self.save_settings();
+
+        if context.input.just_pressed(actions::PAUSE) {
+            Some(
+                StateTransition::push(Box::new(PauseState::new(context)))
+                    .with_crossfade(PauseState::CROSSFADE),
+            )
+        } else {
+            None
+        }
     }
 
-    fn handle_event(&mut self, winit_event: &winit::event::Event<()>) {
+    fn handle_event(&mut self, _context: &StateContext, winit_event: &winit::event::Event<()>) {
         self.egui_pass.handle_event(winit_event);
     }
 
-    fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
-        if new_size.width > 0 && new_size.height > 0 {
-            self.size = new_size;
-            self.config.width = new_size.width;
-            self.config.height = new_size.height;
-            self.surface.configure(&self.device, &self.config);
-            self.egui_pass.resize(new_size);
-        }
+    fn resize(&mut self, context: &StateContext) {
+        self.camera.resize(context.size);
+        self.previous_camera.resize(context.size);
+        self.scene.camera.resize(context.size);
+        self.egui_pass.resize(context.size, context.scale_factor);
     }
 
-    fn size(&self) -> winit::dpi::PhysicalSize<u32> {
-        self.size
+    // The scene resources are still on the CPU, the new passes upload them again lazily.
+    fn recreate(&mut self, context: &StateContext) {
+        self.passes = MainStatePasses::new(
+            &context.gpu.device,
+            &context.gpu.queue,
+            &context.gpu.config,
+            &context.renderer_config,
+            &context.shaders,
+            &context.profiler,
+        );
+        self.egui_pass.recreate(
+            &context.gpu.device,
+            &context.gpu.queue,
+            &context.shaders,
+            context.gpu.config.format,
+        );
+        self.scene.surface_format = context.gpu.config.format;
     }
 
-    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
-        let output = self.surface.get_current_texture()?;
+    fn cursor_icon(&self) -> egui::CursorIcon {
+        self.egui_pass.cursor_icon()
+    }
 
-        let view = output
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+    fn is_ready(&self, context: &StateContext) -> bool {
+        context.resources_loader.is_loaded()
+    }
 
-        let mut encoder = self
-            .device
-            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("Render Encoder"),
-            });
+    fn set_view_state(&mut self, view_state: MainStateViewState) {
+        self.scene.view_state = view_state;
+    }
 
-        match &self.view_state {
-            MainStateViewState::Triangle { clear_color, .. } => {
-                self.triangle_pass.render(&mut encoder, clear_color, &view);
-            }
-            &MainStateViewState::Texture { .. } => {
-                self.texture_pass.render(
-                    &mut encoder,
-                    &self.device,
-                    &self.queue,
-                    &self.config,
-                    &view,
-                    &self.resources,
-                );
-            }
+    fn render(&mut self, context: &StateContext, view: &wgpu::TextureView) {
+        let mut passes: Vec<&mut dyn RenderGraphPass<MainStateScene>> = vec![];
+        match &self.scene.view_state {
+            MainStateViewState::Triangle { .. } => passes.push(&mut self.passes.triangle),
+            MainStateViewState::Texture { .. } => passes.push(&mut self.passes.texture),
+            MainStateViewState::Mesh { .. } => passes.push(&mut self.passes.mesh),
         }
+        if self.scene.post_process.bloom.enabled {
+            passes.push(&mut self.passes.bloom);
+        }
+        passes.push(&mut self.passes.tonemap);
+        passes.push(&mut self.passes.post_process);
+        passes.push(&mut self.egui_pass);
 
-        self.egui_pass.render(
-            &mut encoder,
-            &mut self.view_state,
-            &self.device,
-            &self.queue,
-            &view,
-        );
-
-        self.queue.submit(std::iter::once(encoder.finish()));
-        output.present();
-
-        Ok(())
+        // A broken graph skips the frame instead of taking the app down.
+        if let Err(e) = self.passes.render_graph.execute(
+            &context.gpu.device,
+            &context.gpu.queue,
+            &context.gpu.config,
+            view,
+            &mut passes,
+            &mut self.scene,
+        ) {
+            log::error!("Failed to execute render graph: {}", e);
+        }
     }
 }
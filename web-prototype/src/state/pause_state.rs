@@ -0,0 +1,103 @@
+use instant::Duration;
+use winit::event::Event;
+
+use crate::input::actions;
+use crate::pass::*;
+use crate::render_graph::*;
+use crate::state::*;
+use crate::ui::{pause_ui, PauseEguiState};
+
+// Pause menu drawn over the states below it, which stop updating while it is shown.
+#[derive(Debug)]
+pub(super) struct PauseState {
+    pause_egui_state: PauseEguiState,
+    render_graph: RenderGraph,
+    pause_egui_pass: EguiPass<PauseEguiState>,
+}
+impl PauseState {
+    pub(super) const CROSSFADE: Duration = Duration::from_millis(150);
+
+    pub(super) fn new(context: &StateContext) -> Self {
+        let pause_egui_state = PauseEguiState::new();
+
+        let render_graph = RenderGraph::new().with_profiler(context.profiler.clone());
+        let pause_egui_pass = EguiPass::new(
+            &context.gpu.device,
+            &context.shaders,
+            &context.gpu.config,
+            context.size,
+            context.scale_factor,
+            pause_ui,
+        );
+
+        Self {
+            pause_egui_state,
+            render_graph,
+            pause_egui_pass,
+        }
+    }
+}
+impl StateTrait for PauseState {
+    fn update(
+        &mut self,
+        context: &mut StateContext,
+        _delta_time: f32,
+        _alpha: f32,
+    ) -> Option<StateTransition> {
+        self.pause_egui_pass.update();
+        let (wants_pointer, wants_keyboard) = self.pause_egui_pass.wants_input();
+        context.input.set_ui_capture(wants_pointer, wants_keyboard);
+        // The pause action resumes as well.
+        if std::mem::take(&mut self.pause_egui_state.resume)
+            || context.input.just_pressed(actions::PAUSE)
+        {
+            Some(StateTransition::pop())
+        } else {
+            None
+        }
+    }
+
+    fn handle_event(&mut self, _context: &StateContext, winit_event: &Event<()>) {
+        self.pause_egui_pass.handle_event(winit_event);
+    }
+
+    fn resize(&mut self, context: &StateContext) {
+        self.pause_egui_pass
+            .resize(context.size, context.scale_factor);
+    }
+
+    fn recreate(&mut self, context: &StateContext) {
+        self.pause_egui_pass.recreate(
+            &context.gpu.device,
+            &context.gpu.queue,
+            &context.shaders,
+            context.gpu.config.format,
+        );
+    }
+
+    fn cursor_icon(&self) -> egui::CursorIcon {
+        self.pause_egui_pass.cursor_icon()
+    }
+
+    fn is_ready(&self, _context: &StateContext) -> bool {
+        false
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn render(&mut self, context: &StateContext, view: &wgpu::TextureView) {
+        let mut passes: [&mut dyn RenderGraphPass<PauseEguiState>; 1] = [&mut self.pause_egui_pass];
+        if let Err(e) = self.render_graph.execute(
+            &context.gpu.device,
+            &context.gpu.queue,
+            &context.gpu.config,
+            view,
+            &mut passes,
+            &mut self.pause_egui_state,
+        ) {
+            log::error!("Failed to execute render graph: {}", e);
+        }
+    }
+}
@@ -1,49 +1,323 @@
+use gpu_context::GpuContextError;
+
+use crate::frame_clock::FrameClock;
+use crate::input::actions;
+use crate::pass::CrossfadePass;
+use crate::profiler::Profiler;
+use crate::render_target::*;
+use crate::renderer_config::RendererConfig;
 use crate::resources::*;
+use crate::settings::UserSettings;
 use crate::state::*;
 
 pub(super) trait StateTrait: std::fmt::Debug {
-    fn update(self: Box<Self>) -> Box<dyn StateTrait + Send>;
-    fn handle_event(&mut self, winit_event: &winit::event::Event<()>);
-    fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>);
-    fn size(&self) -> winit::dpi::PhysicalSize<u32>;
-    fn render(&mut self) -> Result<(), wgpu::SurfaceError>;
+    // Only the top state is updated and receives events.
+    // Simulation steps with a fixed delta time, called zero or more times before `update`.
+    fn fixed_update(&mut self, _context: &mut StateContext, _delta_time: f32) {}
+    // Called once per frame, `alpha` interpolates between the last two fixed updates.
+    fn update(
+        &mut self,
+        context: &mut StateContext,
+        delta_time: f32,
+        alpha: f32,
+    ) -> Option<StateTransition>;
+    fn handle_event(&mut self, context: &StateContext, winit_event: &winit::event::Event<()>);
+    // Called for every state on the stack after the context was resized.
+    fn resize(&mut self, context: &StateContext);
+    // Called when the state is on top again after the states above it were popped.
+    fn resume(&mut self, _context: &StateContext) {}
+    fn is_ready(&self, context: &StateContext) -> bool;
+    // Overlays render on top of the states below them instead of replacing them.
+    fn is_overlay(&self) -> bool {
+        false
+    }
+    // Called for every state on the stack after the device was lost and recreated,
+    // and after shaders were reloaded.
+    fn recreate(&mut self, context: &StateContext);
+    fn set_view_state(&mut self, _view_state: MainStateViewState) {}
+    // Cursor the window shows while the state is on top.
+    fn cursor_icon(&self) -> egui::CursorIcon {
+        egui::CursorIcon::Default
+    }
+    fn render(&mut self, context: &StateContext, view: &wgpu::TextureView);
 }
 
+// Stack of states sharing one GPU context.
 #[derive(Debug)]
 pub struct State {
-    state: Option<Box<dyn StateTrait + Send>>,
+    context: StateContext,
+    states: Vec<Box<dyn StateTrait + Send>>,
+    crossfade: Option<Crossfade>,
+    clock: FrameClock,
 }
 impl State {
     pub async fn new(
         size: winit::dpi::PhysicalSize<u32>,
+        scale_factor: f64,
         instance: wgpu::Instance,
         surface: wgpu::Surface,
         resources_loader: ResourcesLoader,
-    ) -> Self {
+        settings: UserSettings,
+    ) -> Result<Self, GpuContextError> {
+        let context = StateContext::new(
+            size,
+            scale_factor,
+            instance,
+            Some(surface),
+            resources_loader,
+            settings.renderer.renderer_config(),
+        )
+        .await?
+        .with_settings(settings);
+        Ok(Self::with_context(context))
+    }
+
+    // State rendering into an offscreen texture instead of a window surface.
+    pub async fn new_headless(
+        size: winit::dpi::PhysicalSize<u32>,
+        instance: wgpu::Instance,
+        resources_loader: ResourcesLoader,
+        renderer_config: RendererConfig,
+    ) -> Result<Self, GpuContextError> {
+        let context =
+            StateContext::new(size, 1.0, instance, None, resources_loader, renderer_config).await?;
+        Ok(Self::with_context(context))
+    }
+
+    fn with_context(context: StateContext) -> Self {
+        let loading_state = LoadingState::new(&context);
         Self {
-            state: Some(Box::new(
-                LoadingState::new(size, instance, surface, resources_loader).await,
-            )),
+            context,
+            states: vec![Box::new(loading_state)],
+            crossfade: None,
+            clock: FrameClock::default(),
         }
     }
 
     pub fn update(&mut self) {
-        self.state = Some(self.state.take().unwrap().update());
+        self.context.profiler.begin_frame();
+        let _scope = self.context.profiler.scope("Update");
+        self.context.input.poll_gamepads();
+        if self.context.shaders.poll_changes() {
+            for state in &mut self.states {
+                state.recreate(&self.context);
+            }
+        }
+        // Sequences step a fixed frame time so the recording plays back at its frame rate.
+        let tick = match self.context.capture.begin_frame() {
+            Some(frame_time) => self.clock.advance(frame_time),
+            None => self.clock.tick(),
+        };
+        let fixed_delta = self.clock.fixed_delta.as_secs_f32();
+        let transition = self.states.last_mut().and_then(|state| {
+            {
+                let _scope = self.context.profiler.scope("Fixed Update");
+                for _ in 0..tick.fixed_steps {
+                    state.fixed_update(&mut self.context, fixed_delta);
+                }
+            }
+            state.update(&mut self.context, tick.delta_time, tick.alpha)
+        });
+        self.context.input.end_frame();
+        if let Some(transition) = transition {
+            self.apply(transition);
+        }
+    }
+
+    fn apply(&mut self, transition: StateTransition) {
+        // Keep the last frame of the outgoing stack to fade from.
+        self.crossfade = transition.crossfade.map(|duration| {
+            let pass = CrossfadePass::new(
+                &self.context.gpu.device,
+                &self.context.shaders,
+                &self.context.gpu.config,
+            );
+            Self::render_states(&mut self.states, &self.context, pass.outgoing_view());
+            Crossfade::new(pass, duration)
+        });
+
+        match transition.op {
+            StateOp::Push(state) => self.states.push(state),
+            StateOp::Pop => {
+                self.states.pop();
+                if let Some(state) = self.states.last_mut() {
+                    state.resume(&self.context);
+                }
+            }
+            StateOp::Replace(state) => {
+                self.states.pop();
+                self.states.push(state);
+            }
+        }
+        if self.states.is_empty() {
+            log::warn!("The state stack is empty, nothing will be rendered.");
+        }
     }
 
     pub fn handle_event(&mut self, winit_event: &winit::event::Event<()>) {
-        self.state.as_mut().unwrap().handle_event(winit_event)
+        if let winit::event::Event::WindowEvent {
+            event: winit::event::WindowEvent::ScaleFactorChanged { scale_factor, .. },
+            ..
+        } = winit_event
+        {
+            // The size may stay the same, `resize` only follows when it changes.
+            self.context.scale_factor = *scale_factor;
+            for state in &mut self.states {
+                state.resize(&self.context);
+            }
+        }
+        self.context.input.handle_event(winit_event);
+        if let Some(state) = self.states.last_mut() {
+            state.handle_event(&self.context, winit_event);
+        }
     }
 
     pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
-        self.state.as_mut().unwrap().resize(new_size)
+        if self.context.resize(new_size) {
+            for state in &mut self.states {
+                state.resize(&self.context);
+            }
+        }
+    }
+
+    pub fn is_quit_requested(&self) -> bool {
+        self.context.input.is_pressed(actions::QUIT)
+    }
+
+    // Whether the device was lost and `recover` has to be called before rendering again.
+    pub fn is_lost(&self) -> bool {
+        self.context.gpu.is_lost()
+    }
+
+    // Recreate the device and every GPU object of the states without reloading the resources.
+    pub async fn recover(&mut self) -> Result<(), GpuContextError> {
+        log::warn!("Recreating the lost GPU device.");
+        self.context.recreate_device().await?;
+        // The outgoing frame belonged to the old device.
+        self.crossfade = None;
+        for state in &mut self.states {
+            state.recreate(&self.context);
+        }
+        Ok(())
+    }
+
+    // CPU and GPU timings of the last frames.
+    pub fn profiler(&self) -> &Profiler {
+        &self.context.profiler
     }
 
     pub fn size(&self) -> winit::dpi::PhysicalSize<u32> {
-        self.state.as_ref().unwrap().size()
+        self.context.size
+    }
+
+    pub fn cursor_icon(&self) -> egui::CursorIcon {
+        self.states
+            .last()
+            .map(|state| state.cursor_icon())
+            .unwrap_or(egui::CursorIcon::Default)
+    }
+
+    // Whether all resources are loaded, the main state is shown and no transition is running.
+    pub fn is_ready(&self) -> bool {
+        let transitioning = self
+            .crossfade
+            .as_ref()
+            .map(|crossfade| !crossfade.is_finished())
+            .unwrap_or(false);
+        !transitioning
+            && self
+                .states
+                .last()
+                .map(|state| state.is_ready(&self.context))
+                .unwrap_or(false)
+    }
+
+    pub fn set_view_state(&mut self, view_state: MainStateViewState) {
+        for state in &mut self.states {
+            state.set_view_state(view_state.clone());
+        }
+    }
+
+    // Frames read back from the offscreen target, empty when rendering to a surface.
+    pub fn offscreen_frames(&mut self, wait: bool) -> Vec<OffscreenFrame> {
+        self.context.target.frames(&self.context.gpu.device, wait)
+    }
+
+    // Render the top state and the overlays above the topmost opaque state, bottom to top.
+    fn render_states(
+        states: &mut [Box<dyn StateTrait + Send>],
+        context: &StateContext,
+        view: &wgpu::TextureView,
+    ) {
+        let first = states
+            .iter()
+            .rposition(|state| !state.is_overlay())
+            .unwrap_or(0);
+        for state in &mut states[first..] {
+            state.render(context, view);
+        }
     }
 
     pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
-        self.state.as_mut().unwrap().render()
+        let _scope = self.context.profiler.scope("Render");
+        let frame = {
+            let _scope = self.context.profiler.scope("Acquire");
+            self.context.target.acquire(&self.context.gpu)?
+        };
+
+        // The outgoing frame is lost when the surface changed, finish the crossfade early.
+        if let Some(crossfade) = &self.crossfade {
+            if crossfade.is_finished() || !crossfade.pass.matches(&self.context.gpu.config) {
+                self.crossfade = None;
+            }
+        }
+        // Captured frames are rendered into the capture target and shown from there.
+        let capture = self.context.capture.next_frame().map(|name| {
+            let view = self.context.capture.view(
+                &self.context.gpu.device,
+                &self.context.shaders,
+                &self.context.gpu.config,
+            );
+            (name, view)
+        });
+        let view = match &capture {
+            Some((_, view)) => view,
+            None => &frame.view,
+        };
+        match &self.crossfade {
+            Some(crossfade) => {
+                Self::render_states(
+                    &mut self.states,
+                    &self.context,
+                    crossfade.pass.incoming_view(),
+                );
+                crossfade.pass.draw(
+                    &self.context.gpu.device,
+                    &self.context.gpu.queue,
+                    view,
+                    crossfade.progress(),
+                );
+            }
+            None => Self::render_states(&mut self.states, &self.context, view),
+        }
+        if let Some((name, _)) = capture {
+            let _scope = self.context.profiler.scope("Capture");
+            self.context.capture.present(
+                &self.context.gpu.device,
+                &self.context.gpu.queue,
+                &frame.view,
+                name,
+            );
+        }
+        self.context
+            .capture
+            .save_frames(&self.context.gpu.device, false);
+
+        {
+            let _scope = self.context.profiler.scope("Present");
+            self.context.target.present(frame, &self.context.gpu);
+        }
+
+        Ok(())
     }
 }
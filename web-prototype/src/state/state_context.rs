@@ -0,0 +1,119 @@
+use gpu_context::{GpuContext, GpuContextError};
+use shader_library::ShaderLibrary;
+
+use crate::capture::FrameCapture;
+use crate::input::*;
+use crate::profiler::Profiler;
+use crate::render_target::*;
+use crate::renderer_config::RendererConfig;
+use crate::resources::*;
+use crate::settings::UserSettings;
+
+// GPU context and resource loader shared by every state on the stack.
+#[derive(Debug)]
+pub(super) struct StateContext {
+    pub(super) gpu: GpuContext,
+    pub(super) target: RenderTarget,
+    pub(super) capture: FrameCapture,
+    // Renderer config the device and surface were created with.
+    pub(super) renderer_config: RendererConfig,
+    pub(super) size: winit::dpi::PhysicalSize<u32>,
+    // Physical pixels per logical pixel of the window.
+    pub(super) scale_factor: f64,
+    pub(super) input: Input,
+    pub(super) profiler: Profiler,
+    pub(super) shaders: ShaderLibrary,
+    // Restored by the main state and saved when they change, `None` in headless mode.
+    pub(super) settings: Option<UserSettings>,
+
+    pub(super) resources_loader: ResourcesLoader,
+}
+impl StateContext {
+    pub(super) async fn new(
+        size: winit::dpi::PhysicalSize<u32>,
+        scale_factor: f64,
+        instance: wgpu::Instance,
+        surface: Option<wgpu::Surface>,
+        resources_loader: ResourcesLoader,
+        renderer_config: RendererConfig,
+    ) -> Result<Self, GpuContextError> {
+        let renderer_config = renderer_config.validate();
+        let headless = surface.is_none();
+        // Prefer an sRGB surface so the hardware encodes the linear output of the passes.
+        // Without one (e.g. WebGPU canvases) the post process pass encodes sRGB itself.
+        let gpu = GpuContext::new(
+            instance,
+            surface,
+            size.width,
+            size.height,
+            &renderer_config.descriptor(),
+        )
+        .await?;
+        let target = if headless {
+            RenderTarget::Offscreen(OffscreenTarget::new(&gpu.device, &gpu.config))
+        } else {
+            RenderTarget::Surface
+        };
+        // Headless rendering has nobody to read gamepads from.
+        let input = Input::new(InputBindings::load());
+        let input = if headless {
+            input
+        } else {
+            input.with_gamepads()
+        };
+
+        let renderer_config = RendererConfig {
+            features: gpu.device.features(),
+            limits: gpu.device.limits(),
+            ..renderer_config
+        };
+
+        Ok(Self {
+            gpu,
+            target,
+            capture: FrameCapture::new(),
+            renderer_config,
+            size,
+            scale_factor,
+            input,
+            profiler: Profiler::new(),
+            // Headless frames should not depend on files edited while rendering.
+            shaders: ShaderLibrary::new().with_hot_reload(!headless),
+            settings: None,
+
+            resources_loader,
+        })
+    }
+
+    pub(super) fn with_settings(self, settings: UserSettings) -> Self {
+        Self {
+            settings: Some(settings),
+            ..self
+        }
+    }
+
+    // Request a new device after the old one was lost.
+    // The loaded resources are kept on the CPU side, only GPU objects are recreated.
+    pub(super) async fn recreate_device(&mut self) -> Result<(), GpuContextError> {
+        self.gpu
+            .recreate_device(&self.renderer_config.descriptor())
+            .await?;
+        if let RenderTarget::Offscreen(_) = self.target {
+            self.target =
+                RenderTarget::Offscreen(OffscreenTarget::new(&self.gpu.device, &self.gpu.config));
+        }
+        self.capture.recreate();
+        Ok(())
+    }
+
+    // Returns false for an empty size, e.g. a minimized window.
+    pub(super) fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) -> bool {
+        if self.gpu.resize(new_size.width, new_size.height) {
+            self.size = new_size;
+            self.target.configure(&self.gpu);
+            true
+        } else {
+            false
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use instant::{Duration, Instant};
+
+use crate::pass::CrossfadePass;
+use crate::state::*;
+
+#[derive(Debug)]
+pub(super) enum StateOp {
+    Push(Box<dyn StateTrait + Send>),
+    Pop,
+    Replace(Box<dyn StateTrait + Send>),
+}
+
+// Change of the state stack requested by the top state.
+#[derive(Debug)]
+pub(super) struct StateTransition {
+    pub(super) op: StateOp,
+    // Crossfade from the last frame of the outgoing stack, cut when `None`.
+    pub(super) crossfade: Option<Duration>,
+}
+impl StateTransition {
+    // Put a state on top, the states below stop updating until it is popped.
+    pub(super) fn push(state: Box<dyn StateTrait + Send>) -> Self {
+        Self {
+            op: StateOp::Push(state),
+            crossfade: None,
+        }
+    }
+
+    pub(super) fn pop() -> Self {
+        Self {
+            op: StateOp::Pop,
+            crossfade: None,
+        }
+    }
+
+    // Replace the top state, e.g. once loading finished.
+    pub(super) fn replace(state: Box<dyn StateTrait + Send>) -> Self {
+        Self {
+            op: StateOp::Replace(state),
+            crossfade: None,
+        }
+    }
+
+    pub(super) fn with_crossfade(self, duration: Duration) -> Self {
+        Self {
+            crossfade: Some(duration),
+            ..self
+        }
+    }
+}
+
+// Crossfade in progress, the outgoing frame is already rendered into the pass.
+#[derive(Debug)]
+pub(super) struct Crossfade {
+    pub(super) pass: CrossfadePass,
+    start: Instant,
+    duration: Duration,
+}
+impl Crossfade {
+    pub(super) fn new(pass: CrossfadePass, duration: Duration) -> Self {
+        Self {
+            pass,
+            start: Instant::now(),
+            duration,
+        }
+    }
+
+    pub(super) fn progress(&self) -> f32 {
+        if self.duration.is_zero() {
+            1.0
+        } else {
+            ((Instant::now() - self.start).as_secs_f32() / self.duration.as_secs_f32()).min(1.0)
+        }
+    }
+
+    pub(super) fn is_finished(&self) -> bool {
+        self.progress() >= 1.0
+    }
+}
@@ -0,0 +1,11 @@
+mod loading_ui;
+mod main_ui;
+mod pause_ui;
+mod resource_inspector;
+mod scene_ui;
+
+pub use loading_ui::{loading_ui, LoadingEguiState};
+pub use main_ui::main_ui;
+pub use pause_ui::{pause_ui, PauseEguiState};
+pub use resource_inspector::{resource_inspector, ResourceInspectorState, TexturePreview};
+pub use scene_ui::scene_ui;
@@ -0,0 +1,42 @@
+#[derive(Debug)]
+pub struct LoadingEguiState {
+    pub load_progress: f32,
+    // Shown instead of the progress once a required resource failed to load.
+    pub error: Option<String>,
+}
+impl LoadingEguiState {
+    pub fn new() -> Self {
+        Self {
+            load_progress: 0.0,
+            error: None,
+        }
+    }
+}
+
+pub fn loading_ui(ctx: &egui::CtxRef, egui_state: &mut LoadingEguiState) {
+    egui::CentralPanel::default()
+        .frame(
+            egui::Frame::default()
+                .fill(egui::Color32::from_rgb(10, 10, 10))
+                .margin([100.0, 100.0]),
+        )
+        .show(ctx, |ui| {
+            ui.with_layout(
+                egui::Layout::bottom_up(egui::Align::Center),
+                |ui| match &egui_state.error {
+                    Some(error) => {
+                        ui.colored_label(egui::Color32::from_rgb(255, 96, 96), error);
+                        ui.label("Loading failed, reload to try again.");
+                    }
+                    None => {
+                        ui.add(
+                            egui::ProgressBar::new(egui_state.load_progress)
+                                .animate(true)
+                                .show_percentage(),
+                        );
+                        ui.label("Now Loading...");
+                    }
+                },
+            );
+        });
+}
@@ -0,0 +1,311 @@
+use gpu_context::srgb_counterpart;
+
+use crate::camera::CameraControllerKind;
+use crate::capture::CaptureRequest;
+use crate::pass::{ColorGradingSettings, TonemapOperator};
+use crate::profiler::*;
+use crate::renderer_config::RendererConfig;
+use crate::resources::hash;
+use crate::state::*;
+use crate::ui::{resource_inspector, scene_ui};
+
+pub fn main_ui(ctx: &egui::CtxRef, scene: &mut MainStateScene) {
+    let view_state = &mut scene.view_state;
+
+    match view_state.clone() {
+        MainStateViewState::Triangle { label, clear_color } => {
+            egui::Window::new("Test")
+                .resizable(true)
+                .scroll2([true, true])
+                .show(ctx, |ui| {
+                    egui::ComboBox::from_id_source("combo")
+                        .selected_text(label)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(
+                                view_state,
+                                MainStateViewState::triangle(),
+                                "Triangle",
+                            );
+                            ui.selectable_value(
+                                view_state,
+                                MainStateViewState::texture(),
+                                "Texture",
+                            );
+                            ui.selectable_value(view_state, MainStateViewState::mesh(), "Mesh");
+                        });
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.label("Scene Clear Color");
+                        let mut hsva = egui::color::Hsva::from_rgba_premultiplied(
+                            clear_color.r,
+                            clear_color.g,
+                            clear_color.b,
+                            clear_color.a,
+                        );
+                        egui::color_picker::color_edit_button_hsva(
+                            ui,
+                            &mut hsva,
+                            egui::color_picker::Alpha::OnlyBlend,
+                        );
+                        if let MainStateViewState::Triangle { clear_color, .. } = view_state {
+                            *clear_color = vek::Rgba::from(hsva.to_rgba_premultiplied());
+                        }
+                    });
+                });
+        }
+        MainStateViewState::Texture { label } => {
+            egui::Window::new("Test")
+                .resizable(true)
+                .scroll2([true, true])
+                .show(ctx, |ui| {
+                    egui::ComboBox::from_id_source("combo")
+                        .selected_text(label)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(
+                                view_state,
+                                MainStateViewState::triangle(),
+                                "Triangle",
+                            );
+                            ui.selectable_value(
+                                view_state,
+                                MainStateViewState::texture(),
+                                "Texture",
+                            );
+                            ui.selectable_value(view_state, MainStateViewState::mesh(), "Mesh");
+                        });
+                    ui.label("Base color texture");
+                    // Registered with the egui pass by the main state once it is loaded.
+                    ui.image(
+                        egui::TextureId::User(hash("room_tex_base_color")),
+                        [256.0, 256.0],
+                    );
+                });
+        }
+        MainStateViewState::Mesh { label, controller } => {
+            egui::Window::new("Test")
+                .resizable(true)
+                .scroll2([true, true])
+                .show(ctx, |ui| {
+                    egui::ComboBox::from_id_source("combo")
+                        .selected_text(label)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(
+                                view_state,
+                                MainStateViewState::triangle(),
+                                "Triangle",
+                            );
+                            ui.selectable_value(
+                                view_state,
+                                MainStateViewState::texture(),
+                                "Texture",
+                            );
+                            ui.selectable_value(view_state, MainStateViewState::mesh(), "Mesh");
+                        });
+                    ui.separator();
+                    let mut selected = controller;
+                    ui.horizontal(|ui| {
+                        ui.label("Camera");
+                        ui.radio_value(&mut selected, CameraControllerKind::Orbit, "Orbit");
+                        ui.radio_value(&mut selected, CameraControllerKind::Fly, "Fly");
+                    });
+                    match selected {
+                        CameraControllerKind::Orbit => {
+                            ui.label("Left drag to rotate, wheel to zoom");
+                        }
+                        CameraControllerKind::Fly => {
+                            ui.label("WASD/QE to move, right drag to look");
+                        }
+                    }
+                    if let MainStateViewState::Mesh { controller, .. } = view_state {
+                        *controller = selected;
+                    }
+                });
+        }
+    }
+
+    let tonemap = &mut scene.tonemap;
+    let post_process = &mut scene.post_process;
+    egui::Window::new("Post Processing")
+        .resizable(true)
+        .scroll2([false, true])
+        .show(ctx, |ui| {
+            ui.collapsing("Tonemapping", |ui| {
+                egui::ComboBox::from_label("Operator")
+                    .selected_text(tonemap.operator.label())
+                    .show_ui(ui, |ui| {
+                        for operator in TonemapOperator::ALL {
+                            ui.selectable_value(&mut tonemap.operator, operator, operator.label());
+                        }
+                    });
+                ui.add(egui::Slider::new(&mut tonemap.exposure, -4.0..=4.0).text("Exposure (EV)"));
+            });
+            ui.collapsing("Bloom", |ui| {
+                let bloom = &mut post_process.bloom;
+                ui.checkbox(&mut bloom.enabled, "Enabled");
+                ui.add(egui::Slider::new(&mut bloom.threshold, 0.0..=4.0).text("Threshold"));
+                ui.add(egui::Slider::new(&mut bloom.knee, 0.0..=1.0).text("Knee"));
+                ui.add(egui::Slider::new(&mut bloom.intensity, 0.0..=1.0).text("Intensity"));
+                ui.add(egui::Slider::new(&mut bloom.radius, 0.5..=4.0).text("Radius"));
+            });
+            ui.collapsing("FXAA", |ui| {
+                ui.checkbox(&mut post_process.fxaa, "Enabled");
+            });
+            ui.collapsing("Vignette", |ui| {
+                let vignette = &mut post_process.vignette;
+                ui.checkbox(&mut vignette.enabled, "Enabled");
+                ui.add(egui::Slider::new(&mut vignette.intensity, 0.0..=1.0).text("Intensity"));
+                ui.add(egui::Slider::new(&mut vignette.radius, 0.0..=1.0).text("Radius"));
+                ui.add(egui::Slider::new(&mut vignette.smoothness, 0.0..=1.0).text("Smoothness"));
+            });
+            ui.collapsing("Color Grading", |ui| {
+                let color_grading = &mut post_process.color_grading;
+                ui.checkbox(&mut color_grading.enabled, "Enabled");
+                let selected = ColorGradingSettings::LUTS
+                    .iter()
+                    .find(|(_, hash)| *hash == color_grading.lut)
+                    .map(|(label, _)| *label)
+                    .unwrap_or("Unknown");
+                egui::ComboBox::from_label("LUT")
+                    .selected_text(selected)
+                    .show_ui(ui, |ui| {
+                        for (label, hash) in ColorGradingSettings::LUTS {
+                            ui.selectable_value(&mut color_grading.lut, hash, label);
+                        }
+                    });
+                ui.add(egui::Slider::new(&mut color_grading.strength, 0.0..=1.0).text("Strength"));
+            });
+        });
+
+    let renderer = &mut scene.renderer;
+    let format = scene.surface_format;
+    egui::Window::new("Renderer")
+        .resizable(true)
+        .scroll2([false, true])
+        .show(ctx, |ui| {
+            egui::ComboBox::from_label("MSAA")
+                .selected_text(format!("{}x", renderer.sample_count))
+                .show_ui(ui, |ui| {
+                    for sample_count in RendererConfig::SAMPLE_COUNTS {
+                        ui.selectable_value(
+                            &mut renderer.sample_count,
+                            sample_count,
+                            format!("{}x", sample_count),
+                        );
+                    }
+                });
+            egui::ComboBox::from_label("Present Mode")
+                .selected_text(format!("{:?}", renderer.present_mode))
+                .show_ui(ui, |ui| {
+                    for present_mode in RendererConfig::PRESENT_MODES {
+                        ui.selectable_value(
+                            &mut renderer.present_mode,
+                            present_mode,
+                            format!("{:?}", present_mode),
+                        );
+                    }
+                });
+            let format_label = |format: Option<wgpu::TextureFormat>| match format {
+                Some(format) => format!("{:?}", format),
+                None => "Preferred".to_string(),
+            };
+            egui::ComboBox::from_label("Surface Format")
+                .selected_text(format_label(renderer.format))
+                .show_ui(ui, |ui| {
+                    let mut formats = vec![None, Some(format)];
+                    formats.extend(srgb_counterpart(format).map(Some));
+                    for option in formats {
+                        ui.selectable_value(&mut renderer.format, option, format_label(option));
+                    }
+                });
+            ui.checkbox(&mut scene.show_profiler, "Show Profiler (F3)");
+            ui.checkbox(&mut scene.inspector.open, "Show Resource Inspector");
+            ui.checkbox(&mut scene.show_scene, "Show Scene");
+            ui.collapsing("Capture", |ui| {
+                let capture = &mut scene.capture;
+                if ui.button("Screenshot (F2)").clicked() {
+                    capture.request = Some(CaptureRequest::Screenshot);
+                }
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(&mut capture.frames).clamp_range(1..=3600));
+                    ui.label("frames at");
+                    ui.add(egui::DragValue::new(&mut capture.fps).clamp_range(1..=240));
+                    ui.label("fps");
+                });
+                if capture.remaining_frames > 0 {
+                    ui.label(format!(
+                        "Recording, {} frames left",
+                        capture.remaining_frames
+                    ));
+                } else if ui.button("Record Sequence").clicked() {
+                    capture.request = Some(CaptureRequest::Sequence {
+                        frames: capture.frames,
+                        fps: capture.fps,
+                    });
+                }
+            });
+        });
+
+    resource_inspector(ctx, &mut scene.inspector, &scene.resources, &scene.catalog);
+    if scene.show_scene {
+        scene_ui(ctx, &mut scene.scene_graph, &scene.camera);
+    }
+
+    if scene.show_profiler {
+        let profiler = &scene.profiler;
+        egui::Window::new("Profiler")
+            .resizable(true)
+            .scroll2([false, true])
+            .show(ctx, |ui| {
+                let frames = profiler.frames();
+                ui.horizontal(|ui| {
+                    let mut enabled = profiler.is_enabled();
+                    if ui.checkbox(&mut enabled, "Record").changed() {
+                        profiler.set_enabled(enabled);
+                    }
+                    if ui.button("Clear").clicked() {
+                        profiler.clear();
+                    }
+                    if ui.button("Export Chrome Trace").clicked() {
+                        save_chrome_trace(&chrome_trace(&frames));
+                    }
+                });
+                ui.label(format!("{} frames", frames.len()));
+                ui.label(FrameStats::new(&frames).to_string());
+
+                let frame_times = frames
+                    .iter()
+                    .enumerate()
+                    .map(|(i, frame)| egui::plot::Value::new(i as f64, frame.duration / 1000.0));
+                egui::plot::Plot::new("frame_times")
+                    .height(80.0)
+                    .include_y(0.0)
+                    .allow_drag(false)
+                    .allow_zoom(false)
+                    .show(ui, |plot_ui| {
+                        plot_ui.line(egui::plot::Line::new(egui::plot::Values::from_values_iter(
+                            frame_times,
+                        )));
+                    });
+
+                let format_ms = |ms: Option<f64>| match ms {
+                    Some(ms) => format!("{:.3} ms", ms),
+                    None => "-".to_string(),
+                };
+                egui::Grid::new("profiler_scopes")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("Scope");
+                        ui.strong("CPU");
+                        ui.strong("GPU");
+                        ui.end_row();
+                        // Top level scopes and the passes right below them.
+                        for scope in ScopeStats::new(&frames, 1) {
+                            ui.label(scope.name);
+                            ui.label(format_ms(scope.cpu));
+                            ui.label(format_ms(scope.gpu));
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+}
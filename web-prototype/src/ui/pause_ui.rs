@@ -0,0 +1,29 @@
+#[derive(Debug)]
+pub struct PauseEguiState {
+    // Set when the resume button was clicked.
+    pub resume: bool,
+}
+impl PauseEguiState {
+    pub fn new() -> Self {
+        Self { resume: false }
+    }
+}
+
+pub fn pause_ui(ctx: &egui::CtxRef, egui_state: &mut PauseEguiState) {
+    // Dim the states below the overlay.
+    egui::CentralPanel::default()
+        .frame(egui::Frame::default().fill(egui::Color32::from_black_alpha(160)))
+        .show(ctx, |_ui| {});
+    egui::Window::new("Paused")
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                if ui.button("Resume").clicked() {
+                    egui_state.resume = true;
+                }
+                ui.label("Press P to resume.");
+            });
+        });
+}
@@ -0,0 +1,222 @@
+use crate::resources::*;
+
+// Selection of the resource inspector window.
+// The main state uploads the texture preview for it as `PREVIEW_TEXTURE`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResourceInspectorState {
+    pub open: bool,
+    pub selected: Option<u64>,
+    pub channel: TextureChannel,
+    pub mip_level: u32,
+    pub zoom: f32,
+}
+impl ResourceInspectorState {
+    pub const PREVIEW_TEXTURE: u64 = hash("resource_inspector_preview");
+
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            selected: None,
+            channel: TextureChannel::Rgba,
+            mip_level: 0,
+            zoom: 1.0,
+        }
+    }
+
+    // Preview of the selected texture, `None` while the inspector does not show one.
+    pub fn texture_preview(&self, resources: &Resources) -> Option<TexturePreview> {
+        let texture = resources.try_texture(self.selected?)?;
+        Some(TexturePreview {
+            texture: texture.hash,
+            channel: self.channel,
+            mip_level: self.mip_level.min(texture.mip_level_count() - 1),
+            loaded: resources.is_texture_loaded(texture.hash),
+        })
+    }
+}
+
+// Texture preview the inspector shows, uploaded again whenever it changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TexturePreview {
+    pub texture: u64,
+    pub channel: TextureChannel,
+    pub mip_level: u32,
+    // The placeholder is replaced once the texture streamed in.
+    pub loaded: bool,
+}
+
+pub fn resource_inspector(
+    ctx: &egui::CtxRef,
+    inspector: &mut ResourceInspectorState,
+    resources: &Resources,
+    catalog: &ResourcesCatalog,
+) {
+    if !inspector.open {
+        return;
+    }
+    egui::Window::new("Resource Inspector")
+        .resizable(true)
+        .scroll2([false, true])
+        .show(ctx, |ui| {
+            let infos = resources.infos(catalog);
+            let memory_size = infos.iter().map(|info| info.memory_size).sum();
+            ui.label(format!(
+                "{} resources, {}",
+                infos.len(),
+                format_bytes(memory_size)
+            ));
+            egui::ScrollArea::vertical()
+                .max_height(240.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("resource_inspector_resources")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.strong("Name");
+                            ui.strong("Kind");
+                            ui.strong("Size");
+                            ui.strong("Memory");
+                            ui.strong("Load Time");
+                            ui.end_row();
+                            for info in &infos {
+                                let selected = inspector.selected == Some(info.hash);
+                                if ui.selectable_label(selected, info.name()).clicked() {
+                                    inspector.selected = Some(info.hash);
+                                }
+                                ui.label(info.kind.label());
+                                ui.label(info.kind.size_label());
+                                ui.label(format_bytes(info.memory_size));
+                                ui.label(match info.load_time {
+                                    Some(load_time) => {
+                                        format!("{:.1} ms", load_time.as_secs_f64() * 1000.0)
+                                    }
+                                    None => "-".to_string(),
+                                });
+                                ui.end_row();
+                            }
+                        });
+                });
+
+            let selected = infos
+                .iter()
+                .find(|info| Some(info.hash) == inspector.selected);
+            if let Some(info) = selected {
+                ui.separator();
+                ui.strong(info.name());
+                match info.kind {
+                    ResourceKind::Mesh { .. } => mesh_details(ui, resources.mesh(info.hash)),
+                    ResourceKind::Material => {
+                        material_details(ui, inspector, resources.material(info.hash), catalog)
+                    }
+                    ResourceKind::Texture { .. } => {
+                        texture_details(ui, inspector, resources.texture(info.hash))
+                    }
+                    // The scene window shows the entities.
+                    ResourceKind::Scene { .. } => {}
+                }
+            }
+        });
+}
+
+fn mesh_details(ui: &mut egui::Ui, mesh: &Mesh) {
+    ui.label(format!(
+        "{} primitives, {} vertices, {} triangles",
+        mesh.primitives.len(),
+        mesh.vertex_count(),
+        mesh.index_count() / 3
+    ));
+    if let Some(bounds) = mesh.bounds() {
+        ui.label(format!(
+            "Bounds {} to {}",
+            format_vec3(bounds.min),
+            format_vec3(bounds.max)
+        ));
+    }
+    egui::Grid::new("resource_inspector_primitives")
+        .striped(true)
+        .show(ui, |ui| {
+            ui.strong("Primitive");
+            ui.strong("Vertices");
+            ui.strong("Indices");
+            ui.strong("Material");
+            ui.strong("Bounds Size");
+            ui.end_row();
+            for (i, primitive) in mesh.primitives.iter().enumerate() {
+                ui.label(i.to_string());
+                ui.label(primitive.vertices.len().to_string());
+                ui.label(primitive.indices.len().to_string());
+                ui.label(format!("{:016x}", primitive.material));
+                ui.label(match primitive.bounds() {
+                    Some(bounds) => format_vec3(bounds.max - bounds.min),
+                    None => "-".to_string(),
+                });
+                ui.end_row();
+            }
+        });
+}
+
+fn material_details(
+    ui: &mut egui::Ui,
+    inspector: &mut ResourceInspectorState,
+    material: &Material,
+    catalog: &ResourcesCatalog,
+) {
+    let slots = [
+        ("Base Color", material.base_color_texture),
+        ("Metallic", material.metallic_texture),
+        ("Roughness", material.roughness_texture),
+        ("Normal", material.normal_texture),
+    ];
+    egui::Grid::new("resource_inspector_material").show(ui, |ui| {
+        for (label, texture) in slots {
+            ui.label(label);
+            match texture {
+                Some(texture) => {
+                    let name = match catalog.path(texture) {
+                        Some(path) => path.display().to_string(),
+                        None => format!("{:016x}", texture),
+                    };
+                    // Jump to the texture to preview it.
+                    if ui.add(egui::Button::new(name).frame(false)).clicked() {
+                        inspector.selected = Some(texture);
+                    }
+                }
+                None => {
+                    ui.label("-");
+                }
+            }
+            ui.end_row();
+        }
+    });
+}
+
+fn texture_details(ui: &mut egui::Ui, inspector: &mut ResourceInspectorState, texture: &Texture) {
+    ui.horizontal(|ui| {
+        ui.label("Channel");
+        for channel in TextureChannel::ALL {
+            ui.selectable_value(&mut inspector.channel, channel, channel.label());
+        }
+    });
+    let max_mip_level = texture.mip_level_count() - 1;
+    ui.add(egui::Slider::new(&mut inspector.mip_level, 0..=max_mip_level).text("Mip"));
+    ui.add(
+        egui::Slider::new(&mut inspector.zoom, 0.125..=8.0)
+            .logarithmic(true)
+            .text("Zoom"),
+    );
+
+    let mip_level = inspector.mip_level.min(max_mip_level);
+    let size = [
+        (texture.width >> mip_level).max(1) as f32 * inspector.zoom,
+        (texture.height >> mip_level).max(1) as f32 * inspector.zoom,
+    ];
+    egui::ScrollArea::both().max_height(512.0).show(ui, |ui| {
+        ui.image(
+            egui::TextureId::User(ResourceInspectorState::PREVIEW_TEXTURE),
+            size,
+        );
+    });
+}
+
+fn format_vec3(v: vek::Vec3<f32>) -> String {
+    format!("({:.2}, {:.2}, {:.2})", v.x, v.y, v.z)
+}
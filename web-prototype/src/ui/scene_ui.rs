@@ -0,0 +1,63 @@
+use crate::camera::Camera;
+use crate::scene::*;
+
+// Entities of the scene graph with their transforms, saved with the current camera.
+pub fn scene_ui(ctx: &egui::CtxRef, scene_graph: &mut SceneGraph, camera: &Camera) {
+    egui::Window::new("Scene")
+        .resizable(true)
+        .scroll2([false, true])
+        .show(ctx, |ui| {
+            if ui.button("Save Scene").clicked() {
+                scene_graph.set_camera(camera);
+                match scene_graph.save().to_ron() {
+                    Ok(source) => save_scene("room.scene.ron", &source),
+                    Err(e) => log::warn!("{}", e),
+                }
+            }
+            ui.separator();
+            let roots = children(&scene_graph.nodes, None);
+            for index in roots {
+                node_ui(ui, &mut scene_graph.nodes, index);
+            }
+        });
+}
+
+fn children(nodes: &[SceneNode], parent: Option<usize>) -> Vec<usize> {
+    nodes
+        .iter()
+        .enumerate()
+        .filter(|(_, node)| node.parent == parent)
+        .map(|(index, _)| index)
+        .collect()
+}
+
+fn node_ui(ui: &mut egui::Ui, nodes: &mut [SceneNode], index: usize) {
+    let children = children(nodes, Some(index));
+    egui::CollapsingHeader::new(nodes[index].name.clone())
+        .id_source(index)
+        .show(ui, |ui| {
+            let node = &mut nodes[index];
+            if let Some(mesh) = &node.mesh {
+                ui.label(format!("Mesh: {}", mesh.name));
+            }
+            if let Some(material) = &node.material {
+                ui.label(format!("Material: {}", material.name));
+            }
+            let transform = &mut node.transform;
+            vec3_ui(ui, "Translation", &mut transform.translation, 0.01);
+            vec3_ui(ui, "Rotation", &mut transform.rotation, 1.0);
+            vec3_ui(ui, "Scale", &mut transform.scale, 0.01);
+            for child in children {
+                node_ui(ui, nodes, child);
+            }
+        });
+}
+
+fn vec3_ui(ui: &mut egui::Ui, label: &str, values: &mut [f32; 3], speed: f32) {
+    ui.horizontal(|ui| {
+        ui.label(label);
+        for value in values {
+            ui.add(egui::DragValue::new(value).speed(speed));
+        }
+    });
+}
@@ -1,15 +1,17 @@
+#include "color.wgsl"
+
 // Vertex shader
 
 struct VertexInput {
-    @location(0) position: vec2<f32>;
-};
+    @location(0) position: vec2<f32>,
+}
 
 struct VertexOutput {
-    @builtin(position) clip_position: vec4<f32>;
-    @location(0) position: vec2<f32>;
-};
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) position: vec2<f32>,
+}
 
-@stage(vertex)
+@vertex
 fn vs_main(
     model: VertexInput,
 ) -> VertexOutput {
@@ -22,13 +24,13 @@ fn vs_main(
 // Fragment shader
 
 struct UniformBuffer {
-    color: vec4<f32>;
+    color: vec4<f32>,
 }
 
 @group(0) @binding(0)
 var<uniform> uniform_buffer: UniformBuffer;
 
-@stage(fragment)
+@fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let x = floor(in.position.x * 8.0);
     let y = floor(in.position.y * 4.0);
@@ -45,5 +47,5 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let t = floor(in.position.x * 2.0);
     let rgb = mix(wo_alpha, w_alpha, t);
 
-    return vec4<f32>(pow(rgb, vec3<f32>(1.0 / 2.2)), 1.0);
+    return vec4<f32>(linear_to_srgb(rgb), 1.0);
 }
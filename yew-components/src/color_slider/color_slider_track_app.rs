@@ -1,5 +1,6 @@
 use num::traits::AsPrimitive;
 use num::Float;
+use shader_library::{shader_source, ShaderLibrary};
 use std::marker::PhantomData;
 use vek::ColorComponent;
 use wgpu::util::DeviceExt;
@@ -77,11 +78,7 @@ impl<T: Float + AsPrimitive<f32> + ColorComponent + bytemuck::Pod> Default
 }
 
 pub struct ColorSliderTrackApp<T: Float + AsPrimitive<f32> + ColorComponent + bytemuck::Pod> {
-    _instance: wgpu::Instance,
-    surface: wgpu::Surface,
-    device: wgpu::Device,
-    queue: wgpu::Queue,
-    config: wgpu::SurfaceConfiguration,
+    gpu: GpuContext,
     size: WgpuCanvasSize,
 
     vertex_buffer: wgpu::Buffer,
@@ -100,47 +97,27 @@ impl<T: Float + AsPrimitive<f32> + ColorComponent + bytemuck::Pod> WgpuCanvasApp
     type Props = ColorSliderTrackProps<T>;
 
     fn new(canvas_window: WgpuCanvasWindow) -> WgpuCanvasAppCreator<Self> {
-        WgpuCanvasAppCreator::new(async move {
+        WgpuCanvasAppCreator::try_new(async move {
             let size = *canvas_window.size();
 
-            let instance = wgpu::Instance::new(wgpu::Backends::BROWSER_WEBGPU);
-            let surface = unsafe { instance.create_surface(&canvas_window) };
-            let adapter = instance
-                .request_adapter(&wgpu::RequestAdapterOptions {
-                    power_preference: wgpu::PowerPreference::default(),
-                    compatible_surface: Some(&surface),
-                    force_fallback_adapter: false,
-                })
-                .await
-                .unwrap_or_else(|| panic!("Failed to request adapter."));
-
-            let (device, queue) = adapter
-                .request_device(
-                    &wgpu::DeviceDescriptor {
-                        features: wgpu::Features::empty(),
-                        limits: wgpu::Limits::default(),
-                        label: None,
-                    },
-                    None, // Trace path
-                )
-                .await
-                .unwrap_or_else(|_| panic!("Failed to request device."));
-
-            let config = wgpu::SurfaceConfiguration {
-                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-                format: wgpu::TextureFormat::Bgra8Unorm,
-                width: size.width,
-                height: size.height,
-                present_mode: wgpu::PresentMode::Fifo,
-            };
-            surface.configure(&device, &config);
+            let gpu = GpuContext::from_window(
+                &canvas_window,
+                size.width,
+                size.height,
+                &GpuContextDescriptor {
+                    backends: wgpu::Backends::BROWSER_WEBGPU,
+                    format: Some(wgpu::TextureFormat::Bgra8Unorm),
+                    ..Default::default()
+                },
+            )
+            .await?;
+            let device = &gpu.device;
 
-            let shader = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
-                label: Some("Shader"),
-                source: wgpu::ShaderSource::Wgsl(
-                    include_str!("color_slider_track_app.wgsl").into(),
-                ),
-            });
+            let shader = ShaderLibrary::new().create_shader_module(
+                device,
+                shader_source!("color_slider/color_slider_track_app.wgsl"),
+                &[],
+            );
 
             let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
                 label: Some("Vertex Buffer"),
@@ -200,7 +177,7 @@ impl<T: Float + AsPrimitive<f32> + ColorComponent + bytemuck::Pod> WgpuCanvasApp
                     module: &shader,
                     entry_point: "fs_main",
                     targets: &[wgpu::ColorTargetState {
-                        format: config.format,
+                        format: gpu.config.format,
                         blend: Some(wgpu::BlendState::REPLACE),
                         write_mask: wgpu::ColorWrites::ALL,
                     }],
@@ -223,12 +200,8 @@ impl<T: Float + AsPrimitive<f32> + ColorComponent + bytemuck::Pod> WgpuCanvasApp
                 multiview: None,
             });
 
-            Self {
-                _instance: instance,
-                surface,
-                device,
-                queue,
-                config,
+            Ok(Self {
+                gpu,
                 size,
 
                 vertex_buffer,
@@ -240,32 +213,40 @@ impl<T: Float + AsPrimitive<f32> + ColorComponent + bytemuck::Pod> WgpuCanvasApp
                 uniform: ColorSliderTrackUniform::default(),
 
                 _marker: PhantomData,
-            }
+            })
         })
     }
 
+    fn is_lost(&self) -> bool {
+        self.gpu.is_lost()
+    }
+
     fn update(&mut self, _delta_time: f64, size: &WgpuCanvasSize) {
-        if size.width > 0 && size.height > 0 {
+        if self.gpu.resize(size.width, size.height) {
             self.size = *size;
-            self.config.width = size.width;
-            self.config.height = size.height;
-            self.surface.configure(&self.device, &self.config);
             self.uniform.resolution = [size.width as f32, size.height as f32];
         }
 
-        let output = self.surface.get_current_texture().unwrap();
+        let output = match self.gpu.acquire() {
+            Ok(output) => output,
+            Err(e) => {
+                log::warn!("Failed to acquire surface texture: {:?}", e);
+                return;
+            }
+        };
 
         let view = output
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
 
-        self.queue.write_buffer(
+        self.gpu.queue.write_buffer(
             &self.uniform_buffer,
             0,
             bytemuck::cast_slice(&[self.uniform]),
         );
 
         let mut encoder = self
+            .gpu
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
                 label: Some("Render Encoder"),
@@ -297,7 +278,7 @@ impl<T: Float + AsPrimitive<f32> + ColorComponent + bytemuck::Pod> WgpuCanvasApp
             render_pass.draw_indexed(0..6, 0, 0..1);
         }
 
-        self.queue.submit(std::iter::once(encoder.finish()));
+        self.gpu.queue.submit(std::iter::once(encoder.finish()));
         output.present();
     }
 
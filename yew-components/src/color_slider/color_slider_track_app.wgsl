@@ -1,15 +1,17 @@
+#include "color.wgsl"
+
 // Vertex shader
 
 struct VertexInput {
-    @location(0) position: vec2<f32>;
-};
+    @location(0) position: vec2<f32>,
+}
 
 struct VertexOutput {
-    @builtin(position) clip_position: vec4<f32>;
-    @location(0) position: vec2<f32>;
-};
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) position: vec2<f32>,
+}
 
-@stage(vertex)
+@vertex
 fn vs_main(
     model: VertexInput,
 ) -> VertexOutput {
@@ -22,16 +24,16 @@ fn vs_main(
 // Fragment shader
 
 struct UniformColorSlider {
-    color_start: vec4<f32>;
-    color_end: vec4<f32>;
-    resolution: vec2<f32>;
-    linear: u32;
-};
+    color_start: vec4<f32>,
+    color_end: vec4<f32>,
+    resolution: vec2<f32>,
+    linear: u32,
+}
 
 @group(0) @binding(0)
 var<uniform> uniform_buffer: UniformColorSlider;
 
-@stage(fragment)
+@fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let x = floor(in.position.x * uniform_buffer.resolution.x / 4.0);
     let y = floor(in.position.y * uniform_buffer.resolution.y / 4.0);
@@ -48,11 +50,10 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
             uniform_buffer.color_end,
             in.position.x);
     } else {
-        let rgb = pow(mix(
-            pow(uniform_buffer.color_start.rgb, vec3<f32>(1.0 / 2.2)),
-            pow(uniform_buffer.color_end.rgb, vec3<f32>(1.0 / 2.2)),
-            in.position.x),
-        vec3<f32>(2.2));
+        let rgb = srgb_to_linear(mix(
+            linear_to_srgb(uniform_buffer.color_start.rgb),
+            linear_to_srgb(uniform_buffer.color_end.rgb),
+            in.position.x));
         let a = mix(
             uniform_buffer.color_start.a,
             uniform_buffer.color_end.a,
@@ -61,5 +62,5 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     };
     let color_with_background = mix(background, color.rgb, color.a);
 
-    return vec4<f32>(pow(color_with_background, vec3<f32>(1.0 / 2.2)), 1.0);
+    return vec4<f32>(linear_to_srgb(color_with_background), 1.0);
 }
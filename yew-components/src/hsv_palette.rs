@@ -11,6 +11,7 @@ use yew_style_in_rs::*;
 use yew_wgpu::*;
 
 mod hsv_palette_app;
+mod hsv_palette_renderer;
 use hsv_palette_app::*;
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -1,41 +1,8 @@
 use num::Float;
 use vek::ColorComponent;
-use wgpu::util::DeviceExt;
 use yew_wgpu::*;
 
-#[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
-struct Vertex {
-    position: [f32; 2],
-}
-impl Vertex {
-    fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
-        wgpu::VertexBufferLayout {
-            array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
-            step_mode: wgpu::VertexStepMode::Vertex,
-            attributes: &[wgpu::VertexAttribute {
-                offset: 0,
-                shader_location: 0,
-                format: wgpu::VertexFormat::Float32x2,
-            }],
-        }
-    }
-}
-const VERTICES: &[Vertex] = &[
-    Vertex {
-        position: [-1.0, -1.0],
-    },
-    Vertex {
-        position: [-1.0, 1.0],
-    },
-    Vertex {
-        position: [1.0, -1.0],
-    },
-    Vertex {
-        position: [1.0, 1.0],
-    },
-];
-const INDICES: &[u16] = &[0, 2, 1, 1, 2, 3];
+use super::hsv_palette_renderer::HsvPaletteRenderer;
 
 #[derive(Clone, PartialEq)]
 pub struct HsvPaletteProps<T: Float + ColorComponent + bytemuck::Pod> {
@@ -50,18 +17,10 @@ impl<T: Float + ColorComponent + bytemuck::Pod> Default for HsvPaletteProps<T> {
 }
 
 pub struct HsvPaletteApp<T: Float + ColorComponent + bytemuck::Pod> {
-    _instance: wgpu::Instance,
-    surface: wgpu::Surface,
-    device: wgpu::Device,
-    queue: wgpu::Queue,
-    config: wgpu::SurfaceConfiguration,
+    gpu: GpuContext,
     size: WgpuCanvasSize,
 
-    vertex_buffer: wgpu::Buffer,
-    index_buffer: wgpu::Buffer,
-    color_uniform: wgpu::Buffer,
-    color_bind_group: wgpu::BindGroup,
-    render_pipeline: wgpu::RenderPipeline,
+    renderer: HsvPaletteRenderer,
 
     color: vek::Rgba<T>,
 }
@@ -69,199 +28,67 @@ impl<T: Float + ColorComponent + bytemuck::Pod> WgpuCanvasApp for HsvPaletteApp<
     type Props = HsvPaletteProps<T>;
 
     fn new(canvas_window: WgpuCanvasWindow) -> WgpuCanvasAppCreator<Self> {
-        WgpuCanvasAppCreator::new(async move {
+        WgpuCanvasAppCreator::try_new(async move {
             let size = *canvas_window.size();
 
-            let instance = wgpu::Instance::new(wgpu::Backends::BROWSER_WEBGPU);
-            let surface = unsafe { instance.create_surface(&canvas_window) };
-            let adapter = instance
-                .request_adapter(&wgpu::RequestAdapterOptions {
-                    power_preference: wgpu::PowerPreference::default(),
-                    compatible_surface: Some(&surface),
-                    force_fallback_adapter: false,
-                })
-                .await
-                .unwrap_or_else(|| panic!("Failed to request adapter."));
-
-            let (device, queue) = adapter
-                .request_device(
-                    &wgpu::DeviceDescriptor {
-                        features: wgpu::Features::empty(),
-                        limits: wgpu::Limits::default(),
-                        label: None,
-                    },
-                    None, // Trace path
-                )
-                .await
-                .unwrap_or_else(|_| panic!("Failed to request device."));
-
-            let config = wgpu::SurfaceConfiguration {
-                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-                format: wgpu::TextureFormat::Bgra8Unorm,
-                width: size.width,
-                height: size.height,
-                present_mode: wgpu::PresentMode::Fifo,
-            };
-            surface.configure(&device, &config);
-
-            let shader = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
-                label: Some("Shader"),
-                source: wgpu::ShaderSource::Wgsl(include_str!("hsv_palette_app.wgsl").into()),
-            });
-
-            let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Vertex Buffer"),
-                contents: bytemuck::cast_slice(VERTICES),
-                usage: wgpu::BufferUsages::VERTEX,
-            });
-            let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Index Buffer"),
-                contents: bytemuck::cast_slice(INDICES),
-                usage: wgpu::BufferUsages::INDEX,
-            });
-
-            let color_uniform = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("HSV Color Buffer"),
-                contents: bytemuck::cast_slice(vek::Rgba::<f32>::zero().as_slice()),
-                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-            });
-            let color_bind_group_layout =
-                device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                    entries: &[wgpu::BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Buffer {
-                            ty: wgpu::BufferBindingType::Uniform,
-                            has_dynamic_offset: false,
-                            min_binding_size: None,
-                        },
-                        count: None,
-                    }],
-                    label: Some("color_bind_group_layout"),
-                });
-            let color_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                layout: &color_bind_group_layout,
-                entries: &[wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: color_uniform.as_entire_binding(),
-                }],
-                label: Some("color_bind_group"),
-            });
-
-            let render_pipeline_layout =
-                device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                    label: Some("Render Pipeline Layout"),
-                    bind_group_layouts: &[&color_bind_group_layout],
-                    push_constant_ranges: &[],
-                });
-
-            let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                label: Some("Render Pipeline"),
-                layout: Some(&render_pipeline_layout),
-                vertex: wgpu::VertexState {
-                    module: &shader,
-                    entry_point: "vs_main",
-                    buffers: &[Vertex::desc()],
-                },
-                fragment: Some(wgpu::FragmentState {
-                    module: &shader,
-                    entry_point: "fs_main",
-                    targets: &[wgpu::ColorTargetState {
-                        format: config.format,
-                        blend: Some(wgpu::BlendState::REPLACE),
-                        write_mask: wgpu::ColorWrites::ALL,
-                    }],
-                }),
-                primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleList,
-                    strip_index_format: None,
-                    front_face: wgpu::FrontFace::Ccw,
-                    cull_mode: Some(wgpu::Face::Back),
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    unclipped_depth: false,
-                    conservative: false,
-                },
-                depth_stencil: None,
-                multisample: wgpu::MultisampleState {
-                    count: 1,
-                    mask: !0,
-                    alpha_to_coverage_enabled: false,
+            let gpu = GpuContext::from_window(
+                &canvas_window,
+                size.width,
+                size.height,
+                &GpuContextDescriptor {
+                    backends: wgpu::Backends::BROWSER_WEBGPU,
+                    format: Some(wgpu::TextureFormat::Bgra8Unorm),
+                    ..Default::default()
                 },
-                multiview: None,
-            });
+            )
+            .await?;
+            let device = &gpu.device;
+
+            let renderer = HsvPaletteRenderer::new(device, gpu.config.format);
 
-            Self {
-                _instance: instance,
-                surface,
-                device,
-                queue,
-                config,
+            Ok(Self {
+                gpu,
                 size,
 
-                vertex_buffer,
-                index_buffer,
-                color_uniform,
-                color_bind_group,
-                render_pipeline,
+                renderer,
 
                 color: vek::Rgba::black(),
-            }
+            })
         })
     }
 
+    fn is_lost(&self) -> bool {
+        self.gpu.is_lost()
+    }
+
     fn update(&mut self, _delta_time: f64, size: &WgpuCanvasSize) {
-        if size.width > 0 && size.height > 0 {
+        if self.gpu.resize(size.width, size.height) {
             self.size = *size;
-            self.config.width = size.width;
-            self.config.height = size.height;
-            self.surface.configure(&self.device, &self.config);
         }
 
-        let output = self.surface.get_current_texture().unwrap();
+        let output = match self.gpu.acquire() {
+            Ok(output) => output,
+            Err(e) => {
+                log::warn!("Failed to acquire surface texture: {:?}", e);
+                return;
+            }
+        };
 
         let view = output
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
 
-        self.queue.write_buffer(
-            &self.color_uniform,
-            0,
-            bytemuck::cast_slice(self.color.as_slice()),
-        );
-
         let mut encoder = self
+            .gpu
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
                 label: Some("Render Encoder"),
             });
 
-        {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
-                color_attachments: &[wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.0,
-                            g: 0.0,
-                            b: 0.0,
-                            a: 0.0,
-                        }),
-                        store: true,
-                    },
-                }],
-                depth_stencil_attachment: None,
-            });
-
-            render_pass.set_pipeline(&self.render_pipeline);
-            render_pass.set_bind_group(0, &self.color_bind_group, &[]);
-            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-            render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-            render_pass.draw_indexed(0..6, 0, 0..1);
-        }
+        self.renderer
+            .render(&mut encoder, &self.gpu.queue, &view, &self.color);
 
-        self.queue.submit(std::iter::once(encoder.finish()));
+        self.gpu.queue.submit(std::iter::once(encoder.finish()));
         output.present();
     }
 
@@ -1,15 +1,17 @@
+#include "color.wgsl"
+
 // Vertex shader
 
 struct VertexInput {
-    @location(0) position: vec2<f32>;
-};
+    @location(0) position: vec2<f32>,
+}
 
 struct VertexOutput {
-    @builtin(position) clip_position: vec4<f32>;
-    @location(0) position: vec2<f32>;
-};
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) position: vec2<f32>,
+}
 
-@stage(vertex)
+@vertex
 fn vs_main(
     model: VertexInput,
 ) -> VertexOutput {
@@ -21,59 +23,18 @@ fn vs_main(
 
 // Fragment shader
 
+struct ColorUniform {
+    color: vec4<f32>,
+}
+
 @group(0) @binding(0)
-var<uniform> color: vec4<f32>;
+var<uniform> color_uniform: ColorUniform;
 
 let pi = 3.1415926536;
 let resolution: vec2<f32> = vec2<f32>(128.0, 128.0);
 
-fn hsv_to_gamma_rgb(hsv: vec3<f32>) -> vec3<f32> {
-    let c = hsv.z * hsv.y;
-    let x = c * (1.0 -  abs((hsv.x / 60.0) % 2.0 - 1.0));
-    let m = hsv.z - c;
-    let h = (hsv.x + 360.0) % 360.0;
-    if (h < 60.0) {
-        return vec3<f32>(c + m, x + m, 0.0 + m);
-    } else if (h < 120.0) {
-        return vec3<f32>(x + m, c + m, 0.0 + m);
-    } else if (h < 180.0) {
-        return vec3<f32>(0.0 + m, c + m, x + m);
-    } else if (h < 240.0) {
-        return vec3<f32>(0.0 + m, x + m, c + m);
-    } else if (h < 300.0) {
-        return vec3<f32>(x + m, 0.0 + m, c + m);
-    } else {
-        return vec3<f32>(c + m, 0.0 + m, x + m);
-    }
-}
-
-fn gamma_rgb_to_hsv(rgb: vec3<f32>) -> vec3<f32> {
-    let cmax = max(max(rgb.r, rgb.g), rgb.b);
-    let cmin = min(min(rgb.r, rgb.g), rgb.b);
-    let delta = cmax - cmin;
-    var h: f32;
-    if (delta == 0.0) {
-        h = 0.0;
-    } else if (cmax == rgb.r) {
-        h = 60.0 * ((rgb.g - rgb.b) / delta % 6.0);
-        h = (h + 360.0) % 360.0;
-    } else if (cmax == rgb.g) {
-        h = 60.0 * ((rgb.b - rgb.r) / delta + 2.0);
-    } else if (cmax == rgb.b) {
-        h = 60.0 * ((rgb.r - rgb.g) / delta + 4.0);
-    }
-    var s: f32;
-    if (cmax == 0.0) {
-        s = 0.0;
-    } else {
-        s = delta / cmax;
-    }
-    let v = cmax;
-    return vec3<f32>(h, s, v);
-}
-
 fn linear_rgb_to_hsv(rgb: vec3<f32>) -> vec3<f32> {
-    return gamma_rgb_to_hsv(pow(rgb, vec3<f32>(1.0 / 2.2)));
+    return gamma_rgb_to_hsv(linear_to_srgb(rgb));
 }
 
 fn render_ring(position: vec2<f32>) -> vec4<f32> {
@@ -89,7 +50,7 @@ fn render_ring(position: vec2<f32>) -> vec4<f32> {
     if (mask) {
         return vec4<f32>(c, 1.0);
     } else {
-        return vec4<f32>();
+        return vec4<f32>(0.0);
     }
 }
 
@@ -97,7 +58,7 @@ fn render_square(position: vec2<f32>) -> vec4<f32> {
     let a = abs(position);
     let mask = a.x < 0.8 / sqrt(2.0) && a.y < 0.8 / sqrt(2.0);
 
-    let h = linear_rgb_to_hsv(color.rgb).x;
+    let h = linear_rgb_to_hsv(color_uniform.color.rgb).x;
     let s = (position.x / (0.8 / sqrt(2.0)) + 1.0) / 2.0;
     let v = (position.y / (0.8 / sqrt(2.0)) + 1.0) / 2.0;
     let c = hsv_to_gamma_rgb(vec3<f32>(h, s, v));
@@ -105,7 +66,7 @@ fn render_square(position: vec2<f32>) -> vec4<f32> {
     if (mask) {
         return vec4<f32>(c, 1.0);
     } else {
-        return vec4<f32>();
+        return vec4<f32>(0.0);
     }
 }
 
@@ -115,10 +76,10 @@ fn render(position: vec2<f32>) -> vec4<f32> {
     return mix(ring, square, square.a);
 }
 
-@stage(fragment)
+@fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let pixel = vec2<f32>(2.0, 2.0) / resolution;
-    var color = vec4<f32>();
+    var color = vec4<f32>(0.0);
     color = color
         + render(in.position + vec2<f32>(-pixel.x / 8.0, -pixel.y * 3.0 / 8.0));
     color = color
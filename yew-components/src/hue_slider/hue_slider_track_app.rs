@@ -1,3 +1,4 @@
+use shader_library::{shader_source, ShaderLibrary};
 use wgpu::util::DeviceExt;
 use yew_wgpu::*;
 
@@ -36,11 +37,7 @@ const VERTICES: &[Vertex] = &[
 const INDICES: &[u16] = &[0, 2, 1, 1, 2, 3];
 
 pub struct HueSliderTrackApp {
-    _instance: wgpu::Instance,
-    surface: wgpu::Surface,
-    device: wgpu::Device,
-    queue: wgpu::Queue,
-    config: wgpu::SurfaceConfiguration,
+    gpu: GpuContext,
     size: WgpuCanvasSize,
 
     vertex_buffer: wgpu::Buffer,
@@ -51,45 +48,27 @@ impl WgpuCanvasApp for HueSliderTrackApp {
     type Props = ();
 
     fn new(canvas_window: WgpuCanvasWindow) -> WgpuCanvasAppCreator<Self> {
-        WgpuCanvasAppCreator::new(async move {
+        WgpuCanvasAppCreator::try_new(async move {
             let size = *canvas_window.size();
 
-            let instance = wgpu::Instance::new(wgpu::Backends::BROWSER_WEBGPU);
-            let surface = unsafe { instance.create_surface(&canvas_window) };
-            let adapter = instance
-                .request_adapter(&wgpu::RequestAdapterOptions {
-                    power_preference: wgpu::PowerPreference::default(),
-                    compatible_surface: Some(&surface),
-                    force_fallback_adapter: false,
-                })
-                .await
-                .unwrap_or_else(|| panic!("Failed to request adapter."));
-
-            let (device, queue) = adapter
-                .request_device(
-                    &wgpu::DeviceDescriptor {
-                        features: wgpu::Features::empty(),
-                        limits: wgpu::Limits::default(),
-                        label: None,
-                    },
-                    None, // Trace path
-                )
-                .await
-                .unwrap_or_else(|_| panic!("Failed to request device."));
-
-            let config = wgpu::SurfaceConfiguration {
-                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-                format: wgpu::TextureFormat::Bgra8Unorm,
-                width: size.width,
-                height: size.height,
-                present_mode: wgpu::PresentMode::Fifo,
-            };
-            surface.configure(&device, &config);
-
-            let shader = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
-                label: Some("Shader"),
-                source: wgpu::ShaderSource::Wgsl(include_str!("hue_slider_track_app.wgsl").into()),
-            });
+            let gpu = GpuContext::from_window(
+                &canvas_window,
+                size.width,
+                size.height,
+                &GpuContextDescriptor {
+                    backends: wgpu::Backends::BROWSER_WEBGPU,
+                    format: Some(wgpu::TextureFormat::Bgra8Unorm),
+                    ..Default::default()
+                },
+            )
+            .await?;
+            let device = &gpu.device;
+
+            let shader = ShaderLibrary::new().create_shader_module(
+                device,
+                shader_source!("hue_slider/hue_slider_track_app.wgsl"),
+                &[],
+            );
 
             let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
                 label: Some("Vertex Buffer"),
@@ -121,7 +100,7 @@ impl WgpuCanvasApp for HueSliderTrackApp {
                     module: &shader,
                     entry_point: "fs_main",
                     targets: &[wgpu::ColorTargetState {
-                        format: config.format,
+                        format: gpu.config.format,
                         blend: Some(wgpu::BlendState::REPLACE),
                         write_mask: wgpu::ColorWrites::ALL,
                     }],
@@ -144,36 +123,40 @@ impl WgpuCanvasApp for HueSliderTrackApp {
                 multiview: None,
             });
 
-            Self {
-                _instance: instance,
-                surface,
-                device,
-                queue,
-                config,
+            Ok(Self {
+                gpu,
                 size,
 
                 vertex_buffer,
                 index_buffer,
                 render_pipeline,
-            }
+            })
         })
     }
 
+    fn is_lost(&self) -> bool {
+        self.gpu.is_lost()
+    }
+
     fn update(&mut self, _delta_time: f64, size: &WgpuCanvasSize) {
-        if size.width > 0 && size.height > 0 {
+        if self.gpu.resize(size.width, size.height) {
             self.size = *size;
-            self.config.width = size.width;
-            self.config.height = size.height;
-            self.surface.configure(&self.device, &self.config);
         }
 
-        let output = self.surface.get_current_texture().unwrap();
+        let output = match self.gpu.acquire() {
+            Ok(output) => output,
+            Err(e) => {
+                log::warn!("Failed to acquire surface texture: {:?}", e);
+                return;
+            }
+        };
 
         let view = output
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
 
         let mut encoder = self
+            .gpu
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
                 label: Some("Render Encoder"),
@@ -204,7 +187,7 @@ impl WgpuCanvasApp for HueSliderTrackApp {
             render_pass.draw_indexed(0..6, 0, 0..1);
         }
 
-        self.queue.submit(std::iter::once(encoder.finish()));
+        self.gpu.queue.submit(std::iter::once(encoder.finish()));
         output.present();
     }
 }
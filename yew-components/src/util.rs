@@ -2,6 +2,24 @@ use num::Float;
 use num_traits::FromPrimitive;
 use vek::ColorComponent;
 
+pub(crate) fn linear_to_srgb<T: Float + FromPrimitive>(c: T) -> T {
+    if c <= T::from_f64(0.0031308).unwrap() {
+        c * T::from_f64(12.92).unwrap()
+    } else {
+        T::from_f64(1.055).unwrap() * c.powf(T::from_f64(1.0 / 2.4).unwrap())
+            - T::from_f64(0.055).unwrap()
+    }
+}
+
+pub(crate) fn srgb_to_linear<T: Float + FromPrimitive>(c: T) -> T {
+    if c <= T::from_f64(0.04045).unwrap() {
+        c / T::from_f64(12.92).unwrap()
+    } else {
+        ((c + T::from_f64(0.055).unwrap()) / T::from_f64(1.055).unwrap())
+            .powf(T::from_f64(2.4).unwrap())
+    }
+}
+
 pub(crate) fn gamma_rgb_to_hsl<T: Float + FromPrimitive + ColorComponent>(
     rgb: vek::Rgb<T>,
 ) -> vek::Vec3<T> {
@@ -36,9 +54,9 @@ pub(crate) fn linear_rgb_to_hsl<T: Float + FromPrimitive + ColorComponent>(
     rgb: vek::Rgb<T>,
 ) -> vek::Vec3<T> {
     return gamma_rgb_to_hsl(vek::Rgb::new(
-        rgb.r.powf(T::from_f64(1.0 / 2.2).unwrap()),
-        rgb.g.powf(T::from_f64(1.0 / 2.2).unwrap()),
-        rgb.b.powf(T::from_f64(1.0 / 2.2).unwrap()),
+        linear_to_srgb(rgb.r),
+        linear_to_srgb(rgb.g),
+        linear_to_srgb(rgb.b),
     ));
 }
 
@@ -73,9 +91,9 @@ pub(crate) fn linear_rgb_to_hsv<T: Float + FromPrimitive + ColorComponent>(
     rgb: vek::Rgb<T>,
 ) -> vek::Vec3<T> {
     return gamma_rgb_to_hsv(vek::Rgb::new(
-        rgb.r.powf(T::from_f64(1.0 / 2.2).unwrap()),
-        rgb.g.powf(T::from_f64(1.0 / 2.2).unwrap()),
-        rgb.b.powf(T::from_f64(1.0 / 2.2).unwrap()),
+        linear_to_srgb(rgb.r),
+        linear_to_srgb(rgb.g),
+        linear_to_srgb(rgb.b),
     ));
 }
 
@@ -108,8 +126,8 @@ pub(crate) fn hsv_to_linear_rgb<T: Float + FromPrimitive + ColorComponent>(
 ) -> vek::Rgb<T> {
     let gamma_rgb = hsv_to_gamma_rgb(vek::Vec3::new(hsv.x, hsv.y, hsv.z));
     return vek::Rgb::new(
-        gamma_rgb.r.powf(T::from_f64(2.2).unwrap()),
-        gamma_rgb.g.powf(T::from_f64(2.2).unwrap()),
-        gamma_rgb.b.powf(T::from_f64(2.2).unwrap()),
+        srgb_to_linear(gamma_rgb.r),
+        srgb_to_linear(gamma_rgb.g),
+        srgb_to_linear(gamma_rgb.b),
     );
 }
@@ -15,11 +15,7 @@ impl Default for MyCanvasAppProps {
 }
 
 pub struct MyCanvasApp {
-    _instance: wgpu::Instance,
-    surface: wgpu::Surface,
-    device: wgpu::Device,
-    queue: wgpu::Queue,
-    config: wgpu::SurfaceConfiguration,
+    gpu: GpuContext,
     size: WgpuCanvasSize,
 
     triangle_pass: TrianglePass,
@@ -28,70 +24,55 @@ impl WgpuCanvasApp for MyCanvasApp {
     type Props = MyCanvasAppProps;
 
     fn new(canvas_window: WgpuCanvasWindow) -> WgpuCanvasAppCreator<Self> {
-        WgpuCanvasAppCreator::new(async move {
+        WgpuCanvasAppCreator::try_new(async move {
             let size = *canvas_window.size();
 
-            let instance = wgpu::Instance::new(wgpu::Backends::BROWSER_WEBGPU);
-            let surface = unsafe { instance.create_surface(&canvas_window) };
-            let adapter = instance
-                .request_adapter(&wgpu::RequestAdapterOptions {
-                    power_preference: wgpu::PowerPreference::default(),
-                    compatible_surface: Some(&surface),
-                    force_fallback_adapter: false,
-                })
-                .await
-                .unwrap_or_else(|| panic!("Failed to request adapter."));
-
-            let (device, queue) = adapter
-                .request_device(
-                    &wgpu::DeviceDescriptor {
-                        features: wgpu::Features::empty(),
-                        limits: wgpu::Limits::default(),
-                        label: None,
-                    },
-                    None, // Trace path
-                )
-                .await
-                .unwrap_or_else(|_| panic!("Failed to request device."));
-
-            let config = wgpu::SurfaceConfiguration {
-                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-                format: wgpu::TextureFormat::Bgra8Unorm,
-                width: size.width,
-                height: size.height,
-                present_mode: wgpu::PresentMode::Fifo,
-            };
-            surface.configure(&device, &config);
-
-            let triangle_pass = TrianglePass::new(&device, &config);
-
-            Self {
-                _instance: instance,
-                surface,
-                device,
-                queue,
-                config,
+            let gpu = GpuContext::from_window(
+                &canvas_window,
+                size.width,
+                size.height,
+                &GpuContextDescriptor {
+                    backends: wgpu::Backends::BROWSER_WEBGPU,
+                    format: Some(wgpu::TextureFormat::Bgra8Unorm),
+                    ..Default::default()
+                },
+            )
+            .await?;
+            let device = &gpu.device;
+
+            let triangle_pass = TrianglePass::new(device, &gpu.config);
+
+            Ok(Self {
+                gpu,
                 size,
                 triangle_pass,
-            }
+            })
         })
     }
 
+    fn is_lost(&self) -> bool {
+        self.gpu.is_lost()
+    }
+
     fn update(&mut self, _delta_time: f64, size: &WgpuCanvasSize) {
-        if size.width > 0 && size.height > 0 {
+        if self.gpu.resize(size.width, size.height) {
             self.size = *size;
-            self.config.width = size.width;
-            self.config.height = size.height;
-            self.surface.configure(&self.device, &self.config);
         }
 
-        let output = self.surface.get_current_texture().unwrap();
+        let output = match self.gpu.acquire() {
+            Ok(output) => output,
+            Err(e) => {
+                log::warn!("Failed to acquire surface texture: {:?}", e);
+                return;
+            }
+        };
 
         let view = output
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
 
         let mut encoder = self
+            .gpu
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
                 label: Some("Render Encoder"),
@@ -99,7 +80,7 @@ impl WgpuCanvasApp for MyCanvasApp {
 
         self.triangle_pass.render(&mut encoder, &view);
 
-        self.queue.submit(std::iter::once(encoder.finish()));
+        self.gpu.queue.submit(std::iter::once(encoder.finish()));
         output.present();
     }
 
@@ -1,16 +1,16 @@
 // Vertex shader
 
 struct VertexInput {
-    @location(0) position: vec3<f32>;
-    @location(1) color: vec3<f32>;
-};
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec3<f32>,
+}
 
 struct VertexOutput {
-    @builtin(position) clip_position: vec4<f32>;
-    @location(0) color: vec3<f32>;
-};
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec3<f32>,
+}
 
-@stage(vertex)
+@vertex
 fn vs_main(
     model: VertexInput,
 ) -> VertexOutput {
@@ -22,7 +22,7 @@ fn vs_main(
 
 // Fragment shader
 
-@stage(fragment)
+@fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     return vec4<f32>(pow(in.color, vec3<f32>(1.0/2.2)), 1.0);
 }
@@ -5,3 +5,5 @@ mod wgpu_canvas;
 mod wgpu_canvas_app;
 pub use wgpu_canvas::*;
 pub use wgpu_canvas_app::*;
+
+pub use gpu_context::{GpuContext, GpuContextDescriptor, GpuContextError};
@@ -13,17 +13,24 @@ enum AppAction<App: WgpuCanvasApp + 'static> {
     Render(f64),
     StateChanged(App::Props),
     AppInitialized(Rc<RefCell<App>>, WgpuCanvasSize),
+    AppFailed(String),
     #[cfg(web_sys_unstable_apis)]
     AppResized(WgpuCanvasSize),
 }
 struct AppReducer<App: WgpuCanvasApp + 'static> {
     app: Option<Rc<RefCell<App>>>,
+    // Message shown instead of the canvas when the app could not be created.
+    error: Option<String>,
+    // Set when the device of the app was lost until the app is created again.
+    lost: bool,
     size: WgpuCanvasSize,
 }
 impl<App: WgpuCanvasApp + 'static> Default for AppReducer<App> {
     fn default() -> Self {
         Self {
             app: None,
+            error: None,
+            lost: false,
             size: WgpuCanvasSize::default(),
         }
     }
@@ -34,6 +41,16 @@ impl<App: WgpuCanvasApp + 'static> Reducible for AppReducer<App> {
         match action {
             Self::Action::Render(delta) => {
                 if let Some(app) = &self.app {
+                    // Every GPU object of the app belongs to the lost device, so the app is dropped.
+                    if app.borrow().is_lost() {
+                        return Self {
+                            app: None,
+                            error: None,
+                            lost: true,
+                            size: self.size,
+                        }
+                        .into();
+                    }
                     app.borrow_mut().update(delta, &self.size);
                 }
                 self
@@ -46,12 +63,23 @@ impl<App: WgpuCanvasApp + 'static> Reducible for AppReducer<App> {
             }
             Self::Action::AppInitialized(app, size) => Self {
                 app: Some(app),
+                error: None,
+                lost: false,
                 size,
             }
             .into(),
+            Self::Action::AppFailed(error) => Self {
+                app: None,
+                error: Some(error),
+                lost: false,
+                size: self.size,
+            }
+            .into(),
             #[cfg(web_sys_unstable_apis)]
             Self::Action::AppResized(size) => Self {
                 app: self.app.clone(),
+                error: self.error.clone(),
+                lost: self.lost,
                 size,
             }
             .into(),
@@ -451,11 +479,10 @@ pub fn wgpu_canvas<App: WgpuCanvasApp + 'static>(props: &Props<App>) -> Html {
             let reducer = reducer.clone();
             use_async_once({
                 || async move {
-                    Rc::new(RefCell::new(
-                        App::new(WgpuCanvasWindow::new(id, reducer.size))
-                            .future
-                            .await,
-                    ))
+                    App::new(WgpuCanvasWindow::new(id, reducer.size))
+                        .future
+                        .await
+                        .map(|app| Rc::new(RefCell::new(app)))
                 }
             })
         };
@@ -463,11 +490,17 @@ pub fn wgpu_canvas<App: WgpuCanvasApp + 'static>(props: &Props<App>) -> Html {
             let reducer = reducer.clone();
             let props = props.clone();
             use_effect_with_deps(
-                move |handle: &UseAsyncOnceHandle<Rc<RefCell<App>>>| {
-                    if let UseAsyncOnceState::Ready(app) = handle.state() {
-                        reducer.dispatch(AppAction::AppInitialized(app.clone(), reducer.size));
-                        reducer.dispatch(AppAction::StateChanged(props));
-                        reducer.dispatch(AppAction::Render(0.0));
+                move |handle: &UseAsyncOnceHandle<Result<Rc<RefCell<App>>, String>>| {
+                    match handle.state() {
+                        UseAsyncOnceState::Ready(Ok(app)) => {
+                            reducer.dispatch(AppAction::AppInitialized(app.clone(), reducer.size));
+                            reducer.dispatch(AppAction::StateChanged(props));
+                            reducer.dispatch(AppAction::Render(0.0));
+                        }
+                        UseAsyncOnceState::Ready(Err(error)) => {
+                            reducer.dispatch(AppAction::AppFailed(error.clone()));
+                        }
+                        UseAsyncOnceState::Pending => {}
                     }
                     || ()
                 },
@@ -476,6 +509,35 @@ pub fn wgpu_canvas<App: WgpuCanvasApp + 'static>(props: &Props<App>) -> Html {
         }
     }
 
+    // Recreate App after its device was lost
+    {
+        let reducer = reducer.clone();
+        let props = props.clone();
+        use_effect_with_deps(
+            move |lost: &bool| {
+                if *lost {
+                    log::warn!("GPU device lost, recreating the canvas app.");
+                    wasm_bindgen_futures::spawn_local(async move {
+                        match App::new(WgpuCanvasWindow::new(id, reducer.size))
+                            .future
+                            .await
+                        {
+                            Ok(app) => {
+                                let app = Rc::new(RefCell::new(app));
+                                reducer.dispatch(AppAction::AppInitialized(app, reducer.size));
+                                reducer.dispatch(AppAction::StateChanged(props));
+                                reducer.dispatch(AppAction::Render(0.0));
+                            }
+                            Err(error) => reducer.dispatch(AppAction::AppFailed(error)),
+                        }
+                    });
+                }
+                || ()
+            },
+            reducer.lost,
+        );
+    }
+
     // Register Animation Callback
     {
         let reducer = reducer.clone();
@@ -511,9 +573,18 @@ pub fn wgpu_canvas<App: WgpuCanvasApp + 'static>(props: &Props<App>) -> Html {
         );
     }
 
+    // The canvas stays mounted but hidden so the resize observer keeps its element.
+    let error = reducer.error.clone();
+    let hidden = error.is_some();
+
     html! {
+        <>
+        if let Some(error) = error {
+            <div class="yew-wgpu-error">{error}</div>
+        }
         <canvas
             data-raw-handle={id.to_string()}
+            {hidden}
             width={reducer.size.width.to_string()}
             height={reducer.size.height.to_string()}
             ref={canvas_ref}
@@ -615,5 +686,6 @@ pub fn wgpu_canvas<App: WgpuCanvasApp + 'static>(props: &Props<App>) -> Html {
             {ontransitionrun}
             {ontransitionstart}
             />
+        </>
     }
 }
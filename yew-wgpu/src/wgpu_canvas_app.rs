@@ -1,6 +1,8 @@
 use raw_window_handle::{HasRawWindowHandle, RawWindowHandle, WebHandle};
 use std::{future::Future, pin::Pin};
 
+use gpu_context::GpuContextError;
+
 #[derive(Clone, Copy)]
 pub struct WgpuCanvasSize {
     pub width: u32,
@@ -39,12 +41,25 @@ unsafe impl HasRawWindowHandle for WgpuCanvasWindow {
 }
 
 pub struct WgpuCanvasAppCreator<App> {
-    pub(crate) future: Pin<Box<dyn Future<Output = App> + 'static>>,
+    // Resolves to the message shown instead of the canvas when the creation failed.
+    pub(crate) future: Pin<Box<dyn Future<Output = Result<App, String>> + 'static>>,
 }
-impl<App> WgpuCanvasAppCreator<App> {
+impl<App: 'static> WgpuCanvasAppCreator<App> {
     pub fn new(creation: impl Future<Output = App> + 'static) -> Self {
         Self {
-            future: Box::pin(creation),
+            future: Box::pin(async move { Ok(creation.await) }),
+        }
+    }
+
+    // Show a readable message instead of the canvas when e.g. WebGPU is not available.
+    pub fn try_new(creation: impl Future<Output = Result<App, GpuContextError>> + 'static) -> Self {
+        Self {
+            future: Box::pin(async move {
+                creation.await.map_err(|e| {
+                    log::error!("Failed to create canvas app: {}", e);
+                    format!("Failed to initialize WebGPU: {}", e)
+                })
+            }),
         }
     }
 }
@@ -69,4 +84,8 @@ pub trait WgpuCanvasApp: Sized {
     fn new(canvas_window: WgpuCanvasWindow) -> WgpuCanvasAppCreator<Self>;
     fn update(&mut self, delta_time: f64, size: &WgpuCanvasSize);
     fn update_props(&mut self, _props: &Self::Props) {}
+    // The canvas creates the app again with `new` once its device is lost.
+    fn is_lost(&self) -> bool {
+        false
+    }
 }